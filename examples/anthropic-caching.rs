@@ -0,0 +1,43 @@
+use anylm::{AiChunk, CacheControl, Completions, Message, Proxy};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let api_key = std::env::var("ANTHROPIC_API_KEY")?;
+    let manual = "The very long product manual... ".repeat(500);
+
+    // the cached system prompt:
+    let system = Message::system(vec![
+        "You are a support assistant. Answer by the manual below.".into(),
+        manual.into(),
+    ])
+    .cache_control(CacheControl::ephemeral().ttl("1h"));
+
+    let request = Completions::anthropic(api_key, "claude-opus-4-6")
+        .proxy(Proxy::all("socks5://127.0.0.1:1080")?)
+        .messages(vec![system]);
+
+    for question in ["How to reset the device?", "How to update the firmware?"] {
+        // send request (the second one reads the system prompt from cache):
+        let mut response = request
+            .clone()
+            .user_message(vec![question.into()])
+            .send()
+            .await?;
+
+        // read response stream:
+        while let Some(chunk) = response.next().await {
+            match chunk? {
+                AiChunk::Text { text } => eprint!("{text}"),
+                AiChunk::Usage { usage } => println!(
+                    "\n[cache read: {}, cache write: {}]",
+                    usage.cache_read_tokens, usage.cache_write_tokens
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use anylm::{Completions, prelude::*};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let api_key = std::env::var("ANTHROPIC_API_KEY")?;
+
+    // prepare request:
+    let request = Completions::anthropic(api_key, "claude-opus-4-6")
+        .proxy(Proxy::all("socks5://127.0.0.1:1080")?)
+        .system_message(vec!["You are a helpful assistant.".into()])
+        .user_message(vec!["Hello, how are you doing?".into()]);
+
+    // count tokens (by the API endpoint):
+    let count = request.count_tokens().await?;
+    println!("Input tokens: {count}");
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anylm::{Completions, DiskCache, Embeddings, MemoryCache};
+use std::{sync::Arc, time::Duration};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // the on-disk cache with 1 day lifetime:
+    let cache = Arc::new(DiskCache::new(".cache/anylm").ttl(Duration::from_secs(86400)));
+
+    let request = Completions::lmstudio("", "qwen/qwen2.5-vl-7b")
+        .temperature(0.0)
+        .response_cache(cache)
+        .user_message(vec!["Hello, how are you doing?".into()]);
+
+    // the second response is read from cache:
+    for _ in 0..2 {
+        let response = request.send().await?;
+        let cached = response.is_cached();
+        println!("[cached: {cached}] {}", response.text().await?);
+    }
+
+    // the embeddings are cached per input:
+    let cache = Arc::new(MemoryCache::new(1000));
+
+    let embeddings = Embeddings::lmstudio("", "nomic-ai/nomic-embed-text-v1.5")
+        .response_cache(cache.clone())
+        .input("Hello, world!")
+        .send()
+        .await?;
+    println!("cache hits: {}", embeddings.cache_hits);
+
+    let embeddings = Embeddings::lmstudio("", "nomic-ai/nomic-embed-text-v1.5")
+        .response_cache(cache)
+        .input("Hello, world!")
+        .input("How are you?")
+        .send()
+        .await?;
+    println!("cache hits: {}", embeddings.cache_hits);
+
+    Ok(())
+}
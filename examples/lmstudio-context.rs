@@ -0,0 +1,31 @@
+use anylm::{AiChunk, Completions, Summarize};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // the summarizer model:
+    let summarizer = Completions::lmstudio("", "mistralai/ministral-3-3b").max_tokens(512);
+
+    // send request:
+    let mut response = Completions::lmstudio("", "qwen/qwen2.5-vl-7b")
+        .context_window(4096)
+        .max_tokens(1024)
+        .context_strategy(Summarize::new(summarizer, 4))
+        .system_message(vec!["You are a helpful assistant.".into()])
+        .user_message(vec!["My name is John, I'm 30 years old.".into()])
+        .assistant_message(vec!["Nice to meet you, John!".into()])
+        .user_message(vec!["What's my name?".into()])
+        .send()
+        .await?;
+
+    // read response stream:
+    while let Some(chunk) = response.next().await {
+        if let AiChunk::Text { text } = chunk? {
+            eprint!("{text}");
+        }
+    }
+    println!();
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anylm::{Completions, Conversation, ConversationStore, FileStore};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let store = FileStore::jsonl("conversations");
+
+    // create conversation:
+    let mut conv = Conversation::new()
+        .system_message(vec!["You are a helpful assistant.".into()])
+        .user_message(vec!["My name is John, what's your name?".into()]);
+
+    // send request:
+    let answer = Completions::lmstudio("", "qwen/qwen2.5-vl-7b")
+        .conversation(&conv)
+        .send()
+        .await?
+        .text()
+        .await?;
+    println!("{answer}");
+
+    // save conversation:
+    conv.add_assistant_message(vec![answer.into()]);
+    store.save(&conv).await?;
+
+    // fork the dialog and ask another question:
+    let fork = conv.fork().user_message(vec!["What's my name?".into()]);
+
+    let answer = Completions::lmstudio("", "qwen/qwen2.5-vl-7b")
+        .conversation(&fork)
+        .send()
+        .await?
+        .text()
+        .await?;
+    println!("{answer}");
+
+    // load the original conversation:
+    let loaded = store.load(&conv.id).await?.unwrap();
+    assert_eq!(loaded.len(), 3);
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anylm::{AiChunk, Completions};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // send request:
+    let mut response = Completions::lmstudio("", "qwen/qwen2.5-vl-7b")
+        .user_message(vec![
+            "Is this review positive or negative? Answer with one word: 'I loved it!'".into(),
+        ])
+        .logprobs(3)
+        .send()
+        .await?;
+
+    // read response stream:
+    while let Some(chunk) = response.next().await {
+        if let AiChunk::Logprobs { logprobs } = chunk? {
+            for lp in logprobs {
+                println!("{:?}: {:.3}", lp.token, lp.probability());
+
+                for top in lp.top_logprobs {
+                    println!("    {:?}: {:.3}", top.token, top.probability());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -28,21 +28,19 @@ async fn main() -> Result<()> {
             AiChunk::Text { text } => {
                 eprint!("{text}");
             }
-            AiChunk::Tool { name, json_str } => {
+            AiChunk::Tool { name, json_str, .. } => {
                 tool_calls.push((name, json_str));
             }
+            _ => {}
         }
     }
     println!();
 
     // handle tool calls:
     for (name, json_str) in tool_calls {
-        match name.as_ref() {
-            "weather" => {
-                let location: LocationData = serde_json::from_str(&json_str)?;
-                println!("Tool call: {location:#?}");
-            }
-            _ => {}
+        if name == "weather" {
+            let location: LocationData = serde_json::from_str(&json_str)?;
+            println!("Tool call: {location:#?}");
         }
     }
 
@@ -0,0 +1,37 @@
+use anylm::{AiChunk, Completions, Schema, Tool, ToolChoice};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // send request:
+    let mut response = Completions::lmstudio("", "qwen/qwen2.5-vl-7b")
+        .user_message(vec!["What's the weather like in London and Paris?".into()])
+        .tool(
+            Tool::new("weather", "Search weather by location")
+                .required_property("location", Schema::string("The location")),
+        )
+        .tool_choice(ToolChoice::Required)
+        .parallel_tool_calls(true)
+        .tool_deltas(true)
+        .send()
+        .await?;
+
+    // read response stream:
+    while let Some(chunk) = response.next().await {
+        match chunk? {
+            AiChunk::Text { text } => {
+                eprint!("{text}");
+            }
+            AiChunk::ToolDelta { id, partial_json } => {
+                eprintln!("[{id}] ..{partial_json}");
+            }
+            AiChunk::Tool { id, name, json_str } => {
+                println!("[{id}] Tool call: {name}({json_str})");
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
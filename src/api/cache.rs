@@ -0,0 +1,35 @@
+use crate::prelude::*;
+
+/// The prompt cache breakpoint (Anthropic `cache_control`)
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct CacheControl {
+    /// The cache type (only `ephemeral` is supported now)
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The cache lifetime (`5m` or `1h`)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<String>,
+}
+
+impl Default for CacheControl {
+    fn default() -> Self {
+        Self::ephemeral()
+    }
+}
+
+impl CacheControl {
+    /// Creates a new ephemeral cache breakpoint (5 minutes by default)
+    pub fn ephemeral() -> Self {
+        Self {
+            kind: str!("ephemeral"),
+            ttl: None,
+        }
+    }
+
+    /// Sets the cache lifetime (`5m` or `1h`)
+    pub fn ttl(mut self, ttl: impl Into<String>) -> Self {
+        self.ttl.replace(ttl.into());
+        self
+    }
+}
@@ -1,22 +1,72 @@
-use super::*;
-use crate::{AiOptions, prelude::*};
-use atoman::{Stream, StreamExt};
+use super::{response_cache::cache_key, *};
+use crate::{
+    AiOptions, Tokenizer,
+    cassette::{self, Cassette},
+    chunk::ResponseError,
+    media,
+    prelude::*,
+};
+use atoman::Stream;
 use reqwest::{Client, Proxy, header};
-use std::time::Duration;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tokio::sync::mpsc;
 
+/// The synthetic tool name used for structured output in `SchemaMode::Tool`
+pub const SCHEMA_TOOL_NAME: &str = "json_response";
+
 /// The completions response stream reader
 #[derive(Debug)]
 pub struct AiStream {
     rx: mpsc::UnboundedReceiver<Result<AiChunk>>,
     handle: tokio::task::JoinHandle<()>,
+    cached: bool,
+    response_id: Arc<Mutex<Option<String>>>,
 }
 
 impl AiStream {
+    /// Creates a new stream from the cached chunks
+    fn from_cache(chunks: Vec<AiChunk>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        for chunk in chunks {
+            tx.send(Ok(chunk)).ok();
+        }
+
+        Self {
+            rx,
+            handle: tokio::spawn(async {}),
+            cached: true,
+            response_id: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns true if the response is read from the response cache
+    pub fn is_cached(&self) -> bool {
+        self.cached
+    }
+
+    /// Returns the server-side response ID (OpenAI Responses, for the `previous_response_id`)
+    pub fn response_id(&self) -> Option<String> {
+        self.response_id.lock().ok()?.clone()
+    }
+
     /// Read a next completions response chunk
     pub async fn next(&mut self) -> Option<Result<AiChunk>> {
         self.rx.recv().await
     }
+
+    /// Reads the full response text
+    pub async fn text(mut self) -> Result<String> {
+        let mut text = String::new();
+        while let Some(chunk) = self.next().await {
+            if let AiChunk::Text { text: part } = chunk? {
+                text.push_str(&part);
+            }
+        }
+        Ok(text)
+    }
 }
 
 impl Drop for AiStream {
@@ -29,8 +79,45 @@ impl Drop for AiStream {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum AiChunk {
-    Text { text: String },
-    Tool { name: String, json_str: String },
+    Text {
+        text: String,
+    },
+    Tool {
+        id: String,
+        name: String,
+        json_str: String,
+    },
+    ToolDelta {
+        id: String,
+        partial_json: String,
+    },
+    Logprobs {
+        logprobs: Vec<Logprob>,
+    },
+    Image {
+        mime: String,
+        #[serde(with = "crate::media::base64_bytes")]
+        bytes: Bytes,
+    },
+    Usage {
+        usage: Usage,
+    },
+    Stats {
+        stats: LmStats,
+    },
+}
+
+impl AiChunk {
+    /// Saves the image chunk into file (the extension is added by MIME type if the path doesn't have it)
+    pub fn save_image(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Option<std::path::PathBuf>> {
+        match self {
+            Self::Image { mime, bytes } => Ok(Some(media::save(path, mime, bytes)?)),
+            _ => Ok(None),
+        }
+    }
 }
 
 /// The LM API chat completions request
@@ -62,12 +149,113 @@ pub struct Completions {
     pub max_tokens: i32,
     /// The AI generation temperature
     pub temperature: f32,
+    /// The nucleus sampling probability
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// The top-K sampling (Anthropic, Google, LM Studio, OpenRouter)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// The stop sequences
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// The sampling seed (OpenAI, Google)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// The presence penalty (OpenAI, Google)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    /// The frequency penalty (OpenAI, Google)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    /// The token logit biases (OpenAI)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub logit_bias: HashMap<String, f32>,
+    /// The minimum token probability (LM Studio, OpenRouter)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+    /// The repeat penalty (LM Studio, OpenRouter)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// The end-user identifier (OpenAI, Anthropic)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// The request metadata (OpenAI)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+    /// The output modalities (`text`, `image`, `audio`)
+    #[serde(skip)]
+    pub modalities: Vec<String>,
+    /// The log probabilities with N most likely alternatives (OpenAI, Google)
+    #[serde(skip)]
+    pub logprobs: Option<u32>,
+    /// The prompt cache routing key (OpenAI)
+    #[serde(skip)]
+    pub prompt_cache_key: Option<String>,
+    /// The cached contents name (Google)
+    #[serde(skip)]
+    pub cached_content: Option<String>,
+    /// Requests the tokens usage chunk (OpenAI, the other APIs report it always)
+    #[serde(skip)]
+    pub include_usage: bool,
+    /// Requests the generation stats (LM Studio native API)
+    #[serde(skip)]
+    pub stats: bool,
+    /// The JIT-loaded model TTL, the idle time before auto-unloading (LM Studio)
+    #[serde(skip)]
+    pub ttl: Option<Duration>,
+    /// The previous response ID to continue the server-side conversation (OpenAI Responses)
+    #[serde(skip)]
+    pub previous_response_id: Option<String>,
+    /// Stores the response on the server (OpenAI Responses)
+    #[serde(skip)]
+    pub store: Option<bool>,
+    /// The extra request body fields (overrides any other fields)
+    #[serde(skip)]
+    pub extra_body: json::Map<String, JsonValue>,
     /// The response schema
     #[serde(skip_serializing_if = "Option::is_none")]
     pub schema: Option<Schema>,
+    /// The structured output mode
+    #[serde(skip)]
+    pub schema_mode: SchemaMode,
     /// The tool calls
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<Tool>,
+    /// The tool choice mode
+    #[serde(skip)]
+    pub tool_choice: Option<ToolChoice>,
+    /// The parallel tool calls toggle
+    #[serde(skip)]
+    pub parallel_tool_calls: Option<bool>,
+    /// Emits the partial tool call arguments (`AiChunk::ToolDelta`)
+    #[serde(skip)]
+    pub tool_deltas: bool,
+    /// The responses cache
+    #[serde(skip)]
+    pub response_cache: Option<Arc<dyn ResponseCache>>,
+    /// The HTTP exchanges recorder/player
+    #[serde(skip)]
+    pub cassette: Option<Arc<Cassette>>,
+    /// The custom context tokenizer
+    #[serde(skip)]
+    pub tokenizer: Option<Arc<dyn Tokenizer>>,
+    /// The model context window size (the context isn't trimmed if not set)
+    #[serde(skip)]
+    pub context_window: Option<usize>,
+    /// The context window management strategy (`SlidingWindow` by default)
+    #[serde(skip)]
+    pub context_strategy: Option<Arc<dyn ContextStrategy>>,
     /// The summary tokens count
     pub tokens_count: usize,
 }
@@ -93,9 +281,39 @@ impl Completions {
             messages: Vec::new(),
             max_tokens: if kind.is_anthropic() { 8096 } else { -1 },
             temperature: 0.7,
+            top_p: None,
+            top_k: None,
+            stop: Vec::new(),
+            seed: None,
+            presence_penalty: None,
+            frequency_penalty: None,
+            logit_bias: HashMap::new(),
+            min_p: None,
+            repeat_penalty: None,
+            user: None,
+            metadata: HashMap::new(),
+            modalities: Vec::new(),
+            logprobs: None,
+            prompt_cache_key: None,
+            cached_content: None,
+            include_usage: false,
+            stats: false,
+            ttl: None,
+            previous_response_id: None,
+            store: None,
+            extra_body: json::Map::new(),
+            response_cache: None,
+            cassette: None,
+            tokenizer: None,
+            context_window: None,
+            context_strategy: None,
             tokens_count: 0,
             schema: None,
+            schema_mode: SchemaMode::Native,
             tools: Vec::new(),
+            tool_choice: None,
+            parallel_tool_calls: None,
+            tool_deltas: false,
             api_kind: kind,
         }
     }
@@ -115,6 +333,11 @@ impl Completions {
         Self::new(ApiKind::OpenAI, key, model)
     }
 
+    /// Creates a new OpenAI Responses API request
+    pub fn responses(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::Responses, key, model)
+    }
+
     /// Creates a new Anthropic (Claude) request
     pub fn anthropic(key: impl Into<String>, model: impl Into<String>) -> Self {
         Self::new(ApiKind::Anthropic, key, model)
@@ -218,6 +441,11 @@ impl Completions {
         self.messages.extend(msgs);
     }
 
+    /// Adds the conversation messages to request
+    pub fn conversation(self, conv: &Conversation) -> Self {
+        self.messages(conv.messages.clone())
+    }
+
     /// Adds a message to request
     pub fn message(mut self, role: Role, content: Vec<Content>) -> Self {
         let msg = Message::new(role, content);
@@ -273,12 +501,219 @@ impl Completions {
         self
     }
 
+    /// Sets the nucleus sampling probability
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.top_p.replace(top_p);
+        self
+    }
+
+    /// Sets the top-K sampling
+    pub fn top_k(mut self, top_k: u32) -> Self {
+        self.top_k.replace(top_k);
+        self
+    }
+
+    /// Adds the stop sequence
+    pub fn stop(mut self, seq: impl Into<String>) -> Self {
+        self.stop.push(seq.into());
+        self
+    }
+
+    /// Adds the stop sequences
+    pub fn stops(mut self, seqs: Vec<impl Into<String>>) -> Self {
+        self.stop.extend(seqs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the sampling seed
+    pub fn seed(mut self, seed: i64) -> Self {
+        self.seed.replace(seed);
+        self
+    }
+
+    /// Sets the presence penalty
+    pub fn presence_penalty(mut self, penalty: f32) -> Self {
+        self.presence_penalty.replace(penalty);
+        self
+    }
+
+    /// Sets the frequency penalty
+    pub fn frequency_penalty(mut self, penalty: f32) -> Self {
+        self.frequency_penalty.replace(penalty);
+        self
+    }
+
+    /// Adds the token logit bias
+    pub fn logit_bias(mut self, token: impl Into<String>, bias: f32) -> Self {
+        self.logit_bias.insert(token.into(), bias);
+        self
+    }
+
+    /// Sets the minimum token probability
+    pub fn min_p(mut self, min_p: f32) -> Self {
+        self.min_p.replace(min_p);
+        self
+    }
+
+    /// Sets the repeat penalty
+    pub fn repeat_penalty(mut self, penalty: f32) -> Self {
+        self.repeat_penalty.replace(penalty);
+        self
+    }
+
+    /// Sets the end-user identifier
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.user.replace(user.into());
+        self
+    }
+
+    /// Adds the request metadata
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the output modalities, like `["text", "image"]` for image-output models (`AiChunk::Image`)
+    pub fn modalities(mut self, list: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.modalities = list.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Enables the log probabilities with N most likely alternatives (`AiChunk::Logprobs`)
+    pub fn logprobs(mut self, top_n: u32) -> Self {
+        self.logprobs.replace(top_n);
+        self
+    }
+
+    /// Sets the prompt cache routing key (OpenAI `prompt_cache_key`)
+    pub fn prompt_cache_key(mut self, key: impl Into<String>) -> Self {
+        self.prompt_cache_key.replace(key.into());
+        self
+    }
+
+    /// Uses the cached contents (Google `cachedContent`, see `create_cached_content`)
+    pub fn cached_content(mut self, name: impl Into<String>) -> Self {
+        self.cached_content.replace(name.into());
+        self
+    }
+
+    /// Requests the tokens usage chunk (`AiChunk::Usage`, the non-OpenAI APIs report it always)
+    pub fn include_usage(mut self, enabled: bool) -> Self {
+        self.include_usage = enabled;
+        self
+    }
+
+    /// Requests the generation stats (`AiChunk::Stats`, LM Studio native API)
+    pub fn stats(mut self, enabled: bool) -> Self {
+        self.stats = enabled;
+        self
+    }
+
+    /// Sets the JIT-loaded model TTL, the idle time before auto-unloading (LM Studio)
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl.replace(ttl);
+        self
+    }
+
+    /// Continues the server-side conversation from the response (OpenAI Responses, see `AiStream::response_id`)
+    pub fn previous_response_id(mut self, id: impl Into<String>) -> Self {
+        self.previous_response_id.replace(id.into());
+        self
+    }
+    /// Continues the server-side conversation from the response (OpenAI Responses, see `AiStream::response_id`)
+    pub fn set_previous_response_id(&mut self, id: impl Into<String>) {
+        self.previous_response_id.replace(id.into());
+    }
+
+    /// Stores the response on the server (OpenAI Responses)
+    pub fn store(mut self, enabled: bool) -> Self {
+        self.store.replace(enabled);
+        self
+    }
+
+    /// Adds the extra request body field (overrides any other fields)
+    pub fn extra_body(mut self, key: impl Into<String>, value: JsonValue) -> Self {
+        self.extra_body.insert(key.into(), value);
+        self
+    }
+
+    /// Sets the responses cache (the identical requests are read from it)
+    pub fn response_cache(mut self, cache: Arc<dyn ResponseCache>) -> Self {
+        self.response_cache.replace(cache);
+        self
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Sets the custom context tokenizer
+    pub fn tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer.replace(tokenizer);
+        self
+    }
+
+    /// Returns the context tokenizer (custom or chosen by model)
+    pub fn get_tokenizer(&self) -> Arc<dyn Tokenizer> {
+        self.tokenizer
+            .clone()
+            .unwrap_or_else(|| crate::tokenizer_for(&self.api_kind, &self.model))
+    }
+
+    /// Sets the model context window size (the output `max_tokens` is reserved from it)
+    pub fn context_window(mut self, tokens: usize) -> Self {
+        self.context_window.replace(tokens);
+        self
+    }
+
+    /// Sets the context window management strategy
+    pub fn context_strategy(mut self, strategy: impl ContextStrategy + 'static) -> Self {
+        self.context_strategy.replace(Arc::new(strategy));
+        self
+    }
+
+    /// Adds an assistant tool calls message to request (the tool results must follow it)
+    pub fn tool_calls_message(mut self, content: Vec<Content>, calls: Vec<ToolCall>) -> Self {
+        let msg = Message::tool_calls(content, calls);
+        self.tokens_count += msg.tokens_count;
+        self.messages.push(msg);
+        self
+    }
+    /// Adds an assistant tool calls message to request (the tool results must follow it)
+    pub fn add_tool_calls_message(&mut self, content: Vec<Content>, calls: Vec<ToolCall>) {
+        let msg = Message::tool_calls(content, calls);
+        self.tokens_count += msg.tokens_count;
+        self.messages.push(msg);
+    }
+
+    /// Adds a tool call result message to request
+    pub fn tool_message(mut self, id: impl Into<String>, content: Vec<Content>) -> Self {
+        let msg = Message::tool(id, content);
+        self.tokens_count += msg.tokens_count;
+        self.messages.push(msg);
+        self
+    }
+    /// Adds a tool call result message to request
+    pub fn add_tool_message(&mut self, id: impl Into<String>, content: Vec<Content>) {
+        let msg = Message::tool(id, content);
+        self.tokens_count += msg.tokens_count;
+        self.messages.push(msg);
+    }
+
     /// Sets the structured response schema
     pub fn schema(mut self, schema: Schema) -> Self {
         self.schema.replace(schema);
         self
     }
 
+    /// Sets the structured output mode
+    pub fn schema_mode(mut self, mode: SchemaMode) -> Self {
+        self.schema_mode = mode;
+        self
+    }
+
     /// Adds the tool calls
     pub fn tools(mut self, tools: Vec<Tool>) -> Self {
         self.tools.extend(tools);
@@ -291,68 +726,114 @@ impl Completions {
         self
     }
 
-    /// Sends the request to LM server
-    pub async fn send(&mut self) -> Result<AiStream> {
+    /// Sets the tool choice mode
+    pub fn tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice.replace(choice);
+        self
+    }
+    /// Sets the tool choice mode
+    pub fn set_tool_choice(&mut self, choice: ToolChoice) {
+        self.tool_choice.replace(choice);
+    }
+
+    /// Allows/forbids the parallel tool calls (not supported by Google API)
+    pub fn parallel_tool_calls(mut self, enabled: bool) -> Self {
+        self.parallel_tool_calls.replace(enabled);
+        self
+    }
+
+    /// Enables the streamed partial tool call arguments (`AiChunk::ToolDelta`)
+    pub fn tool_deltas(mut self, enabled: bool) -> Self {
+        self.tool_deltas = enabled;
+        self
+    }
+
+    /// Sends the request to LM server (the request stays unchanged, so it can be sent again)
+    pub async fn send(&self) -> Result<AiStream> {
         use crate::chunk::*;
 
-        // generate URL:
+        // generate URL (the LM Studio native API reports the generation stats):
+        let path = if self.stats && self.api_kind.is_lmstudio() {
+            str!("api/v0/chat/completions")
+        } else {
+            self.api_kind.completions_path(&self.model)
+        };
         let url = if let Some(host) = &self.host {
-            str!(
-                "{host}{}{}",
-                if host.ends_with("/") { "" } else { "/" },
-                self.api_kind.completions_path(&self.model)
-            )
+            str!("{host}{}{path}", if host.ends_with("/") { "" } else { "/" })
         } else {
-            str!(
-                "{}/{}",
-                self.api_kind.host(),
-                self.api_kind.completions_path(&self.model)
-            )
+            str!("{}/{path}", self.api_kind.host())
         };
 
-        // context management:
-        if self.max_tokens > 0 {
-            let mut idx = 0;
-            let mut max_idx = self.messages.len() - 1;
-            let max_count = self.max_tokens as usize;
-
-            while self.tokens_count > max_count && idx < max_idx {
-                let msg = &self.messages[idx];
-                if msg.role.is_system() {
-                    idx += 1;
-                    continue;
-                }
-                self.tokens_count -= msg.tokens_count;
-                self.messages.remove(idx);
-                max_idx -= 1;
+        // context management (trimming a copy of messages):
+        let mut trimmed = None;
+        if let Some(window) = self.context_window {
+            // recount tokens with the model tokenizer:
+            let tokenizer = self.get_tokenizer();
+            let mut messages = self.messages.clone();
+            for msg in &mut messages {
+                msg.update_tokens_for(&self.api_kind, &*tokenizer);
             }
 
-            if let Some(msg) = self.messages.last()
+            // reserve the output tokens:
+            let budget = window.saturating_sub(self.max_tokens.max(0) as usize);
+            let strategy = self
+                .context_strategy
+                .clone()
+                .unwrap_or_else(|| Arc::new(SlidingWindow));
+
+            let messages = strategy.fit(messages, budget).await?;
+            if let Some(msg) = messages.last()
                 && msg.role.is_assistant()
             {
                 return Err(Error::IncorrectContext.into());
             }
+
+            trimmed = Some(messages);
         }
 
         // serialize & clean data:
-        let mut data = json::to_value(&self).map_err(Error::from)?;
+        let mut data = json::to_value(self).map_err(Error::from)?;
         let data_obj = data.as_object_mut().unwrap();
         data_obj.remove("tokens_count");
-        if let Some(messages) = data_obj.get_mut("messages").and_then(|v| v.as_array_mut()) {
-            for msg in messages {
-                if let Some(msg_obj) = msg.as_object_mut() {
-                    msg_obj.remove("tokens_count");
-                    msg_obj.remove("timestamp");
-                }
+
+        let mut messages = self.messages_json(trimmed.as_deref().unwrap_or(&self.messages))?;
+        if self.api_kind.is_anthropic() {
+            let system = take_system_blocks(&mut messages);
+            if !system.is_empty() {
+                data_obj.insert(str!("system"), json!(system));
             }
         }
+        data_obj.insert(str!("messages"), json!(messages));
         data_obj.insert(str!("stream"), JsonValue::Bool(true));
 
+        // prepare sampling params:
+        self.prepare_sampling(data_obj);
+
+        // prepare prompt caching:
+        self.prepare_caching(data_obj);
+
         // prepare JSON-schema:
-        if let Some(schema) = self.schema.take() {
-            data_obj.remove("schema");
+        let mut tools = self.tools.clone();
+        let mut tool_choice = self.tool_choice.clone();
+        let mut schema_tool = None;
 
-            if self.api_kind.is_openai() {
+        data_obj.remove("schema");
+        if let Some(schema) = &self.schema {
+            if self.schema_mode.is_tool() {
+                // the tool input must be an object, so wrap other types into it:
+                let wrapped = !schema.kind.is_object();
+                let schema = if wrapped {
+                    Schema::object("").required_property("value", schema.clone())
+                } else {
+                    schema.clone()
+                };
+
+                tools.push(Tool::from_schema(SCHEMA_TOOL_NAME, schema));
+                tool_choice.replace(ToolChoice::tool(SCHEMA_TOOL_NAME));
+                schema_tool.replace(wrapped);
+            } else if self.api_kind.is_responses() {
+                data_obj.insert(str!("text"), schema.to_responses_format()?);
+            } else if self.api_kind.is_openai() {
                 data_obj.insert(str!("response_format"), schema.to_openai_format()?);
             } else if self.api_kind.is_google() {
                 let google_config = schema.to_google_format()?;
@@ -375,11 +856,13 @@ impl Completions {
         }
 
         // prepare tools schemes:
-        if !self.tools.is_empty() {
+        if !tools.is_empty() {
             let mut tools_json = Vec::new();
 
-            for tool in &self.tools {
-                let formatted_tool = if self.api_kind.is_openai() {
+            for tool in &tools {
+                let formatted_tool = if self.api_kind.is_responses() {
+                    tool.to_responses_format()
+                } else if self.api_kind.is_openai() {
                     tool.to_openai_format()
                 } else if self.api_kind.is_google() {
                     tool.to_google_format()
@@ -390,14 +873,13 @@ impl Completions {
                 if self.api_kind.is_google() {
                     if tools_json.is_empty() {
                         tools_json.push(formatted_tool);
-                    } else if let Some(first_tool) = tools_json.get_mut(0) {
-                        if let Some(decls) = first_tool
+                    } else if let Some(first_tool) = tools_json.get_mut(0)
+                        && let Some(decls) = first_tool
                             .get_mut("function_declarations")
                             .and_then(|d| d.as_array_mut())
-                        {
-                            let tool_val = tool.to_json_tool()?;
-                            decls.push(tool_val);
-                        }
+                    {
+                        let tool_val = tool.to_json_tool()?;
+                        decls.push(tool_val);
                     }
                 } else {
                     tools_json.push(formatted_tool);
@@ -405,11 +887,99 @@ impl Completions {
             }
 
             data_obj.insert("tools".to_string(), JsonValue::Array(tools_json));
+
+            // prepare tool choice:
+            if self.api_kind.is_openai() {
+                if let Some(choice) = tool_choice {
+                    let choice = if self.api_kind.is_responses() {
+                        choice.to_responses_format()
+                    } else {
+                        choice.to_openai_format()
+                    };
+                    data_obj.insert(str!("tool_choice"), choice);
+                }
+                if let Some(enabled) = self.parallel_tool_calls {
+                    data_obj.insert(str!("parallel_tool_calls"), json!(enabled));
+                }
+            } else if self.api_kind.is_google() {
+                if let Some(choice) = tool_choice {
+                    data_obj.insert(str!("toolConfig"), choice.to_google_format());
+                }
+            } else {
+                let mut choice = tool_choice.map(|c| c.to_anthropic_format());
+
+                if let Some(enabled) = self.parallel_tool_calls
+                    && choice.as_ref().map(|c| c["type"] != "none").unwrap_or(true)
+                {
+                    choice.get_or_insert_with(|| ToolChoice::Auto.to_anthropic_format())["disable_parallel_tool_use"] =
+                        json!(!enabled);
+                }
+                if let Some(choice) = choice {
+                    data_obj.insert(str!("tool_choice"), choice);
+                }
+            }
+        }
+
+        // prepare OpenAI Responses input:
+        if self.api_kind.is_responses() {
+            let messages = data_obj.remove("messages").unwrap_or(json!([]));
+            data_obj.insert(
+                str!("input"),
+                to_responses_input(&messages, &self.api_kind)?,
+            );
+            if let Some(id) = &self.previous_response_id {
+                data_obj.insert(str!("previous_response_id"), json!(id));
+            }
+            if let Some(store) = self.store {
+                data_obj.insert(str!("store"), json!(store));
+            }
+        } else if self.previous_response_id.is_some() || self.store.is_some() {
+            log::warn!(
+                "The 'previous_response_id' & 'store' params aren't supported by {} API, skipped",
+                self.api_kind
+            );
         }
 
+        // prepare LM Studio params:
+        if self.api_kind.is_lmstudio() {
+            if let Some(ttl) = self.ttl {
+                data_obj.insert(str!("ttl"), json!(ttl.as_secs()));
+            }
+        } else if self.stats || self.ttl.is_some() {
+            log::warn!(
+                "The 'stats' & 'ttl' params aren't supported by {} API, skipped",
+                self.api_kind
+            );
+        }
+
+        // prepare Google contents:
+        if self.api_kind.is_google() {
+            let messages = data_obj.remove("messages").unwrap_or(json!([]));
+            data_obj.insert(str!("contents"), to_google_contents(&messages));
+            data_obj.remove("model");
+            data_obj.remove("stream");
+        }
+
+        // merge extra body fields:
+        for (k, v) in &self.extra_body {
+            data_obj.insert(k.clone(), v.clone());
+        }
+
+        // read the response cache:
+        let cache = match &self.response_cache {
+            Some(cache) => {
+                let key = cache_key(&str!("{url}\n{}", json::to_string(&data_obj)?));
+                if let Some(data) = cache.get(&key).await? {
+                    return Ok(AiStream::from_cache(json::from_value(data)?));
+                }
+                Some((cache.clone(), key))
+            }
+            None => None,
+        };
+
         // create client & configure proxy:
         let mut client_builder = Client::builder().timeout(self.timeout);
-        if let Some(proxy) = self.proxy.take() {
+        if let Some(proxy) = self.proxy.clone() {
             client_builder = client_builder
                 .proxy(proxy)
                 .danger_accept_invalid_certs(true);
@@ -430,140 +1000,74 @@ impl Completions {
             request = request.header("x-api-key", &self.api_key);
             request = request.header(
                 "anthropic-version",
-                self.api_version.take().unwrap_or(str!("2023-06-01")),
+                self.api_version.as_deref().unwrap_or("2023-06-01"),
             );
         } else {
             request = request.header(header::AUTHORIZATION, str!("Bearer {}", self.api_key));
         }
 
-        if self.api_kind.is_google() {
-            let messages = data_obj.remove("messages").unwrap_or(json!([]));
-            let contents: Vec<JsonValue> = messages
-                .as_array()
-                .unwrap()
-                .iter()
-                .map(|m| {
-                    json!({
-                        "role": if m["role"] == "assistant" { "model" } else { "user" },
-                        "parts": m["content"]
-                    })
-                })
-                .collect();
-            data_obj.insert(str!("contents"), json!(contents));
-            data_obj.remove("model");
-        }
-
         // send & spawn reader:
-        let response = request.send().await?;
-        let bytes_stream = response.bytes_stream().map(|r| r.map_err(Into::into));
+        let bytes_stream = cassette::send_sse(request, &url, &data, self.cassette.as_ref()).await?;
 
-        let mut reader = Stream::read::<ResponseChunk>(bytes_stream);
+        let mut reader = Stream::read::<ResponseChunk>(sse_data_lines(bytes_stream));
 
         let (tx, rx) = mpsc::unbounded_channel::<Result<AiChunk>>();
-        let mut tool_buffers = HashMap::<usize, (String, String)>::new();
+        let mut chunks = ChunkReader::new(schema_tool, self.tool_deltas);
+        let response_id = Arc::new(Mutex::new(None));
+        let response_id_ref = response_id.clone();
 
         let handle = tokio::spawn(async move {
+            let mut recorded = Vec::new();
+
             loop {
                 if tx.is_closed() {
                     break;
                 }
 
-                match reader.read().await {
+                let output = match reader.read().await {
                     Ok(Some(chunk)) => {
-                        let mut text_output = String::new();
-
-                        match chunk {
-                            ResponseChunk::OpenAi(OpenAIChunk { choices }) => {
-                                for choice in choices {
-                                    if let Some(content) = choice.delta.content {
-                                        text_output.push_str(&content);
-                                    }
-                                    if let Some(tool_calls) = choice.delta.tool_calls {
-                                        for tc in tool_calls {
-                                            if let (Some(idx), Some(fn_delta)) =
-                                                (tc.index, tc.function)
-                                            {
-                                                let entry = tool_buffers.entry(idx).or_default();
-                                                if let Some(name) = fn_delta.name {
-                                                    entry.0 = name;
-                                                }
-                                                if let Some(args) = fn_delta.arguments {
-                                                    entry.1.push_str(&args);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            ResponseChunk::Anthropic(anth) => {
-                                if let Some(delta) = anth.delta {
-                                    if let Some(t) = delta.text {
-                                        text_output.push_str(&t);
-                                    }
-                                    if let Some(pj) = delta.partial_json {
-                                        let idx = anth.index.unwrap_or(0);
-                                        tool_buffers.entry(idx).or_default().1.push_str(&pj);
-                                    }
-                                }
-                                if let Some(block) = anth.content_block
-                                    && block.kind == "tool_use"
-                                {
-                                    let idx = anth.index.unwrap_or(0);
-                                    tool_buffers.entry(idx).or_default().0 = block.name;
-                                }
-                            }
-                            ResponseChunk::Google(google) => {
-                                for cand in google.candidates {
-                                    if let Some(content) = cand.content {
-                                        for part in content.parts {
-                                            match part {
-                                                GeminiPart::Text { text } => {
-                                                    text_output.push_str(&text)
-                                                }
-                                                GeminiPart::FunctionCall { function_call } => {
-                                                    tx.send(Ok(AiChunk::Tool {
-                                                        name: function_call["name"]
-                                                            .as_str()
-                                                            .unwrap_or("")
-                                                            .to_string(),
-                                                        json_str: function_call["args"].to_string(),
-                                                    }))
-                                                    .ok();
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            ResponseChunk::Error(err) => {
-                                tx.send(Err(
-                                    Error::ResponseError(ResponseError { error: err }).into()
-                                ))
-                                .ok();
-                                return;
+                        let output = chunks.read(chunk);
+                        if let Some(id) = chunks.response_id.take()
+                            && let Ok(mut guard) = response_id_ref.lock()
+                        {
+                            guard.replace(id);
+                        }
+                        output
+                    }
+                    // flush the tool calls without an end-of-block signal and the usage:
+                    Ok(None) => {
+                        for chunk in chunks.finish_stream() {
+                            if cache.is_some() {
+                                recorded.push(chunk.clone());
                             }
+                            tx.send(Ok(chunk)).ok();
                         }
 
-                        if !text_output.is_empty() {
-                            if tx.send(Ok(AiChunk::Text { text: text_output })).is_err() {
-                                break;
-                            }
+                        // save the completed response into cache:
+                        if let Some((cache, key)) = &cache
+                            && let Err(e) = cache.put(key, json!(recorded)).await
+                        {
+                            log::warn!("Failed to save the response into cache: {e}");
                         }
+                        break;
+                    }
+                    Err(e) => {
+                        tx.send(Err(e)).ok();
+                        break;
+                    }
+                };
 
-                        tool_buffers.retain(|_, (name, args)| {
-                            if json::from_str::<JsonValue>(args).is_ok() {
-                                tx.send(Ok(AiChunk::Tool {
-                                    name: name.clone(),
-                                    json_str: args.clone(),
-                                }))
-                                .is_ok()
-                                    == false
-                            } else {
-                                true
+                match output {
+                    Ok(output) => {
+                        for chunk in output {
+                            if cache.is_some() {
+                                recorded.push(chunk.clone());
+                            }
+                            if tx.send(Ok(chunk)).is_err() {
+                                return;
                             }
-                        });
+                        }
                     }
-                    Ok(None) => break,
                     Err(e) => {
                         tx.send(Err(e.into())).ok();
                         break;
@@ -572,8 +1076,659 @@ impl Completions {
             }
         });
 
-        Ok(AiStream { rx, handle })
+        Ok(AiStream {
+            rx,
+            handle,
+            cached: false,
+            response_id,
+        })
+    }
+}
+
+impl Completions {
+    /// Counts the request tokens (by API endpoint for Anthropic and Google, else by tokenizer)
+    pub async fn count_tokens(&self) -> Result<usize> {
+        let path = if self.api_kind.is_anthropic() {
+            str!("v1/messages/count_tokens")
+        } else if self.api_kind.is_google() {
+            str!("v1beta/models/{}:countTokens", self.model)
+        } else {
+            let tokenizer = self.get_tokenizer();
+            return Ok(self
+                .messages
+                .iter()
+                .map(|msg| {
+                    let mut msg = msg.clone();
+                    msg.update_tokens_for(&self.api_kind, &*tokenizer);
+                    msg.tokens_count
+                })
+                .sum());
+        };
+
+        // generate URL:
+        let url = if let Some(host) = &self.host {
+            str!("{host}{}{path}", if host.ends_with("/") { "" } else { "/" })
+        } else {
+            str!("{}/{path}", self.api_kind.host())
+        };
+
+        // serialize messages:
+        let mut messages = self.messages_json(&self.messages)?;
+
+        let data = if self.api_kind.is_anthropic() {
+            let system = take_system_blocks(&mut messages);
+            let mut data = json!({ "model": self.model, "messages": messages });
+            if !system.is_empty() {
+                data["system"] = json!(system);
+            }
+            data
+        } else {
+            json!({ "contents": to_google_contents(&json!(messages)) })
+        };
+
+        // create client & configure proxy:
+        let mut client_builder = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client_builder = client_builder
+                .proxy(proxy)
+                .danger_accept_invalid_certs(true);
+        }
+
+        // build request & set api key:
+        let mut request = client_builder
+            .build()?
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .json(&data);
+
+        if self.api_kind.is_google() {
+            request = request.header("x-goog-api-key", &self.api_key);
+        } else {
+            request = request.header("x-api-key", &self.api_key).header(
+                "anthropic-version",
+                self.api_version.as_deref().unwrap_or("2023-06-01"),
+            );
+        }
+
+        let output = cassette::send_text(request, &url, &data, self.cassette.as_ref()).await?;
+
+        // check for an error:
+        if let Some(e) = ResponseError::from_str(&output) {
+            return Err(Error::ResponseError(e).into());
+        }
+
+        // else parse response:
+        let response: JsonValue = json::from_str(&output)?;
+        let count = response
+            .get("input_tokens")
+            .or_else(|| response.get("totalTokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or_default();
+
+        Ok(count as usize)
+    }
+
+    /// Creates the Google cached contents from the request messages and tools, returns the cache name
+    /// (use it with `cached_content` and send only the new messages)
+    pub async fn create_cached_content(&self, ttl: Duration) -> Result<String> {
+        if !self.api_kind.is_google() {
+            return Err(Error::Unsupported(str!("cached contents"), self.api_kind.clone()).into());
+        }
+
+        // generate URL:
+        let path = "v1beta/cachedContents";
+        let url = if let Some(host) = &self.host {
+            str!("{host}{}{path}", if host.ends_with("/") { "" } else { "/" })
+        } else {
+            str!("{}/{path}", self.api_kind.host())
+        };
+
+        // serialize messages & tools:
+        let mut messages = self.messages_json(&self.messages)?;
+        let system = take_system_blocks(&mut messages);
+
+        let mut data = json!({
+            "model": str!("models/{}", self.model),
+            "contents": to_google_contents(&json!(messages)),
+            "ttl": str!("{}s", ttl.as_secs()),
+        });
+        if !system.is_empty() {
+            let parts: Vec<_> = system
+                .iter()
+                .map(|block| json!({ "text": block["text"] }))
+                .collect();
+            data["systemInstruction"] = json!({ "parts": parts });
+        }
+        if !self.tools.is_empty() {
+            let decls = self
+                .tools
+                .iter()
+                .map(|tool| tool.to_json_tool())
+                .collect::<Result<Vec<_>>>()?;
+            data["tools"] = json!([{ "function_declarations": decls }]);
+        }
+
+        // create client & configure proxy:
+        let mut client_builder = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client_builder = client_builder
+                .proxy(proxy)
+                .danger_accept_invalid_certs(true);
+        }
+
+        let request = client_builder
+            .build()?
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-goog-api-key", &self.api_key)
+            .json(&data);
+
+        let output = cassette::send_text(request, &url, &data, self.cassette.as_ref()).await?;
+
+        // check for an error:
+        if let Some(e) = ResponseError::from_str(&output) {
+            return Err(Error::ResponseError(e).into());
+        }
+
+        // else parse response:
+        let response: JsonValue = json::from_str(&output)?;
+        Ok(response["name"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// Maps the sampling params into the API format (drops unsupported params with a warning)
+    fn prepare_sampling(&self, data_obj: &mut json::Map<String, JsonValue>) {
+        // the max tokens count isn't limited:
+        if self.max_tokens <= 0 {
+            data_obj.remove("max_tokens");
+        }
+
+        // (openai name, api name or None if unsupported):
+        let params: &[(&str, Option<&str>)] = if self.api_kind.is_google() {
+            &[
+                ("temperature", Some("temperature")),
+                ("max_tokens", Some("maxOutputTokens")),
+                ("top_p", Some("topP")),
+                ("top_k", Some("topK")),
+                ("stop", Some("stopSequences")),
+                ("seed", Some("seed")),
+                ("presence_penalty", Some("presencePenalty")),
+                ("frequency_penalty", Some("frequencyPenalty")),
+                ("logit_bias", None),
+                ("min_p", None),
+                ("repeat_penalty", None),
+                ("user", None),
+                ("metadata", None),
+            ]
+        } else if self.api_kind.is_anthropic() {
+            &[
+                ("stop", Some("stop_sequences")),
+                ("seed", None),
+                ("presence_penalty", None),
+                ("frequency_penalty", None),
+                ("logit_bias", None),
+                ("min_p", None),
+                ("repeat_penalty", None),
+                ("metadata", None),
+            ]
+        } else if self.api_kind.is_responses() {
+            &[
+                ("max_tokens", Some("max_output_tokens")),
+                ("top_k", None),
+                ("stop", None),
+                ("seed", None),
+                ("presence_penalty", None),
+                ("frequency_penalty", None),
+                ("logit_bias", None),
+                ("min_p", None),
+                ("repeat_penalty", None),
+            ]
+        } else if self.api_kind.is_lmstudio() {
+            &[]
+        } else if matches!(self.api_kind, ApiKind::OpenRouter) {
+            &[("repeat_penalty", Some("repetition_penalty"))]
+        } else {
+            &[("top_k", None), ("min_p", None), ("repeat_penalty", None)]
+        };
+
+        let mut generation_config = json::Map::new();
+        for (name, api_name) in params {
+            let Some(value) = data_obj.remove(*name) else {
+                continue;
+            };
+
+            match api_name {
+                Some(api_name) if self.api_kind.is_google() => {
+                    generation_config.insert(api_name.to_string(), value);
+                }
+                Some(api_name) => {
+                    data_obj.insert(api_name.to_string(), value);
+                }
+                None => {
+                    log::warn!(
+                        "The '{name}' param isn't supported by {} API, skipped",
+                        self.api_kind
+                    );
+                }
+            }
+        }
+
+        // prepare log probabilities:
+        if let Some(top_n) = self.logprobs {
+            if self.api_kind.is_google() {
+                generation_config.insert(str!("responseLogprobs"), json!(true));
+                generation_config.insert(str!("logprobs"), json!(top_n));
+            } else if self.api_kind.is_anthropic() {
+                log::warn!(
+                    "The 'logprobs' param isn't supported by {} API, skipped",
+                    self.api_kind
+                );
+            } else if self.api_kind.is_responses() {
+                data_obj.insert(str!("include"), json!(["message.output_text.logprobs"]));
+                data_obj.insert(str!("top_logprobs"), json!(top_n));
+            } else {
+                data_obj.insert(str!("logprobs"), json!(true));
+                data_obj.insert(str!("top_logprobs"), json!(top_n));
+            }
+        }
+
+        // prepare output modalities:
+        if !self.modalities.is_empty() {
+            if self.api_kind.is_google() {
+                let list: Vec<String> = self.modalities.iter().map(|m| m.to_uppercase()).collect();
+                generation_config.insert(str!("responseModalities"), json!(list));
+            } else if self.api_kind.is_anthropic() || self.api_kind.is_responses() {
+                log::warn!(
+                    "The 'modalities' param isn't supported by {} API, skipped",
+                    self.api_kind
+                );
+            } else {
+                let list: Vec<String> = self.modalities.iter().map(|m| m.to_lowercase()).collect();
+                data_obj.insert(str!("modalities"), json!(list));
+            }
+        }
+
+        // Anthropic sends the user ID into metadata:
+        if self.api_kind.is_anthropic()
+            && let Some(user) = data_obj.remove("user")
+        {
+            data_obj.insert(str!("metadata"), json!({ "user_id": user }));
+        }
+
+        if !generation_config.is_empty() {
+            data_obj.insert(
+                str!("generationConfig"),
+                JsonValue::Object(generation_config),
+            );
+        }
+    }
+
+    /// Maps the prompt caching params into the API format
+    fn prepare_caching(&self, data_obj: &mut json::Map<String, JsonValue>) {
+        if let Some(key) = &self.prompt_cache_key {
+            if self.api_kind.is_openai() {
+                data_obj.insert(str!("prompt_cache_key"), json!(key));
+            } else {
+                log::warn!(
+                    "The 'prompt_cache_key' param isn't supported by {} API, skipped",
+                    self.api_kind
+                );
+            }
+        }
+
+        if let Some(name) = &self.cached_content {
+            if self.api_kind.is_google() {
+                data_obj.insert(str!("cachedContent"), json!(name));
+            } else {
+                log::warn!(
+                    "The 'cached_content' param isn't supported by {} API, skipped",
+                    self.api_kind
+                );
+            }
+        }
+
+        // the Responses API reports the usage always:
+        if self.include_usage && self.api_kind.is_openai() && !self.api_kind.is_responses() {
+            data_obj.insert(str!("stream_options"), json!({ "include_usage": true }));
+        }
+    }
+
+    /// Serializes the messages into the API format
+    fn messages_json(&self, messages: &[Message]) -> Result<Vec<JsonValue>> {
+        let mut output = Vec::with_capacity(messages.len());
+
+        for msg in messages {
+            let mut msg = json::to_value(msg)?;
+            if let Some(msg_obj) = msg.as_object_mut() {
+                msg_obj.remove("tokens_count");
+                msg_obj.remove("timestamp");
+                if self.api_kind.is_anthropic() {
+                    to_anthropic_blocks(msg_obj, &self.api_kind)?;
+                    to_anthropic_tool_use(msg_obj)?;
+                    to_anthropic_tool_result(msg_obj);
+                    to_anthropic_cache_control(msg_obj);
+                } else {
+                    to_openai_tool_calls(msg_obj)?;
+                    // the other APIs cache the prompt prefix automatically:
+                    msg_obj.remove("cache_control");
+                }
+            }
+
+            // the parallel tool results are sent in one user turn (Anthropic):
+            if is_anthropic_tool_result(&msg)
+                && let Some(last) = output.last_mut()
+                && is_anthropic_tool_result(last)
+                && let (Some(blocks), Some(results)) =
+                    (last["content"].as_array_mut(), msg["content"].as_array())
+            {
+                blocks.extend(results.iter().cloned());
+                continue;
+            }
+            output.push(msg);
+        }
+
+        Ok(output)
+    }
+}
+
+/// Moves the message cache breakpoint onto its last content block (`Anthropic` format)
+fn to_anthropic_cache_control(msg: &mut json::Map<String, JsonValue>) {
+    let Some(cache) = msg.remove("cache_control") else {
+        return;
+    };
+
+    if let Some(block) = msg
+        .get_mut("content")
+        .and_then(|c| c.as_array_mut())
+        .and_then(|c| c.last_mut())
+        .and_then(|b| b.as_object_mut())
+    {
+        block.insert(str!("cache_control"), cache);
+    }
+}
+
+/// Takes the system messages out into the system prompt text blocks
+fn take_system_blocks(messages: &mut Vec<JsonValue>) -> Vec<JsonValue> {
+    let mut system = Vec::new();
+
+    messages.retain_mut(|msg| {
+        if msg["role"] != "system" {
+            return true;
+        }
+        if let Some(content) = msg.get_mut("content").and_then(|c| c.as_array_mut()) {
+            system.extend(content.drain(..).filter(|block| block["type"] == "text"));
+        }
+        false
+    });
+
+    system
+}
+
+/// Converts the messages into `Google` contents (the tool calls into `functionCall` & `functionResponse` parts)
+fn to_google_contents(messages: &JsonValue) -> JsonValue {
+    let mut contents: Vec<JsonValue> = Vec::new();
+    let mut tool_names = HashMap::new();
+
+    for m in messages.as_array().into_iter().flatten() {
+        // the tool results are sent as function responses (in one user turn):
+        if m["role"] == "tool" {
+            let id = m["tool_call_id"].as_str().unwrap_or_default();
+            let part = json!({ "functionResponse": {
+                "id": id,
+                "name": tool_names.get(id).cloned().unwrap_or_else(|| json!(id)),
+                "response": { "result": blocks_text(&m["content"]) }
+            }});
+
+            if let Some(last) = contents.last_mut()
+                && last["parts"][0].get("functionResponse").is_some()
+                && let Some(parts) = last["parts"].as_array_mut()
+            {
+                parts.push(part);
+            } else {
+                contents.push(json!({ "role": "user", "parts": [part] }));
+            }
+            continue;
+        }
+
+        let mut parts: Vec<JsonValue> = m["content"]
+            .as_array()
+            .map(|blocks| blocks.iter().map(to_google_part).collect())
+            .unwrap_or_default();
+
+        for call in m["tool_calls"].as_array().into_iter().flatten() {
+            let id = call["id"].as_str().unwrap_or_default().to_string();
+            let name = call["function"]["name"].clone();
+            let args = call["function"]["arguments"]
+                .as_str()
+                .and_then(|args| json::from_str::<JsonValue>(args).ok())
+                .unwrap_or_else(|| json!({}));
+
+            parts.push(json!({ "functionCall": { "id": id, "name": name, "args": args } }));
+            tool_names.insert(id, name);
+        }
+
+        contents.push(json!({
+            "role": if m["role"] == "assistant" { "model" } else { "user" },
+            "parts": parts
+        }));
+    }
+
+    json!(contents)
+}
+
+/// Joins the text content blocks
+fn blocks_text(content: &JsonValue) -> String {
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|b| b["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Converts the messages into `OpenAI Responses` input items (the tool results into `function_call_output`)
+fn to_responses_input(messages: &JsonValue, kind: &ApiKind) -> Result<JsonValue> {
+    let mut input = Vec::new();
+
+    for msg in messages.as_array().into_iter().flatten() {
+        let blocks = msg["content"].as_array().cloned().unwrap_or_default();
+
+        if msg["role"] == "tool" {
+            input.push(json!({
+                "type": "function_call_output",
+                "call_id": msg["tool_call_id"],
+                "output": blocks_text(&msg["content"])
+            }));
+            continue;
+        }
+
+        let text_type = if msg["role"] == "assistant" {
+            "output_text"
+        } else {
+            "input_text"
+        };
+        let mut content = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            content.push(match block["type"].as_str() {
+                Some("text") => json!({ "type": text_type, "text": block["text"] }),
+                Some("image_url") => {
+                    let mut image =
+                        json!({ "type": "input_image", "image_url": block["image_url"]["url"] });
+                    image["detail"] = match block["detail"].as_str() {
+                        Some(detail) => json!(detail),
+                        None => json!("auto"),
+                    };
+                    image
+                }
+                Some("file") => {
+                    let url = block["file"]["file_data"].as_str().unwrap_or_default();
+
+                    // the inline files require a filename:
+                    match media::split_data_url(url) {
+                        Some((mime, _)) => {
+                            let name = block["file"]["filename"]
+                                .as_str()
+                                .map(String::from)
+                                .unwrap_or_else(|| str!("document.{}", media::extension(mime)));
+                            json!({ "type": "input_file", "filename": name, "file_data": url })
+                        }
+                        None => json!({ "type": "input_file", "file_url": url }),
+                    }
+                }
+                Some("input_audio") => {
+                    return Err(Error::Unsupported(str!("audio input"), kind.clone()).into());
+                }
+                _ => block,
+            });
+        }
+
+        if !content.is_empty() {
+            input.push(json!({ "role": msg["role"], "content": content }));
+        }
+
+        // the assistant tool calls are the separate items:
+        for call in msg["tool_calls"].as_array().into_iter().flatten() {
+            input.push(json!({
+                "type": "function_call",
+                "call_id": call["id"],
+                "name": call["function"]["name"],
+                "arguments": call["function"]["arguments"]
+            }));
+        }
     }
+
+    Ok(json!(input))
+}
+
+/// Converts the content block into `Google` part (the media files into `inlineData`)
+fn to_google_part(block: &JsonValue) -> JsonValue {
+    let inline =
+        |mime: &str, data: &str| json!({ "inlineData": { "mimeType": mime, "data": data } });
+
+    match block["type"].as_str() {
+        Some("image_url") | Some("file") => {
+            let url = block["image_url"]["url"]
+                .as_str()
+                .or_else(|| block["file"]["file_data"].as_str())
+                .unwrap_or_default();
+            match media::split_data_url(url) {
+                Some((mime, data)) => inline(mime, data),
+                None => json!({ "fileData": { "fileUri": url } }),
+            }
+        }
+        Some("input_audio") => inline(
+            media::audio_mime(block["input_audio"]["format"].as_str().unwrap_or_default()),
+            block["input_audio"]["data"].as_str().unwrap_or_default(),
+        ),
+        _ => block.clone(),
+    }
+}
+
+/// Converts the media content blocks into `Anthropic` format (`image` & `document` sources)
+fn to_anthropic_blocks(msg: &mut json::Map<String, JsonValue>, kind: &ApiKind) -> Result<()> {
+    let Some(content) = msg.get_mut("content").and_then(|c| c.as_array_mut()) else {
+        return Ok(());
+    };
+
+    for block in content.iter_mut() {
+        let converted = match block["type"].as_str() {
+            Some("image_url") => {
+                let url = block["image_url"]["url"].as_str().unwrap_or_default();
+                let source = match media::split_data_url(url) {
+                    Some((mime, data)) => {
+                        json!({ "type": "base64", "media_type": mime, "data": data })
+                    }
+                    None => json!({ "type": "url", "url": url }),
+                };
+                json!({ "type": "image", "source": source })
+            }
+            Some("file") => {
+                let url = block["file"]["file_data"].as_str().unwrap_or_default();
+                let (mime, data) = media::split_data_url(url).unwrap_or(("application/pdf", url));
+
+                // the plain text documents are sent as text source:
+                let source = if mime.starts_with("text/") {
+                    let text = String::from_utf8_lossy(&media::decode(data)?).into_owned();
+                    json!({ "type": "text", "media_type": "text/plain", "data": text })
+                } else {
+                    json!({ "type": "base64", "media_type": mime, "data": data })
+                };
+
+                let mut doc = json!({ "type": "document", "source": source });
+                if let Some(name) = block["file"]["filename"].as_str() {
+                    doc["title"] = json!(name);
+                }
+                doc
+            }
+            Some("input_audio") => {
+                return Err(Error::Unsupported(str!("audio input"), kind.clone()).into());
+            }
+            _ => continue,
+        };
+
+        *block = converted;
+    }
+
+    Ok(())
+}
+
+/// Converts the assistant tool calls into `OpenAI` format (the content is `null` without text)
+fn to_openai_tool_calls(msg: &mut json::Map<String, JsonValue>) -> Result<()> {
+    let Some(calls) = msg.remove("tool_calls") else {
+        return Ok(());
+    };
+    let calls: Vec<ToolCall> = json::from_value(calls)?;
+
+    msg.insert(
+        str!("tool_calls"),
+        json!(
+            calls
+                .iter()
+                .map(ToolCall::to_openai_format)
+                .collect::<Vec<_>>()
+        ),
+    );
+    if msg["content"].as_array().is_some_and(|c| c.is_empty()) {
+        msg.insert(str!("content"), JsonValue::Null);
+    }
+
+    Ok(())
+}
+
+/// Converts the assistant tool calls into `Anthropic` format (`tool_use` content blocks)
+fn to_anthropic_tool_use(msg: &mut json::Map<String, JsonValue>) -> Result<()> {
+    let Some(calls) = msg.remove("tool_calls") else {
+        return Ok(());
+    };
+    let calls: Vec<ToolCall> = json::from_value(calls)?;
+
+    if let Some(content) = msg.get_mut("content").and_then(|c| c.as_array_mut()) {
+        content.extend(calls.iter().map(|call| {
+            json!({ "type": "tool_use", "id": call.id, "name": call.name, "input": call.args() })
+        }));
+    }
+
+    Ok(())
+}
+
+/// Returns true if it's the tool call result message (`Anthropic` format)
+fn is_anthropic_tool_result(msg: &JsonValue) -> bool {
+    msg["role"] == "user" && msg["content"][0]["type"] == "tool_result"
+}
+
+/// Converts the tool call result message into `Anthropic` format (user message with `tool_result`)
+fn to_anthropic_tool_result(msg: &mut json::Map<String, JsonValue>) {
+    if msg.get("role").and_then(|r| r.as_str()) != Some("tool") {
+        return;
+    }
+    let id = msg.remove("tool_call_id").unwrap_or_default();
+    let content = msg.remove("content").unwrap_or(json!([]));
+
+    msg.insert(str!("role"), json!("user"));
+    msg.insert(
+        str!("content"),
+        json!([{ "type": "tool_result", "tool_use_id": id, "content": content }]),
+    );
 }
 
 impl TryFrom<AiOptions> for Completions {
@@ -593,7 +1748,24 @@ impl TryFrom<AiOptions> for Completions {
             ops.model,
         )
         .max_tokens(ops.max_tokens.unwrap_or(8096))
-        .temperature(ops.temperature.unwrap_or(0.6));
+        .temperature(ops.temperature.unwrap_or(0.6))
+        .stops(ops.stop);
+
+        // set context window size:
+        if let Some(window) = ops.context_window {
+            this = this.context_window(window);
+        }
+
+        // set sampling params:
+        if let Some(top_p) = ops.top_p {
+            this = this.top_p(top_p);
+        }
+        if let Some(top_k) = ops.top_k {
+            this = this.top_k(top_k);
+        }
+        if let Some(seed) = ops.seed {
+            this = this.seed(seed);
+        }
 
         // set default server host:
         if let Some(host) = ops.server.as_ref() {
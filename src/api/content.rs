@@ -1,6 +1,8 @@
-use crate::{image, prelude::*};
+use crate::{image, media, prelude::*};
+use regex::bytes::Regex as BytesRegex;
 use std::borrow::Cow;
 use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
 /// The image base64 url
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -8,6 +10,94 @@ pub struct Image {
     pub url: String,
 }
 
+/// The base64 audio
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Audio {
+    /// The base64 audio data
+    pub data: String,
+    /// The audio format (wav, mp3, etc.)
+    pub format: String,
+}
+
+impl Audio {
+    /// Returns the audio MIME type
+    pub fn mime_type(&self) -> &'static str {
+        media::audio_mime(&self.format)
+    }
+
+    /// Estimates the audio duration in seconds (by WAV header or the 128 kbps bitrate)
+    pub fn duration_secs(&self) -> f32 {
+        let padding = self.data.bytes().rev().take_while(|b| *b == b'=').count();
+        let mut size = (self.data.len() / 4 * 3).saturating_sub(padding);
+
+        // read the WAV byte rate (the header offset 28):
+        let byte_rate = if self.format == "wav" {
+            self.data
+                .get(..self.data.len().min(48))
+                .and_then(|head| media::decode(head).ok())
+                .and_then(|head| {
+                    head.get(28..32)
+                        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                })
+                .filter(|rate| *rate > 0)
+                .unwrap_or(32_000)
+        } else {
+            16_000
+        };
+        if self.format == "wav" {
+            size = size.saturating_sub(44); // the header
+        }
+
+        size as f32 / byte_rate as f32
+    }
+}
+
+/// The base64 document file (PDF, plain text, etc.)
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Document {
+    /// The file name
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// The file base64 url
+    pub file_data: String,
+}
+
+impl Document {
+    /// Returns the document MIME type
+    pub fn mime_type(&self) -> &str {
+        media::split_data_url(&self.file_data)
+            .map(|(mime, _)| mime)
+            .unwrap_or("application/pdf")
+    }
+
+    /// Returns the document base64 data (without url prefix)
+    pub fn data(&self) -> &str {
+        media::split_data_url(&self.file_data)
+            .map(|(_, data)| data)
+            .unwrap_or(&self.file_data)
+    }
+
+    /// Decodes the document bytes
+    pub fn bytes(&self) -> Result<Vec<u8>> {
+        media::decode(self.data())
+    }
+
+    /// Counts the PDF pages (1 for other documents)
+    pub fn pages(&self) -> usize {
+        static PAGE_RE: LazyLock<BytesRegex> =
+            LazyLock::new(|| BytesRegex::new(r"/Type\s*/Page\b").unwrap());
+
+        if self.mime_type() != "application/pdf" {
+            return 1;
+        }
+        self.bytes()
+            .map(|bytes| PAGE_RE.find_iter(&bytes).count())
+            .unwrap_or_default()
+            .max(1)
+    }
+}
+
 /// The message content
 #[derive(From, Debug, Clone, Eq, PartialEq)]
 #[from(Bytes, "Content::text(String::from_utf8_lossy(&value))")]
@@ -27,6 +117,14 @@ pub enum Content {
         /// The image detail level (low/high/auto)
         detail: Option<String>,
     },
+    Audio {
+        /// The base64 audio
+        audio: Audio,
+    },
+    Document {
+        /// The base64 document file
+        file: Document,
+    },
 }
 
 impl Content {
@@ -54,6 +152,71 @@ impl Content {
             detail,
         })
     }
+
+    /// Reads image file as preprocessed base64 url (downsized & re-encoded, without EXIF)
+    #[cfg(feature = "image")]
+    pub fn image_file_with(
+        path: impl AsRef<Path>,
+        detail: Option<String>,
+        options: &image::ImageOptions,
+    ) -> Result<Self> {
+        Ok(Self::Image {
+            image: Image {
+                url: image::read_with(path, options)?,
+            },
+            detail,
+        })
+    }
+
+    /// Creates a new base64 audio content (format: wav, mp3, etc.)
+    pub fn audio(base64: impl Into<String>, format: impl Into<String>) -> Result<Self> {
+        let data = base64.into();
+        if !image::validate_base64(&data) {
+            return Err(Error::InvalidBase64Url.into());
+        }
+
+        Ok(Self::Audio {
+            audio: Audio {
+                data,
+                format: format.into(),
+            },
+        })
+    }
+
+    /// Reads audio file as base64
+    pub fn audio_file(path: impl AsRef<Path>) -> Result<Self> {
+        let (mime_type, data) = media::read(path)?;
+
+        Ok(Self::Audio {
+            audio: Audio {
+                data,
+                format: media::audio_format(mime_type).to_string(),
+            },
+        })
+    }
+
+    /// Creates a new document base64 url content (example: "data:application/pdf;base64,JVBERi0x...")
+    pub fn document_url(base64: impl Into<String>, filename: Option<String>) -> Result<Self> {
+        Ok(Self::Document {
+            file: Document {
+                filename,
+                file_data: image::base64(base64)?,
+            },
+        })
+    }
+
+    /// Reads document file (PDF, plain text, etc.) as base64 url
+    pub fn document_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let (mime_type, data) = media::read(path)?;
+
+        Ok(Self::Document {
+            file: Document {
+                filename: path.file_name().map(|s| s.to_string_lossy().to_string()),
+                file_data: str!("data:{mime_type};base64,{data}"),
+            },
+        })
+    }
 }
 
 impl ::serde::Serialize for Content {
@@ -79,6 +242,18 @@ impl ::serde::Serialize for Content {
                 }
                 s.end()
             }
+            Content::Audio { audio } => {
+                let mut s = se.serialize_struct("Content", 2)?;
+                s.serialize_field("type", "input_audio")?;
+                s.serialize_field("input_audio", audio)?;
+                s.end()
+            }
+            Content::Document { file } => {
+                let mut s = se.serialize_struct("Content", 2)?;
+                s.serialize_field("type", "file")?;
+                s.serialize_field("file", file)?;
+                s.end()
+            }
         }
     }
 }
@@ -99,6 +274,8 @@ impl<'de> serde::de::Visitor<'de> for ContentVisitor {
         let mut ctype: Option<String> = None;
         let mut text: Option<String> = None;
         let mut image_url: Option<Image> = None;
+        let mut input_audio: Option<Audio> = None;
+        let mut file: Option<Document> = None;
         let mut detail: Option<String> = None;
 
         while let Some(key) = map.next_key::<String>()? {
@@ -121,6 +298,18 @@ impl<'de> serde::de::Visitor<'de> for ContentVisitor {
                     }
                     image_url = Some(map.next_value()?);
                 }
+                "input_audio" => {
+                    if input_audio.is_some() {
+                        return Err(serde::de::Error::duplicate_field("input_audio"));
+                    }
+                    input_audio = Some(map.next_value()?);
+                }
+                "file" => {
+                    if file.is_some() {
+                        return Err(serde::de::Error::duplicate_field("file"));
+                    }
+                    file = Some(map.next_value()?);
+                }
                 "detail" => {
                     if detail.is_some() {
                         return Err(serde::de::Error::duplicate_field("detail"));
@@ -148,9 +337,18 @@ impl<'de> serde::de::Visitor<'de> for ContentVisitor {
                     detail,
                 })
             }
+            "input_audio" => {
+                let audio =
+                    input_audio.ok_or_else(|| serde::de::Error::missing_field("input_audio"))?;
+                Ok(Content::Audio { audio })
+            }
+            "file" => {
+                let file = file.ok_or_else(|| serde::de::Error::missing_field("file"))?;
+                Ok(Content::Document { file })
+            }
             _ => Err(serde::de::Error::unknown_variant(
                 &ctype,
-                &["text", "image_url"],
+                &["text", "image_url", "input_audio", "file"],
             )),
         }
     }
@@ -161,7 +359,7 @@ impl<'de> serde::Deserialize<'de> for Content {
     where
         D: serde::Deserializer<'de>,
     {
-        const FIELDS: &[&str] = &["type", "text", "image_url", "detail"];
+        const FIELDS: &[&str] = &["type", "text", "image_url", "input_audio", "file", "detail"];
         de.deserialize_struct("Content", FIELDS, ContentVisitor)
     }
 }
@@ -0,0 +1,273 @@
+use super::{Completions, Content, Message};
+use crate::prelude::*;
+use futures::future::BoxFuture;
+
+/// The context window management strategy
+pub trait ContextStrategy: std::fmt::Debug + Send + Sync {
+    /// Fits the messages into the tokens budget (the messages tokens are counted by model tokenizer)
+    fn fit(&self, messages: Vec<Message>, budget: usize) -> BoxFuture<'_, Result<Vec<Message>>>;
+}
+
+/// Returns the summary tokens count of messages
+fn tokens_count(messages: &[Message]) -> usize {
+    messages.iter().map(|msg| msg.tokens_count).sum()
+}
+
+/// Groups the messages into units (an assistant tool calls message with its results is one unit)
+fn into_units(messages: Vec<Message>) -> Vec<Vec<Message>> {
+    let mut units: Vec<Vec<Message>> = Vec::new();
+
+    for msg in messages {
+        if msg.role.is_tool()
+            && let Some(unit) = units.last_mut()
+            && !unit[0].tool_calls.is_empty()
+        {
+            unit.push(msg);
+            continue;
+        }
+        units.push(vec![msg]);
+    }
+
+    units
+}
+
+/// Returns true if it's the tool call unit (the tool calls or their results)
+fn is_tool_unit(unit: &[Message]) -> bool {
+    !unit[0].tool_calls.is_empty() || unit[0].role.is_tool()
+}
+
+/// Drops the leading assistant & tool units left after trimming (the dialog must start with a user message)
+fn drop_leading_replies(units: &mut Vec<Vec<Message>>) {
+    while let Some(idx) = units.iter().position(|unit| !unit[0].role.is_system())
+        && idx + 1 < units.len()
+        && (units[idx][0].role.is_assistant() || units[idx][0].role.is_tool())
+    {
+        units.remove(idx);
+    }
+}
+
+/// Drops the oldest non-system units until the context fits the budget (keeps the last unit)
+fn drop_oldest(messages: &mut Vec<Message>, budget: usize, filter: impl Fn(&[Message]) -> bool) {
+    let mut count = tokens_count(messages);
+    let mut units = into_units(std::mem::take(messages));
+    let mut dropped = false;
+    let mut idx = 0;
+
+    while count > budget && idx + 1 < units.len() {
+        let unit = &units[idx];
+        if unit[0].role.is_system() || !filter(unit) {
+            idx += 1;
+            continue;
+        }
+        count -= tokens_count(unit);
+        units.remove(idx);
+        dropped = true;
+    }
+
+    if dropped {
+        drop_leading_replies(&mut units);
+    }
+    *messages = units.into_iter().flatten().collect();
+}
+
+/// Drops the oldest non-system messages one by one
+#[derive(Debug, Default, Clone)]
+pub struct SlidingWindow;
+
+impl SlidingWindow {
+    /// Creates a new sliding window strategy
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ContextStrategy for SlidingWindow {
+    fn fit(
+        &self,
+        mut messages: Vec<Message>,
+        budget: usize,
+    ) -> BoxFuture<'_, Result<Vec<Message>>> {
+        Box::pin(async move {
+            drop_oldest(&mut messages, budget, |_| true);
+            Ok(messages)
+        })
+    }
+}
+
+/// Keeps the system prompt, the first N and the last M messages (drops the middle of dialog)
+#[derive(Debug, Clone)]
+pub struct KeepFirstLast {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl KeepFirstLast {
+    /// Creates a new first/last strategy
+    pub fn new(first: usize, last: usize) -> Self {
+        Self { first, last }
+    }
+}
+
+impl ContextStrategy for KeepFirstLast {
+    fn fit(&self, messages: Vec<Message>, budget: usize) -> BoxFuture<'_, Result<Vec<Message>>> {
+        Box::pin(async move {
+            if tokens_count(&messages) <= budget {
+                return Ok(messages);
+            }
+
+            // the units are kept whole if any of their messages is in the first/last range:
+            let dialog_len = messages.iter().filter(|m| !m.role.is_system()).count();
+            let mut dialog_idx = 0;
+            let mut units: Vec<Vec<Message>> = into_units(messages)
+                .into_iter()
+                .filter(|unit| {
+                    if unit[0].role.is_system() {
+                        return true;
+                    }
+                    let start = dialog_idx;
+                    dialog_idx += unit.len();
+                    start < self.first || dialog_idx + self.last > dialog_len
+                })
+                .collect();
+            drop_leading_replies(&mut units);
+            let mut messages: Vec<Message> = units.into_iter().flatten().collect();
+
+            // the kept messages still don't fit:
+            drop_oldest(&mut messages, budget, |_| true);
+
+            Ok(messages)
+        })
+    }
+}
+
+/// Drops the oldest tool calls with their results first, then the oldest other messages
+#[derive(Debug, Default, Clone)]
+pub struct DropToolResults;
+
+impl DropToolResults {
+    /// Creates a new tool results dropping strategy
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ContextStrategy for DropToolResults {
+    fn fit(
+        &self,
+        mut messages: Vec<Message>,
+        budget: usize,
+    ) -> BoxFuture<'_, Result<Vec<Message>>> {
+        Box::pin(async move {
+            drop_oldest(&mut messages, budget, is_tool_unit);
+            drop_oldest(&mut messages, budget, |_| true);
+            Ok(messages)
+        })
+    }
+}
+
+/// Summarizes the older dialog turns with a second model call
+#[derive(Debug, Clone)]
+pub struct Summarize {
+    /// The summarizer model request
+    pub completions: Completions,
+    /// The number of last messages to keep as is
+    pub keep_last: usize,
+    /// The summarization instruction
+    pub prompt: String,
+}
+
+impl Summarize {
+    /// Creates a new summarization strategy
+    pub fn new(completions: Completions, keep_last: usize) -> Self {
+        Self {
+            completions,
+            keep_last,
+            prompt: str!(
+                "Summarize the conversation above briefly. Keep the facts, names, decisions and open questions."
+            ),
+        }
+    }
+
+    /// Sets the summarization instruction
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+}
+
+impl ContextStrategy for Summarize {
+    fn fit(&self, messages: Vec<Message>, budget: usize) -> BoxFuture<'_, Result<Vec<Message>>> {
+        Box::pin(async move {
+            if tokens_count(&messages) <= budget {
+                return Ok(messages);
+            }
+
+            // split messages into the older turns and the kept ones (by the whole units):
+            let mut system = Vec::new();
+            let mut dialog = Vec::new();
+            for unit in into_units(messages) {
+                if unit[0].role.is_system() {
+                    system.extend(unit);
+                } else {
+                    dialog.push(unit);
+                }
+            }
+
+            let mut split = dialog.len();
+            let mut kept_len = 0;
+            while split > 0 && kept_len < self.keep_last.max(1) {
+                split -= 1;
+                kept_len += dialog[split].len();
+            }
+
+            // the kept turns start with a user message:
+            while split > 0 && !dialog[split][0].role.is_user() {
+                split -= 1;
+            }
+
+            let kept: Vec<Message> = dialog.split_off(split).into_iter().flatten().collect();
+            let older: Vec<Message> = dialog.into_iter().flatten().collect();
+
+            // summarize the older turns:
+            if !older.is_empty() {
+                let transcript = older
+                    .iter()
+                    .map(|msg| {
+                        let text = msg
+                            .content
+                            .iter()
+                            .filter_map(|c| match c {
+                                Content::Text { text, .. } => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        str!("{}: {text}", msg.role)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+
+                let summary = self
+                    .completions
+                    .clone()
+                    .user_message(vec![transcript.into(), self.prompt.clone().into()])
+                    .send()
+                    .await?
+                    .text()
+                    .await?;
+
+                system.push(Message::system(vec![
+                    str!("The summary of the earlier conversation:\n{summary}").into(),
+                ]));
+            }
+
+            let mut messages = system;
+            messages.extend(kept);
+
+            // the summary still doesn't fit:
+            drop_oldest(&mut messages, budget, |_| true);
+
+            Ok(messages)
+        })
+    }
+}
@@ -0,0 +1,265 @@
+use super::{Content, Message, Role, ToolCall};
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+/// The conversation header (the first line of JSONL file)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ConversationHeader {
+    id: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<String>,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, String>,
+}
+
+/// The conversation history
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Conversation {
+    /// The conversation ID
+    pub id: String,
+    /// The parent conversation ID (if it's a fork)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// The creation time
+    pub created_at: DateTime<Utc>,
+    /// The conversation messages
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    /// The custom metadata
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+}
+
+impl Default for Conversation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Conversation {
+    /// Creates a new conversation with unique ID
+    pub fn new() -> Self {
+        Self::with_id(uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Creates a new conversation with the specified ID
+    pub fn with_id(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            parent_id: None,
+            created_at: Utc::now(),
+            messages: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Returns the messages count
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns true if there are no messages
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns the last message
+    pub fn last(&self) -> Option<&Message> {
+        self.messages.last()
+    }
+
+    /// Returns the summary tokens count
+    pub fn tokens_count(&self) -> usize {
+        self.messages.iter().map(|msg| msg.tokens_count).sum()
+    }
+
+    /// Adds the custom metadata
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Appends a message
+    pub fn push(&mut self, msg: Message) {
+        self.messages.push(msg);
+    }
+
+    /// Adds a messages to conversation
+    pub fn messages(mut self, msgs: Vec<Message>) -> Self {
+        self.messages.extend(msgs);
+        self
+    }
+    /// Adds a messages to conversation
+    pub fn add_messages(&mut self, msgs: Vec<Message>) {
+        self.messages.extend(msgs);
+    }
+
+    /// Adds a message to conversation
+    pub fn message(mut self, role: Role, content: Vec<Content>) -> Self {
+        self.add_message(role, content);
+        self
+    }
+    /// Adds a system message to conversation
+    pub fn system_message(self, content: Vec<Content>) -> Self {
+        self.message(Role::System, content)
+    }
+    /// Adds a user message to conversation
+    pub fn user_message(self, content: Vec<Content>) -> Self {
+        self.message(Role::User, content)
+    }
+    /// Adds a assistant message to conversation
+    pub fn assistant_message(self, content: Vec<Content>) -> Self {
+        self.message(Role::Assistant, content)
+    }
+
+    /// Adds a message to conversation
+    pub fn add_message(&mut self, role: Role, content: Vec<Content>) {
+        self.messages.push(Message::new(role, content));
+    }
+    /// Adds a system message to conversation
+    pub fn add_system_message(&mut self, content: Vec<Content>) {
+        self.add_message(Role::System, content)
+    }
+    /// Adds a user message to conversation
+    pub fn add_user_message(&mut self, content: Vec<Content>) {
+        self.add_message(Role::User, content)
+    }
+    /// Adds a assistant message to conversation
+    pub fn add_assistant_message(&mut self, content: Vec<Content>) {
+        self.add_message(Role::Assistant, content)
+    }
+    /// Adds an assistant tool calls message to conversation (the tool results must follow it)
+    pub fn add_tool_calls_message(&mut self, content: Vec<Content>, calls: Vec<ToolCall>) {
+        self.messages.push(Message::tool_calls(content, calls));
+    }
+    /// Adds a tool call result message to conversation
+    pub fn add_tool_message(&mut self, id: impl Into<String>, content: Vec<Content>) {
+        self.messages.push(Message::tool(id, content));
+    }
+
+    /// Creates a copy of conversation with a new ID
+    pub fn fork(&self) -> Self {
+        self.branch(self.messages.len())
+    }
+
+    /// Creates a copy of conversation with a new ID and the first N messages
+    pub fn branch(&self, len: usize) -> Self {
+        Self {
+            parent_id: Some(self.id.clone()),
+            messages: self.messages.iter().take(len).cloned().collect(),
+            metadata: self.metadata.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// Removes the last dialog turn (the last user message and all the messages after it)
+    pub fn undo(&mut self) -> Vec<Message> {
+        match self.messages.iter().rposition(|msg| msg.role.is_user()) {
+            Some(idx) => self.messages.split_off(idx),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Conversation {
+    /// Serializes the conversation into JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(json::to_string_pretty(self)?)
+    }
+
+    /// Parses the conversation from JSON string
+    pub fn from_json(s: &str) -> Result<Self> {
+        Ok(json::from_str(s)?)
+    }
+
+    /// Serializes the conversation into JSONL string (the header line, then a message per line)
+    pub fn to_jsonl(&self) -> Result<String> {
+        let header = ConversationHeader {
+            id: self.id.clone(),
+            parent_id: self.parent_id.clone(),
+            created_at: self.created_at,
+            metadata: self.metadata.clone(),
+        };
+
+        let mut lines = vec![json::to_string(&header)?];
+        for msg in &self.messages {
+            lines.push(json::to_string(msg)?);
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Parses the conversation from JSONL string
+    pub fn from_jsonl(s: &str) -> Result<Self> {
+        Self::read_jsonl(s.as_bytes())
+    }
+
+    /// Saves the conversation into JSON file
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Loads the conversation from JSON file
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Saves the conversation into JSONL file
+    pub fn save_jsonl(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_jsonl()?)?;
+        Ok(())
+    }
+
+    /// Appends the messages to JSONL file (the file must be already saved)
+    pub fn append_jsonl(path: impl AsRef<Path>, msgs: &[Message]) -> Result<()> {
+        let mut file = fs::OpenOptions::new().append(true).open(path)?;
+        for msg in msgs {
+            writeln!(file, "{}", json::to_string(msg)?)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the conversation from JSONL file
+    pub fn load_jsonl(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read_jsonl(BufReader::new(fs::File::open(path)?))
+    }
+
+    /// Reads the conversation from JSONL lines
+    fn read_jsonl(reader: impl BufRead) -> Result<Self> {
+        let mut lines = reader.lines();
+        let mut this = Self::with_id("");
+
+        // reading header:
+        match lines.next() {
+            Some(line) => {
+                let header: ConversationHeader = json::from_str(&line?)?;
+                this.id = header.id;
+                this.parent_id = header.parent_id;
+                this.created_at = header.created_at;
+                this.metadata = header.metadata;
+            }
+            None => return Err(Error::InvalidConversation.into()),
+        }
+
+        // reading messages:
+        for line in lines {
+            let line = line?;
+            if !line.trim().is_empty() {
+                this.messages.push(json::from_str(&line)?);
+            }
+        }
+
+        Ok(this)
+    }
+}
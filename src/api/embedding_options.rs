@@ -0,0 +1,49 @@
+use crate::prelude::*;
+
+/// The embeddings encoding format
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    /// The JSON numbers array
+    #[default]
+    Float,
+    /// The base64 string (smaller payload, decoded into vector)
+    Base64,
+}
+
+/// The embeddings output data type (Voyage `output_dtype`)
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputDtype {
+    /// The 32-bit floats
+    #[default]
+    Float,
+    /// The 8-bit signed integers
+    Int8,
+    /// The 8-bit unsigned integers
+    Uint8,
+    /// The bit-packed signed integers
+    Binary,
+    /// The bit-packed unsigned integers
+    Ubinary,
+}
+
+impl OutputDtype {
+    /// Returns true if it's a quantized data type
+    pub fn is_quantized(&self) -> bool {
+        !matches!(self, Self::Float)
+    }
+}
+
+/// The too long input truncation (Cohere-style)
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Truncation {
+    /// Returns an error for the too long input
+    None,
+    /// Cuts the input start
+    Start,
+    /// Cuts the input end
+    #[default]
+    End,
+}
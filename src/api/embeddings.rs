@@ -1,13 +1,21 @@
-use super::ApiKind;
-use crate::{AiOptions, chunk::ResponseError, prelude::*};
+use super::{
+    ApiKind, EncodingFormat, OutputDtype, ResponseCache, TaskType, Truncation, Usage,
+    response_cache::cache_key,
+};
+use crate::{
+    AiOptions,
+    cassette::{self, Cassette},
+    chunk::ResponseError,
+    prelude::*,
+    tokenizer_for,
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use futures::{StreamExt, TryStreamExt};
 use reqwest::{Client, Proxy, header};
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-/// The embeddings usage info
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Usage {
-    pub total_tokens: usize,
-}
+/// The max delay between the request retries
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
 
 /// The embeddings response
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -16,6 +24,9 @@ pub struct EmbeddingsData {
     pub data: Vec<Embedding>,
     pub model: String,
     pub usage: Usage,
+    /// The number of embeddings read from the response cache
+    #[serde(default)]
+    pub cache_hits: usize,
 }
 
 /// The embedding chunk
@@ -23,7 +34,126 @@ pub struct EmbeddingsData {
 pub struct Embedding {
     pub object: String,
     pub index: usize,
+    /// The embedding vector (dequantized for the quantized data types)
     pub embedding: Vec<f32>,
+    /// The quantized embedding (for the non-float output data types)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantized: Option<QuantizedEmbedding>,
+}
+
+impl Embedding {
+    /// Creates a new embedding
+    pub fn new(index: usize, embedding: Vec<f32>) -> Self {
+        Self {
+            object: str!("embedding"),
+            index,
+            embedding,
+            quantized: None,
+        }
+    }
+
+    /// Decodes the response embedding (numbers array or base64 string)
+    fn decode(index: usize, value: JsonValue, dtype: OutputDtype) -> Result<Self> {
+        let quantized = match (value, dtype) {
+            // float vector:
+            (JsonValue::String(s), OutputDtype::Float) => {
+                let embedding = BASE64
+                    .decode(s)?
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                return Ok(Self::new(index, embedding));
+            }
+            (value, OutputDtype::Float) => return Ok(Self::new(index, json::from_value(value)?)),
+
+            // quantized vector:
+            (JsonValue::String(s), dtype) => {
+                QuantizedEmbedding::from_bytes(dtype, BASE64.decode(s)?)
+            }
+            (value, OutputDtype::Int8) => QuantizedEmbedding::Int8(json::from_value(value)?),
+            (value, OutputDtype::Uint8) => QuantizedEmbedding::Uint8(json::from_value(value)?),
+            (value, OutputDtype::Binary) => QuantizedEmbedding::Binary(json::from_value(value)?),
+            (value, OutputDtype::Ubinary) => QuantizedEmbedding::Ubinary(json::from_value(value)?),
+        };
+
+        Ok(Self {
+            embedding: quantized.to_vector(),
+            quantized: Some(quantized),
+            ..Self::new(index, Vec::new())
+        })
+    }
+
+    /// Truncates the vector to dimensions and normalizes it (Matryoshka embeddings)
+    fn truncate(&mut self, dims: usize) {
+        if self.quantized.is_some() || self.embedding.len() <= dims {
+            return;
+        }
+        self.embedding.truncate(dims);
+
+        let norm = self.embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            self.embedding.iter_mut().for_each(|v| *v /= norm);
+        }
+    }
+}
+
+/// The quantized embedding vector
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "dtype", content = "values", rename_all = "lowercase")]
+pub enum QuantizedEmbedding {
+    /// The 8-bit signed integers
+    Int8(Vec<i8>),
+    /// The 8-bit unsigned integers
+    Uint8(Vec<u8>),
+    /// The bit-packed signed integers (offset binary)
+    Binary(Vec<i8>),
+    /// The bit-packed unsigned integers
+    Ubinary(Vec<u8>),
+}
+
+impl QuantizedEmbedding {
+    /// Creates the quantized embedding from raw bytes
+    fn from_bytes(dtype: OutputDtype, bytes: Vec<u8>) -> Self {
+        match dtype {
+            OutputDtype::Int8 | OutputDtype::Float => {
+                Self::Int8(bytes.into_iter().map(|b| b as i8).collect())
+            }
+            OutputDtype::Uint8 => Self::Uint8(bytes),
+            OutputDtype::Binary => Self::Binary(bytes.into_iter().map(|b| b as i8).collect()),
+            OutputDtype::Ubinary => Self::Ubinary(bytes),
+        }
+    }
+
+    /// Returns the data type
+    pub fn dtype(&self) -> OutputDtype {
+        match self {
+            Self::Int8(_) => OutputDtype::Int8,
+            Self::Uint8(_) => OutputDtype::Uint8,
+            Self::Binary(_) => OutputDtype::Binary,
+            Self::Ubinary(_) => OutputDtype::Ubinary,
+        }
+    }
+
+    /// Converts into float vector (the packed bits are unpacked into `1.0`/`-1.0` values)
+    pub fn to_vector(&self) -> Vec<f32> {
+        let unpack = |bytes: &mut dyn Iterator<Item = u8>| -> Vec<f32> {
+            bytes
+                .flat_map(|b| {
+                    (0..8)
+                        .rev()
+                        .map(move |i| if b >> i & 1 == 1 { 1.0 } else { -1.0 })
+                })
+                .collect()
+        };
+
+        match self {
+            Self::Int8(v) => v.iter().map(|&x| x as f32).collect(),
+            Self::Uint8(v) => v.iter().map(|&x| x as f32).collect(),
+            Self::Binary(v) => unpack(&mut v.iter().map(|&x| (x as i16 + 128) as u8)),
+            Self::Ubinary(v) => unpack(&mut v.iter().copied()),
+        }
+    }
 }
 
 /// The LM API embeddings request
@@ -51,6 +181,42 @@ pub struct Embeddings {
     pub model: String,
     /// The input texts
     pub input: Vec<String>,
+    /// The embeddings task type (Gemini `taskType`, Voyage `input_type`)
+    #[serde(skip)]
+    pub task_type: Option<TaskType>,
+    /// The document title (Gemini only, for `RetrievalDocument` task)
+    #[serde(skip)]
+    pub title: Option<String>,
+    /// The output embedding dimensions (the longer vectors are truncated & normalized)
+    #[serde(skip)]
+    pub dimensions: Option<usize>,
+    /// The response encoding format
+    #[serde(skip)]
+    pub encoding_format: EncodingFormat,
+    /// The output data type (Voyage, Cohere)
+    #[serde(skip)]
+    pub output_dtype: OutputDtype,
+    /// The too long input truncation (Voyage, Cohere)
+    #[serde(skip)]
+    pub truncation: Option<Truncation>,
+    /// The responses cache (the embeddings are cached per input)
+    #[serde(skip)]
+    pub response_cache: Option<Arc<dyn ResponseCache>>,
+    /// The HTTP exchanges recorder/player
+    #[serde(skip)]
+    pub cassette: Option<Arc<Cassette>>,
+    /// The max inputs count per batch request (the API limit by default)
+    #[serde(skip)]
+    pub batch_size: Option<usize>,
+    /// The max input tokens per batch request (the API limit by default)
+    #[serde(skip)]
+    pub batch_tokens: Option<usize>,
+    /// The max concurrent batch requests
+    #[serde(skip)]
+    pub concurrency: usize,
+    /// The max retries of the failed batch request
+    #[serde(skip)]
+    pub retries: usize,
 }
 
 impl Embeddings {
@@ -73,6 +239,18 @@ impl Embeddings {
             timeout: Duration::from_secs(30),
             model: model.into(),
             input: Vec::new(),
+            task_type: None,
+            title: None,
+            dimensions: None,
+            encoding_format: EncodingFormat::Float,
+            output_dtype: OutputDtype::Float,
+            truncation: None,
+            response_cache: None,
+            cassette: None,
+            batch_size: None,
+            batch_tokens: None,
+            concurrency: 4,
+            retries: 2,
         }
     }
 
@@ -121,6 +299,11 @@ impl Embeddings {
         Self::new(ApiKind::Voyage, key, model)
     }
 
+    /// Creates a new Cohere embeddings request
+    pub fn cohere(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::Cohere, key, model)
+    }
+
     /// Creates a new Google AI embeddings request
     pub fn google(key: impl Into<String>, model: impl Into<String>) -> Self {
         Self::new(ApiKind::Google, key, model)
@@ -183,10 +366,244 @@ impl Embeddings {
         self
     }
 
+    /// Sets the embeddings task type (Gemini `taskType`, Voyage `input_type` query/document)
+    pub fn task_type(mut self, task: TaskType) -> Self {
+        self.task_type.replace(task);
+        self
+    }
+
+    /// Sets the document title (Gemini only, for `RetrievalDocument` task)
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title.replace(title.into());
+        self
+    }
+
+    /// Sets the output embedding dimensions
+    pub fn dimensions(mut self, dims: usize) -> Self {
+        self.dimensions.replace(dims);
+        self
+    }
+
+    /// Sets the response encoding format (base64 cuts the payload size)
+    pub fn encoding_format(mut self, format: EncodingFormat) -> Self {
+        self.encoding_format = format;
+        self
+    }
+
+    /// Sets the output data type (Voyage, Cohere)
+    pub fn output_dtype(mut self, dtype: OutputDtype) -> Self {
+        self.output_dtype = dtype;
+        self
+    }
+
+    /// Sets the too long input truncation (Voyage, Cohere)
+    pub fn truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation.replace(truncation);
+        self
+    }
+
+    /// Sets the responses cache (only the missing inputs are sent)
+    pub fn response_cache(mut self, cache: Arc<dyn ResponseCache>) -> Self {
+        self.response_cache.replace(cache);
+        self
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Sets the max inputs count per batch request
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size.replace(size);
+        self
+    }
+
+    /// Sets the max input tokens per batch request
+    pub fn batch_tokens(mut self, tokens: usize) -> Self {
+        self.batch_tokens.replace(tokens);
+        self
+    }
+
+    /// Sets the max concurrent batch requests
+    pub fn concurrency(mut self, count: usize) -> Self {
+        self.concurrency = count;
+        self
+    }
+
+    /// Sets the max retries of the failed batch request (the transport errors, 429 and 5xx statuses)
+    pub fn retries(mut self, count: usize) -> Self {
+        self.retries = count;
+        self
+    }
+
+    /// Sends the inputs in batches (split by count & tokens) with bounded concurrency and retries
+    pub async fn send_batched(&self) -> Result<EmbeddingsData> {
+        let template = Self {
+            input: Vec::new(),
+            ..self.clone()
+        };
+
+        let mut responses: Vec<(usize, EmbeddingsData)> =
+            futures::stream::iter(self.batches().into_iter().map(|(offset, input)| {
+                let request = Self {
+                    input,
+                    ..template.clone()
+                };
+                async move { Ok::<_, DynError>((offset, request.send_retrying().await?)) }
+            }))
+            .buffer_unordered(self.concurrency.max(1))
+            .try_collect()
+            .await?;
+        responses.sort_by_key(|(offset, _)| *offset);
+
+        // reassemble the results in input order:
+        let mut output = EmbeddingsData {
+            object: str!("list"),
+            data: Vec::with_capacity(self.input.len()),
+            model: self.model.clone(),
+            usage: Usage::default(),
+            cache_hits: 0,
+        };
+
+        for (offset, response) in responses {
+            output.data.extend(response.data.into_iter().map(|mut emb| {
+                emb.index += offset;
+                emb
+            }));
+            output.usage += &response.usage;
+            output.cache_hits += response.cache_hits;
+            output.model = response.model;
+        }
+        output.data.sort_by_key(|emb| emb.index);
+
+        Ok(output)
+    }
+
+    /// Splits the inputs into batches (with the first input offsets)
+    fn batches(&self) -> Vec<(usize, Vec<String>)> {
+        let max_inputs = self
+            .batch_size
+            .unwrap_or_else(|| self.api_kind.embeddings_batch_size())
+            .max(1);
+        let max_tokens = self
+            .batch_tokens
+            .or_else(|| self.api_kind.embeddings_batch_tokens());
+        let tokenizer = max_tokens.map(|_| tokenizer_for(&self.api_kind, &self.model));
+
+        let mut batches: Vec<(usize, Vec<String>)> = Vec::new();
+        let mut tokens = 0;
+
+        for (idx, input) in self.input.iter().enumerate() {
+            let count = tokenizer
+                .as_ref()
+                .map(|t| t.count_tokens(input))
+                .unwrap_or(0);
+
+            // start a new batch if the limits are exceeded (an oversized input goes alone):
+            let full = batches.last().is_none_or(|(_, batch)| {
+                batch.len() >= max_inputs || max_tokens.is_some_and(|max| tokens + count > max)
+            });
+            if full {
+                batches.push((idx, Vec::new()));
+                tokens = 0;
+            }
+
+            if let Some((_, batch)) = batches.last_mut() {
+                batch.push(input.clone());
+            }
+            tokens += count;
+        }
+
+        batches
+    }
+
+    /// Sends the request with retries (exponential backoff)
+    async fn send_retrying(&self) -> Result<EmbeddingsData> {
+        let mut attempt = 0;
+        loop {
+            match self.send().await {
+                Err(e) if attempt < self.retries && is_retryable(&e) => {
+                    let delay = Duration::from_millis(500u64.saturating_mul(1 << attempt.min(6)))
+                        .min(MAX_BACKOFF);
+                    log::warn!("Embeddings request failed (retry in {delay:?}): {e}");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Sends the request to LM server
-    pub async fn send(&mut self) -> Result<EmbeddingsData> {
-        // generate URL:
-        let url = if let Some(host) = &self.host {
+    pub async fn send(&self) -> Result<EmbeddingsData> {
+        let Some(cache) = &self.response_cache else {
+            return self.request(&self.input).await;
+        };
+
+        // read the cached embeddings:
+        let prefix = str!("{}\n{}", self.url(), self.request_data(&[])?);
+        let mut keys = Vec::with_capacity(self.input.len());
+        let mut embeddings = vec![None; self.input.len()];
+        let mut misses = Vec::new();
+
+        for (idx, input) in self.input.iter().enumerate() {
+            let key = cache_key(&str!("{prefix}\n{input}"));
+            match cache.get(&key).await? {
+                Some(data) => embeddings[idx] = Some(json::from_value::<Embedding>(data)?),
+                None => misses.push(idx),
+            }
+            keys.push(key);
+        }
+
+        let mut output = EmbeddingsData {
+            object: str!("list"),
+            data: Vec::new(),
+            model: self.model.clone(),
+            usage: Usage::default(),
+            cache_hits: self.input.len() - misses.len(),
+        };
+
+        // request the missing embeddings:
+        if !misses.is_empty() {
+            let input: Vec<String> = misses.iter().map(|&idx| self.input[idx].clone()).collect();
+            let response = self.request(&input).await?;
+
+            for emb in response.data {
+                let Some(&idx) = misses.get(emb.index) else {
+                    continue;
+                };
+                if let Err(e) = cache.put(&keys[idx], json!(emb)).await {
+                    log::warn!("Failed to save the embedding into cache: {e}");
+                }
+                embeddings[idx] = Some(emb);
+            }
+
+            output.object = response.object;
+            output.model = response.model;
+            output.usage = response.usage;
+        }
+
+        let missing: Vec<usize> = (0..embeddings.len())
+            .filter(|&idx| embeddings[idx].is_none())
+            .collect();
+        if !missing.is_empty() {
+            return Err(missing_embeddings(&missing));
+        }
+
+        output.data = embeddings
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, embedding)| embedding.map(|emb| Embedding { index, ..emb }))
+            .collect();
+
+        Ok(output)
+    }
+
+    /// Returns the request URL
+    fn url(&self) -> String {
+        if let Some(host) = &self.host {
             str!(
                 "{host}{}{}",
                 if host.ends_with("/") { "" } else { "/" },
@@ -198,30 +615,117 @@ impl Embeddings {
                 self.api_kind.host(),
                 self.api_kind.embeddings_path(&self.model)
             )
-        };
-
-        // serialize request data:
-        let mut data = json::to_value(&self).map_err(Error::from)?;
-        let obj = data.as_object_mut().unwrap();
+        }
+    }
 
+    /// Serializes the request data with the input texts
+    fn request_data(&self, input: &[String]) -> Result<JsonValue> {
         if self.api_kind.is_google() {
-            let parts: Vec<JsonValue> = self
-                .input
+            let mut options = json!({ "model": str!("models/{}", self.model) });
+            if let Some(task) = &self.task_type {
+                options["taskType"] = json!(task);
+            }
+            if let Some(title) = &self.title {
+                options["title"] = json!(title);
+            }
+            if let Some(dims) = self.dimensions {
+                options["outputDimensionality"] = json!(dims);
+            }
+
+            // one request per input text:
+            let requests: Vec<JsonValue> = input
                 .iter()
-                .map(|text| json!({ "text": text }))
+                .map(|text| {
+                    let mut request = options.clone();
+                    request["content"] = json!({ "parts": [{ "text": text }] });
+                    request
+                })
                 .collect();
 
-            *obj = json!({
-                "content": { "parts": parts }
-            })
-            .as_object()
-            .unwrap()
-            .clone();
+            return Ok(json!({
+                "requests": if input.is_empty() { vec![options] } else { requests }
+            }));
+        }
+
+        if self.api_kind == ApiKind::Cohere {
+            return Ok(self.cohere_data(input));
         }
 
+        let mut data = json::to_value(self).map_err(Error::from)?;
+        data["input"] = json!(input);
+
+        if self.api_kind == ApiKind::Voyage {
+            if let Some(dims) = self.dimensions {
+                data["output_dimension"] = json!(dims);
+            }
+            match self.task_type {
+                Some(TaskType::RetrievalQuery | TaskType::CodeRetrievalQuery) => {
+                    data["input_type"] = json!("query")
+                }
+                Some(TaskType::RetrievalDocument) => data["input_type"] = json!("document"),
+                _ => {}
+            }
+            if self.output_dtype.is_quantized() {
+                data["output_dtype"] = json!(self.output_dtype);
+            }
+            if let Some(truncation) = self.truncation {
+                data["truncation"] = json!(truncation != Truncation::None);
+            }
+        } else {
+            if let Some(dims) = self.dimensions {
+                data["dimensions"] = json!(dims);
+            }
+            if self.truncation.is_some() {
+                log::warn!(
+                    "The 'truncate' param isn't supported by {} API, skipped",
+                    self.api_kind
+                );
+            }
+        }
+
+        if self.encoding_format == EncodingFormat::Base64 {
+            data["encoding_format"] = json!(self.encoding_format);
+        }
+
+        Ok(data)
+    }
+
+    /// Serializes the `Cohere` v2 embed request data (`texts`, `input_type` and `embedding_types`)
+    fn cohere_data(&self, input: &[String]) -> JsonValue {
+        let input_type = match self.task_type {
+            Some(TaskType::RetrievalQuery | TaskType::CodeRetrievalQuery) => "search_query",
+            Some(TaskType::Classification) => "classification",
+            Some(TaskType::Clustering) => "clustering",
+            _ => "search_document",
+        };
+        let mut data = json!({
+            "model": self.model,
+            "texts": input,
+            "input_type": input_type,
+            "embedding_types": [self.output_dtype],
+        });
+
+        if let Some(dims) = self.dimensions {
+            data["output_dimension"] = json!(dims);
+        }
+        if let Some(truncation) = self.truncation {
+            data["truncate"] = json!(truncation);
+        }
+        if self.encoding_format == EncodingFormat::Base64 {
+            log::warn!("The base64 encoding isn't supported by Cohere API, skipped");
+        }
+
+        data
+    }
+
+    /// Sends the request with the input texts
+    async fn request(&self, input: &[String]) -> Result<EmbeddingsData> {
+        let url = self.url();
+        let data = self.request_data(input)?;
+
         // create client & configure proxy:
         let mut client = Client::builder().timeout(self.timeout);
-        if let Some(proxy) = self.proxy.take() {
+        if let Some(proxy) = self.proxy.clone() {
             client = client.proxy(proxy);
             client = client.danger_accept_invalid_certs(true); // VPN SSL
         }
@@ -231,7 +735,7 @@ impl Embeddings {
             .build()?
             .post(&url)
             .header(header::CONTENT_TYPE, "application/json")
-            .json(&obj);
+            .json(&data);
 
         // set api key:
         if self.api_kind.is_google() {
@@ -240,8 +744,7 @@ impl Embeddings {
             request = request.header(header::AUTHORIZATION, str!("Bearer {}", self.api_key));
         }
 
-        let response = request.send().await.map_err(Error::from)?;
-        let output = response.text().await?;
+        let output = cassette::send_text(request, &url, &data, self.cassette.as_ref()).await?;
 
         // check for an error:
         if let Some(e) = ResponseError::from_str(&output) {
@@ -249,10 +752,141 @@ impl Embeddings {
         }
 
         // else parse response:
-        let embeddings = json::from_str(&output)?;
+        let mut embeddings = if self.api_kind.is_google() {
+            self.parse_google(json::from_str(&output)?)?
+        } else if self.api_kind == ApiKind::Cohere {
+            self.parse_cohere(json::from_str(&output)?)?
+        } else {
+            self.parse(json::from_str(&output)?)?
+        };
+
+        // check every input has an embedding:
+        let missing: Vec<usize> = (0..input.len())
+            .filter(|&idx| !embeddings.data.iter().any(|emb| emb.index == idx))
+            .collect();
+        if !missing.is_empty() {
+            return Err(missing_embeddings(&missing));
+        }
+
+        // truncate the longer vectors:
+        if let Some(dims) = self.dimensions {
+            embeddings
+                .data
+                .iter_mut()
+                .for_each(|emb| emb.truncate(dims));
+        }
 
         Ok(embeddings)
     }
+
+    /// Converts the OpenAI/Voyage response (decodes the base64 & quantized vectors)
+    fn parse(&self, mut output: JsonValue) -> Result<EmbeddingsData> {
+        let data = match output["data"].take() {
+            JsonValue::Array(list) => list,
+            _ => Vec::new(),
+        };
+
+        let data = data
+            .into_iter()
+            .enumerate()
+            .map(|(idx, mut emb)| {
+                let index = emb["index"].as_u64().map_or(idx, |i| i as usize);
+                Embedding::decode(index, emb["embedding"].take(), self.output_dtype)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(EmbeddingsData {
+            object: output["object"].as_str().unwrap_or("list").to_string(),
+            data,
+            model: output["model"].as_str().unwrap_or(&self.model).to_string(),
+            usage: json::from_value(output["usage"].take()).unwrap_or_default(),
+            cache_hits: 0,
+        })
+    }
+
+    /// Converts the Cohere response (`{embeddings:{float:[...]}, meta:{billed_units}}`)
+    fn parse_cohere(&self, mut output: JsonValue) -> Result<EmbeddingsData> {
+        let dtype = json::to_value(self.output_dtype)?;
+        let key = dtype.as_str().unwrap_or("float");
+        let values = match output["embeddings"][key].take() {
+            JsonValue::Array(list) => list,
+            _ => Vec::new(),
+        };
+
+        let data = values
+            .into_iter()
+            .enumerate()
+            .map(|(index, emb)| Embedding::decode(index, emb, self.output_dtype))
+            .collect::<Result<Vec<_>>>()?;
+
+        let tokens = output["meta"]["billed_units"]["input_tokens"]
+            .as_u64()
+            .unwrap_or_default() as usize;
+
+        Ok(EmbeddingsData {
+            object: str!("list"),
+            data,
+            model: self.model.clone(),
+            usage: Usage {
+                input_tokens: tokens,
+                total_tokens: tokens,
+                ..Default::default()
+            },
+            cache_hits: 0,
+        })
+    }
+
+    /// Converts the Gemini response (`{embeddings:[{values}]}` or `{embedding:{values}}`)
+    fn parse_google(&self, mut output: JsonValue) -> Result<EmbeddingsData> {
+        let values = match output["embeddings"].take() {
+            JsonValue::Array(list) => list,
+            _ => vec![output["embedding"].take()],
+        };
+
+        let data = values
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut emb)| {
+                Ok(Embedding::new(
+                    index,
+                    json::from_value(emb["values"].take())?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(EmbeddingsData {
+            object: str!("list"),
+            data,
+            model: self.model.clone(),
+            usage: Usage::default(),
+            cache_hits: 0,
+        })
+    }
+}
+
+/// Returns true if the request error is temporary (the transport errors, 429 and 5xx statuses)
+fn is_retryable(err: &DynError) -> bool {
+    let retryable_status = |status: u16| status == 429 || status >= 500;
+
+    if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+        return e.status().is_none_or(|s| retryable_status(s.as_u16()));
+    }
+    match err.downcast_ref::<Error>() {
+        Some(Error::HttpStatus(status, _)) => retryable_status(*status),
+        Some(Error::Request(e)) => e.status().is_none_or(|s| retryable_status(s.as_u16())),
+        Some(Error::Io(_)) => true,
+        _ => false,
+    }
+}
+
+/// Returns the missing embeddings error (with the input indices)
+fn missing_embeddings(indices: &[usize]) -> DynError {
+    let indices = indices
+        .iter()
+        .map(|idx| idx.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Error::MissingEmbeddings(indices).into()
 }
 
 impl TryFrom<AiOptions> for Embeddings {
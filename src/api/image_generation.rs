@@ -0,0 +1,440 @@
+use super::{ApiKind, Usage};
+use crate::{
+    AiOptions,
+    cassette::{self, Cassette},
+    chunk::ResponseError,
+    media,
+    prelude::*,
+};
+use reqwest::{
+    Client, Proxy, header,
+    multipart::{Form, Part},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+/// The generated image
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GeneratedImage {
+    /// The image MIME type
+    pub mime: String,
+    /// The image bytes
+    #[serde(with = "crate::media::base64_bytes")]
+    pub bytes: Bytes,
+    /// The prompt revised by model
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revised_prompt: Option<String>,
+}
+
+impl GeneratedImage {
+    /// Saves the image into file (the extension is added by MIME type if the path doesn't have it)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        media::save(path, &self.mime, &self.bytes)
+    }
+}
+
+/// The image generation response
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ImagesData {
+    pub images: Vec<GeneratedImage>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+impl ImagesData {
+    /// Saves the images into files with numbered names (example: "out/cat" -> "out/cat-1.png")
+    pub fn save_all(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| str!("image"));
+
+        self.images
+            .iter()
+            .enumerate()
+            .map(|(i, image)| image.save(path.with_file_name(str!("{name}-{}", i + 1))))
+            .collect()
+    }
+}
+
+/// The LM API image generation (or editing) request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImageGeneration {
+    /// The API standart
+    #[serde(skip)]
+    pub api_kind: ApiKind,
+    /// The API authorization key
+    #[serde(skip)]
+    pub api_key: String,
+    /// The custom server host
+    #[serde(skip)]
+    pub host: Option<String>,
+    /// The proxy tunnel settings
+    #[serde(skip)]
+    pub proxy: Option<Proxy>,
+    /// The connection timeout
+    #[serde(skip)]
+    pub timeout: Duration,
+    /// The AI model name
+    pub model: String,
+    /// The image description (or the editing instructions)
+    pub prompt: String,
+    /// The images count
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    /// The image size (1024x1024, 1536x1024, auto, etc.)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// The image quality (low, medium, high, auto, etc.)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    /// The background (transparent, opaque, auto)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    /// The image format (png, jpeg, webp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    /// The response format (url, b64_json), the URLs are downloaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    /// The source images to edit (file name & bytes)
+    #[serde(skip)]
+    pub images: Vec<(String, Bytes)>,
+    /// The editing mask (file name & bytes)
+    #[serde(skip)]
+    pub mask: Option<(String, Bytes)>,
+    /// The HTTP exchanges recorder/player
+    #[serde(skip)]
+    pub cassette: Option<Arc<Cassette>>,
+}
+
+impl ImageGeneration {
+    /// Creates a new LM image generation request
+    pub fn new(kind: ApiKind, key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            host: if kind.is_lmstudio() {
+                Some(str!("http://127.0.0.1:1234"))
+            } else {
+                None
+            },
+            api_kind: kind,
+            api_key: key.into(),
+            proxy: None,
+            timeout: Duration::from_secs(180),
+            model: model.into(),
+            prompt: String::new(),
+            n: None,
+            size: None,
+            quality: None,
+            background: None,
+            output_format: None,
+            response_format: None,
+            images: Vec::new(),
+            mask: None,
+            cassette: None,
+        }
+    }
+
+    /// Creates a new OpenAI (ChatGPT) image generation request
+    pub fn openai(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::OpenAI, key, model)
+    }
+
+    /// Creates a new LM Studio (or other local server) image generation request
+    pub fn lmstudio(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::LmStudio, key, model)
+    }
+
+    /// Creates a new ChatGPT image generation request
+    pub fn chatgpt(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::ChatGpt, key, model)
+    }
+
+    /// Sets the LM API authorization key
+    pub fn set_key(&mut self, key: impl Into<String>) {
+        self.api_key = key.into();
+    }
+    /// Sets the LM API authorization key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.set_key(key);
+        self
+    }
+
+    /// Sets the custom API server host
+    pub fn host(mut self, url: impl Into<String>) -> Self {
+        self.host = Some(url.into());
+        self
+    }
+
+    /// Sets a proxy tunnel settings
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets a connection timeout
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Sets a connection timeout (from seconds)
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_secs(secs);
+        self
+    }
+
+    /// Sets a connection timeout (from millis)
+    pub fn timeout_ms(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_millis(secs);
+        self
+    }
+
+    /// Sets the LM model name
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the image description (or the editing instructions)
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Sets the images count
+    pub fn n(mut self, n: u32) -> Self {
+        self.n.replace(n);
+        self
+    }
+
+    /// Sets the image size (1024x1024, 1536x1024, auto, etc.)
+    pub fn size(mut self, size: impl Into<String>) -> Self {
+        self.size.replace(size.into());
+        self
+    }
+
+    /// Sets the image quality (low, medium, high, auto, etc.)
+    pub fn quality(mut self, quality: impl Into<String>) -> Self {
+        self.quality.replace(quality.into());
+        self
+    }
+
+    /// Sets the background (transparent, opaque, auto)
+    pub fn background(mut self, background: impl Into<String>) -> Self {
+        self.background.replace(background.into());
+        self
+    }
+
+    /// Sets the image format (png, jpeg, webp)
+    pub fn output_format(mut self, format: impl Into<String>) -> Self {
+        self.output_format.replace(format.into());
+        self
+    }
+
+    /// Sets the response format (url, b64_json)
+    pub fn response_format(mut self, format: impl Into<String>) -> Self {
+        self.response_format.replace(format.into());
+        self
+    }
+
+    /// Adds the source image to edit (switches to the editing endpoint)
+    pub fn image(mut self, file_name: impl Into<String>, bytes: impl Into<Bytes>) -> Self {
+        self.images.push((file_name.into(), bytes.into()));
+        self
+    }
+
+    /// Reads the source image file to edit (switches to the editing endpoint)
+    pub fn image_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let (name, bytes) = read_file(path.as_ref())?;
+        Ok(self.image(name, bytes))
+    }
+
+    /// Sets the editing mask (the transparent areas are edited)
+    pub fn mask(mut self, file_name: impl Into<String>, bytes: impl Into<Bytes>) -> Self {
+        self.mask.replace((file_name.into(), bytes.into()));
+        self
+    }
+
+    /// Reads the editing mask file
+    pub fn mask_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let (name, bytes) = read_file(path.as_ref())?;
+        Ok(self.mask(name, bytes))
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Returns true if it's an editing request
+    pub fn is_edit(&self) -> bool {
+        !self.images.is_empty()
+    }
+
+    /// Sends the request to LM server
+    pub async fn send(&self) -> Result<ImagesData> {
+        if !self.api_kind.is_openai() {
+            return Err(Error::Unsupported(str!("image generation"), self.api_kind.clone()).into());
+        }
+
+        let path = if self.is_edit() {
+            self.api_kind.images_edits_path()
+        } else {
+            self.api_kind.images_generations_path()
+        };
+        let url = if let Some(host) = &self.host {
+            str!("{host}{}{path}", if host.ends_with("/") { "" } else { "/" })
+        } else {
+            str!("{}/{path}", self.api_kind.host())
+        };
+
+        // serialize request data:
+        let mut data = json::to_value(self).map_err(Error::from)?;
+
+        // create client & configure proxy:
+        let mut client = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client = client.proxy(proxy);
+            client = client.danger_accept_invalid_certs(true); // VPN SSL
+        }
+        let client = client.build()?;
+
+        // build request (the edits are sent as multipart form):
+        let mut request = client
+            .post(&url)
+            .header(header::AUTHORIZATION, str!("Bearer {}", self.api_key));
+
+        if self.is_edit() {
+            let mut form = Form::new();
+            for (name, value) in data.as_object().into_iter().flatten() {
+                match value {
+                    JsonValue::String(s) => form = form.text(name.clone(), s.clone()),
+                    JsonValue::Number(n) => form = form.text(name.clone(), n.to_string()),
+                    _ => {}
+                }
+            }
+
+            let field = if self.images.len() > 1 {
+                "image[]"
+            } else {
+                "image"
+            };
+            for (name, bytes) in &self.images {
+                form = form.part(field, file_part(name, bytes)?);
+            }
+            if let Some((name, bytes)) = &self.mask {
+                form = form.part("mask", file_part(name, bytes)?);
+            }
+
+            // the file names are recorded by cassette:
+            data["image"] = json!(self.images.iter().map(|(name, _)| name).collect::<Vec<_>>());
+            if let Some((name, _)) = &self.mask {
+                data["mask"] = json!(name);
+            }
+
+            request = request.multipart(form);
+        } else {
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .json(&data);
+        }
+
+        let output = cassette::send_text(request, &url, &data, self.cassette.as_ref()).await?;
+
+        // check for an error:
+        if let Some(e) = ResponseError::from_str(&output) {
+            return Err(Error::ResponseError(e).into());
+        }
+
+        // else parse response:
+        let mut output: JsonValue = json::from_str(&output)?;
+        let default_mime = media::mime_type(str!(
+            "image.{}",
+            output["output_format"]
+                .as_str()
+                .or(self.output_format.as_deref())
+                .unwrap_or("png")
+        ));
+
+        let mut images = Vec::new();
+        for item in output["data"].as_array().into_iter().flatten() {
+            let bytes = if let Some(data) = item["b64_json"].as_str() {
+                Bytes::from(media::decode(data)?)
+            } else if let Some(image_url) = item["url"].as_str() {
+                // download the image:
+                let request = client.get(image_url);
+                cassette::send_bytes(request, image_url, &JsonValue::Null, self.cassette.as_ref())
+                    .await?
+            } else {
+                continue;
+            };
+
+            images.push(GeneratedImage {
+                mime: media::image_mime(&bytes)
+                    .unwrap_or(default_mime)
+                    .to_string(),
+                bytes,
+                revised_prompt: item["revised_prompt"].as_str().map(String::from),
+            });
+        }
+
+        Ok(ImagesData {
+            images,
+            usage: json::from_value(output["usage"].take()).ok(),
+        })
+    }
+}
+
+/// Reads the image file (file name & bytes)
+fn read_file(path: &Path) -> Result<(String, Bytes)> {
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| str!("image.png"));
+
+    Ok((name, Bytes::from(std::fs::read(path)?)))
+}
+
+/// Creates the multipart file part
+fn file_part(name: &str, bytes: &Bytes) -> Result<Part> {
+    Ok(Part::bytes(bytes.to_vec())
+        .file_name(name.to_string())
+        .mime_str(media::mime_type(name))?)
+}
+
+impl TryFrom<AiOptions> for ImageGeneration {
+    type Error = DynError;
+
+    fn try_from(ops: AiOptions) -> Result<Self> {
+        let mut this = Self::new(
+            // choose AI service
+            ops.kind,
+            // read API key
+            if let Some(v) = ops.env_var.as_ref() {
+                std::env::var(v).unwrap_or_default()
+            } else {
+                String::new()
+            },
+            // choose model
+            ops.model,
+        );
+
+        // set default server host:
+        if let Some(host) = ops.server.as_ref() {
+            this = this.host(host.to_owned());
+        }
+        // set proxy options:
+        if let Some(proxy) = ops.proxy.as_ref() {
+            this = this.proxy(Proxy::all(proxy.to_owned())?);
+        }
+
+        Ok(this)
+    }
+}
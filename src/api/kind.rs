@@ -8,13 +8,16 @@ pub const PERPLEXITY_HOST: &str = "https://api.perplexity.ai";
 pub const ANTHROPIC_HOST: &str = "https://api.anthropic.com";
 pub const VOYAGE_HOST: &str = "https://api.voyageai.com";
 pub const GOOGLE_HOST: &str = "https://generativelanguage.googleapis.com";
+pub const COHERE_HOST: &str = "https://api.cohere.com";
+pub const JINA_HOST: &str = "https://api.jina.ai";
 
 /// The AI API type
-#[derive(Clone, Debug, Display, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Default, Clone, Debug, Display, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum ApiKind {
     /// ------- API STANDARTS: ---------
     /// OpenAI API (/v1/chat/completions, /v1/embeddings)
+    #[default]
     OpenAI,
     /// Anthropic API (/v1/messages, /v0/embeddings)
     Anthropic,
@@ -38,6 +41,12 @@ pub enum ApiKind {
     Claude,
     /// Embeddings models (instead Anthropic embeddings)
     Voyage,
+    /// Cohere rerank & embedding models
+    Cohere,
+    /// Jina embeddings & rerank models (OpenAI compatible)
+    Jina,
+    /// OpenAI Responses API (/v1/responses, OpenAI compatible for other endpoints)
+    Responses,
 }
 
 impl ApiKind {
@@ -56,6 +65,11 @@ impl ApiKind {
         matches!(self, Self::Google | Self::Gemini)
     }
 
+    /// Returns true if it's OpenAI Responses API
+    pub fn is_responses(&self) -> bool {
+        matches!(self, Self::Responses)
+    }
+
     /// Returns true if it's LM Studio API
     pub fn is_lmstudio(&self) -> bool {
         matches!(self, Self::LmStudio)
@@ -64,7 +78,7 @@ impl ApiKind {
     /// Returns LM API host
     pub fn host(&self) -> &'static str {
         match *self {
-            Self::OpenAI | Self::ChatGpt => OPENAI_HOST,
+            Self::OpenAI | Self::ChatGpt | Self::Responses => OPENAI_HOST,
             Self::Anthropic | Self::Claude => ANTHROPIC_HOST,
             Self::Google | Self::Gemini => GOOGLE_HOST,
             Self::LmStudio => LMSTUDIO_HOST,
@@ -72,6 +86,8 @@ impl ApiKind {
             Self::OpenRouter => OPENROUTER_HOST,
             Self::Perplexity => PERPLEXITY_HOST,
             Self::Voyage => VOYAGE_HOST,
+            Self::Cohere => COHERE_HOST,
+            Self::Jina => JINA_HOST,
         }
     }
 
@@ -81,23 +97,68 @@ impl ApiKind {
             str!("v1beta/models/{}:streamGenerateContent?alt=sse", model)
         } else if self.is_anthropic() {
             str!("v1/messages")
+        } else if self.is_responses() {
+            str!("v1/responses")
         } else {
             str!("v1/chat/completions")
         }
     }
 
+    /// Returns rerank path
+    pub fn rerank_path(&self) -> String {
+        match *self {
+            Self::Cohere => str!("v2/rerank"),
+            _ => str!("v1/rerank"),
+        }
+    }
+
+    /// Returns audio transcriptions path
+    pub fn transcriptions_path(&self) -> String {
+        str!("v1/audio/transcriptions")
+    }
+
+    /// Returns text-to-speech path
+    pub fn speech_path(&self) -> String {
+        str!("v1/audio/speech")
+    }
+
+    /// Returns image generation path
+    pub fn images_generations_path(&self) -> String {
+        str!("v1/images/generations")
+    }
+
+    /// Returns image editing path
+    pub fn images_edits_path(&self) -> String {
+        str!("v1/images/edits")
+    }
+
+    /// Returns the max embeddings inputs count per request
+    pub fn embeddings_batch_size(&self) -> usize {
+        match *self {
+            Self::Voyage => 128,
+            Self::Google | Self::Gemini => 100,
+            Self::Cohere => 96,
+            _ => 2048,
+        }
+    }
+
+    /// Returns the max embeddings input tokens per request (if it's limited)
+    pub fn embeddings_batch_tokens(&self) -> Option<usize> {
+        match *self {
+            Self::OpenAI | Self::ChatGpt | Self::Responses => Some(300_000),
+            Self::Voyage => Some(120_000),
+            _ => None,
+        }
+    }
+
     /// Returns embeddings path
     pub fn embeddings_path(&self, model: &str) -> String {
         if self.is_google() {
-            str!("v1beta/models/{}:embedContent", model)
+            str!("v1beta/models/{}:batchEmbedContents", model)
+        } else if *self == Self::Cohere {
+            str!("v2/embed")
         } else {
             str!("v1/embeddings")
         }
     }
 }
-
-impl Default for ApiKind {
-    fn default() -> Self {
-        Self::OpenAI
-    }
-}
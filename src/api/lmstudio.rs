@@ -0,0 +1,290 @@
+use super::kind::LMSTUDIO_HOST;
+use crate::{
+    AiOptions,
+    cassette::{self, Cassette},
+    chunk::ResponseError,
+    prelude::*,
+};
+use reqwest::{Client, Proxy, RequestBuilder, header};
+use std::{sync::Arc, time::Duration};
+
+/// The LM Studio model info (native REST API)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LmModel {
+    /// The model key
+    pub id: String,
+    /// The model type (`llm`, `vlm`, `embeddings`)
+    #[serde(rename = "type")]
+    #[serde(default)]
+    pub kind: String,
+    /// The model publisher
+    #[serde(default)]
+    pub publisher: String,
+    /// The model architecture
+    #[serde(default)]
+    pub arch: String,
+    /// The model format (`gguf`, `mlx`)
+    #[serde(default)]
+    pub compatibility_type: String,
+    /// The model quantization (`Q4_K_M` and etc.)
+    #[serde(default)]
+    pub quantization: String,
+    /// The model load state (`loaded`, `not-loaded`)
+    #[serde(default)]
+    pub state: String,
+    /// The max supported context length
+    #[serde(default)]
+    pub max_context_length: usize,
+    /// The context length of the loaded model
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loaded_context_length: Option<usize>,
+}
+
+impl LmModel {
+    /// Returns true if the model is loaded into memory
+    pub fn is_loaded(&self) -> bool {
+        self.state == "loaded"
+    }
+}
+
+/// The loaded model instance
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LmLoadedModel {
+    /// The model type (`llm`, `embedding`)
+    #[serde(rename = "type")]
+    #[serde(default)]
+    pub kind: String,
+    /// The loaded instance ID (for unloading)
+    pub instance_id: String,
+    /// The model loading time
+    #[serde(default)]
+    pub load_time_seconds: f32,
+    /// The load status
+    #[serde(default)]
+    pub status: String,
+}
+
+/// The LM Studio generation stats (`AiChunk::Stats`)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LmStats {
+    /// The generation speed
+    #[serde(default)]
+    pub tokens_per_second: f32,
+    /// The time to first token (in seconds)
+    #[serde(default)]
+    pub time_to_first_token: f32,
+    /// The generation time (in seconds)
+    #[serde(default)]
+    pub generation_time: f32,
+    /// The generation stop reason
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+/// The LM Studio management client (native REST API)
+#[derive(Clone, Debug)]
+pub struct LmStudio {
+    /// The API authorization key
+    pub api_key: String,
+    /// The custom server host
+    pub host: Option<String>,
+    /// The proxy tunnel settings
+    pub proxy: Option<Proxy>,
+    /// The connection timeout (the models loading can be slow)
+    pub timeout: Duration,
+    /// The HTTP exchanges recorder/player
+    pub cassette: Option<Arc<Cassette>>,
+}
+
+impl Default for LmStudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LmStudio {
+    /// Creates a new LM Studio client
+    pub fn new() -> Self {
+        Self {
+            api_key: String::new(),
+            host: Some(str!("http://127.0.0.1:1234")),
+            proxy: None,
+            timeout: Duration::from_secs(300),
+            cassette: None,
+        }
+    }
+
+    /// Sets the LM API authorization key
+    pub fn set_key(&mut self, key: impl Into<String>) {
+        self.api_key = key.into();
+    }
+    /// Sets the LM API authorization key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.set_key(key);
+        self
+    }
+
+    /// Sets the custom API server host
+    pub fn host(mut self, url: impl Into<String>) -> Self {
+        self.host = Some(url.into());
+        self
+    }
+
+    /// Sets a proxy tunnel settings
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets a connection timeout
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Sets a connection timeout (from seconds)
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_secs(secs);
+        self
+    }
+
+    /// Sets a connection timeout (from millis)
+    pub fn timeout_ms(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_millis(secs);
+        self
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Returns the available models (downloaded and loaded)
+    pub async fn models(&self) -> Result<Vec<LmModel>> {
+        let mut output = self.get("api/v0/models").await?;
+        Ok(json::from_value(output["data"].take())?)
+    }
+
+    /// Returns the model info
+    pub async fn model(&self, id: &str) -> Result<LmModel> {
+        let output = self.get(&str!("api/v0/models/{id}")).await?;
+        Ok(json::from_value(output)?)
+    }
+
+    /// Returns the loaded models
+    pub async fn loaded_models(&self) -> Result<Vec<LmModel>> {
+        let mut models = self.models().await?;
+        models.retain(LmModel::is_loaded);
+        Ok(models)
+    }
+
+    /// Loads the model with the context length and TTL (the idle time before auto-unloading)
+    pub async fn load(
+        &self,
+        model: &str,
+        context_length: Option<usize>,
+        ttl: Option<Duration>,
+    ) -> Result<LmLoadedModel> {
+        let mut data = json!({ "model": model });
+        if let Some(length) = context_length {
+            data["context_length"] = json!(length);
+        }
+        if let Some(ttl) = ttl {
+            data["ttl"] = json!(ttl.as_secs());
+        }
+
+        let output = self.post("api/v1/models/load", data).await?;
+        Ok(json::from_value(output)?)
+    }
+
+    /// Unloads the model instance
+    pub async fn unload(&self, instance_id: &str) -> Result<()> {
+        self.post(
+            "api/v1/models/unload",
+            json!({ "instance_id": instance_id }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Generates the endpoint URL
+    fn url(&self, path: &str) -> String {
+        let host = self.host.as_deref().unwrap_or(LMSTUDIO_HOST);
+        str!("{host}{}{path}", if host.ends_with("/") { "" } else { "/" })
+    }
+
+    /// Creates the HTTP client
+    fn client(&self) -> Result<Client> {
+        let mut client = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client = client.proxy(proxy);
+            client = client.danger_accept_invalid_certs(true); // VPN SSL
+        }
+        Ok(client.build()?)
+    }
+
+    /// Sends the GET request
+    async fn get(&self, path: &str) -> Result<JsonValue> {
+        let url = self.url(path);
+        let request = self.client()?.get(&url);
+        self.send(request, &url, &JsonValue::Null).await
+    }
+
+    /// Sends the POST request
+    async fn post(&self, path: &str, data: JsonValue) -> Result<JsonValue> {
+        let url = self.url(path);
+        let request = self
+            .client()?
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .json(&data);
+        self.send(request, &url, &data).await
+    }
+
+    /// Sends the request & parses the JSON response
+    async fn send(
+        &self,
+        request: RequestBuilder,
+        url: &str,
+        data: &JsonValue,
+    ) -> Result<JsonValue> {
+        let request = request.header(header::AUTHORIZATION, str!("Bearer {}", self.api_key));
+        let output = cassette::send_text(request, url, data, self.cassette.as_ref()).await?;
+
+        // check for an error:
+        if let Some(e) = ResponseError::from_str(&output) {
+            return Err(Error::ResponseError(e).into());
+        }
+
+        Ok(json::from_str(&output)?)
+    }
+}
+
+impl TryFrom<AiOptions> for LmStudio {
+    type Error = DynError;
+
+    fn try_from(ops: AiOptions) -> Result<Self> {
+        let mut this = Self::new().key(
+            // read API key
+            if let Some(v) = ops.env_var.as_ref() {
+                std::env::var(v).unwrap_or_default()
+            } else {
+                String::new()
+            },
+        );
+
+        // set default server host:
+        if let Some(host) = ops.server.as_ref() {
+            this = this.host(host.to_owned());
+        }
+        // set proxy options:
+        if let Some(proxy) = ops.proxy.as_ref() {
+            this = this.proxy(Proxy::all(proxy.to_owned())?);
+        }
+
+        Ok(this)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::prelude::*;
+
+/// The token log probability
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Logprob {
+    /// The generated token
+    pub token: String,
+    /// The token log probability
+    pub logprob: f32,
+    /// The most likely alternative tokens
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+impl Logprob {
+    /// Returns the token probability (0.0..=1.0)
+    pub fn probability(&self) -> f32 {
+        self.logprob.exp()
+    }
+}
+
+/// The alternative token log probability
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TopLogprob {
+    /// The alternative token
+    pub token: String,
+    /// The token log probability
+    pub logprob: f32,
+}
+
+impl TopLogprob {
+    /// Returns the token probability (0.0..=1.0)
+    pub fn probability(&self) -> f32 {
+        self.logprob.exp()
+    }
+}
@@ -1,8 +1,15 @@
-use super::{Content, Role};
-use crate::{prelude::*, utils};
+use super::{ApiKind, CacheControl, Content, Role, ToolCall};
+use crate::{BpeTokenizer, Tokenizer, prelude::*};
+#[cfg(feature = "image")]
+use crate::{image, media};
 
 use chrono::{DateTime, Utc};
 
+/// The estimated audio tokens per second
+const AUDIO_TOKENS_PER_SEC: f32 = 32.0;
+/// The estimated PDF page tokens (text & page image)
+const PDF_PAGE_TOKENS: usize = 1500;
+
 /// The request message
 #[derive(From, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[from(Bytes, "Message::user(vec![String::from_utf8_lossy(&value).into()])")]
@@ -16,33 +23,91 @@ pub struct Message {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// The assistant tool calls (their results are the next tool messages)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
 }
 
 impl Message {
-    /// Returns message tokens count
-    fn count_tokens(content: &[Content]) -> usize {
+    /// Returns message tokens count (the images cost depends on API)
+    fn count_tokens(
+        content: &[Content],
+        tokenizer: &dyn Tokenizer,
+        kind: Option<&ApiKind>,
+    ) -> usize {
         content
             .iter()
             .map(|c| match c {
-                Content::Text { text } => utils::count_tokens(&text),
-                Content::Image { detail, .. } => match detail.as_deref() {
-                    Some("high") => 170,
-                    Some("auto") => 110,
-                    _ => 85, // low (by default)
-                },
+                Content::Text { text, .. } => tokenizer.count_tokens(text),
+                Content::Image { image, detail, .. } => {
+                    Self::image_tokens(&image.url, detail.as_deref(), kind)
+                }
+                Content::Audio { audio, .. } => {
+                    (audio.duration_secs() * AUDIO_TOKENS_PER_SEC).ceil() as usize
+                }
+                Content::Document { file, .. } => {
+                    if file.mime_type().starts_with("text/") {
+                        file.bytes()
+                            .map(|bytes| tokenizer.count_tokens(&String::from_utf8_lossy(&bytes)))
+                            .unwrap_or_default()
+                    } else {
+                        file.pages() * PDF_PAGE_TOKENS
+                    }
+                }
             })
             .sum::<usize>()
     }
 
+    /// Returns the tool calls tokens count (the names & arguments)
+    fn tool_calls_tokens(calls: &[ToolCall], tokenizer: &dyn Tokenizer) -> usize {
+        calls
+            .iter()
+            .map(|call| tokenizer.count_tokens(&call.name) + tokenizer.count_tokens(&call.json_str))
+            .sum()
+    }
+
+    /// Returns the image tokens cost (by the image size with `image` feature, else estimated)
+    fn image_tokens(url: &str, detail: Option<&str>, kind: Option<&ApiKind>) -> usize {
+        #[cfg(feature = "image")]
+        if let Some((_, data)) = media::split_data_url(url)
+            && let Ok(bytes) = media::decode(data)
+            && let Ok((width, height)) = image::dimensions(&bytes)
+        {
+            return image::image_tokens(kind.unwrap_or(&ApiKind::OpenAI), width, height, detail);
+        }
+        #[cfg(not(feature = "image"))]
+        let _ = url;
+
+        match kind {
+            Some(kind) if kind.is_anthropic() => 1600, // ~1092x1092
+            Some(kind) if kind.is_google() => 258,
+            _ => match detail {
+                Some("high") => 170,
+                Some("auto") => 110,
+                _ => 85, // low (by default)
+            },
+        }
+    }
+
     /// Creates a new message structure
     pub fn new(role: Role, content: Vec<Content>) -> Self {
-        let tokens_count = Self::count_tokens(&content);
+        let tokens_count = Self::count_tokens(&content, &BpeTokenizer::default(), None);
 
         Self {
             role,
             content,
             tokens_count,
             timestamp: Some(Utc::now()),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+            cache_control: None,
         }
     }
 
@@ -61,6 +126,27 @@ impl Message {
         Self::new(Role::Assistant, content)
     }
 
+    /// The tool call result message
+    pub fn tool(id: impl Into<String>, content: Vec<Content>) -> Self {
+        let mut msg = Self::new(Role::Tool, content);
+        msg.tool_call_id.replace(id.into());
+        msg
+    }
+
+    /// The assistant tool calls message (the tool results must follow it)
+    pub fn tool_calls(content: Vec<Content>, calls: Vec<ToolCall>) -> Self {
+        let mut msg = Self::new(Role::Assistant, content);
+        msg.tokens_count += Self::tool_calls_tokens(&calls, &BpeTokenizer::default());
+        msg.tool_calls = calls;
+        msg
+    }
+
+    /// Sets the prompt cache breakpoint (Anthropic caches the prompt prefix up to this message)
+    pub fn cache_control(mut self, cache: CacheControl) -> Self {
+        self.cache_control.replace(cache);
+        self
+    }
+
     /// Maps the message content
     pub fn map(&mut self, f: impl FnOnce(&mut Vec<Content>)) {
         f(&mut self.content);
@@ -69,6 +155,20 @@ impl Message {
 
     /// Updates the number of used tokens
     pub fn update_tokens(&mut self) {
-        self.tokens_count = Self::count_tokens(&self.content);
+        let tokenizer = BpeTokenizer::default();
+        self.tokens_count = Self::count_tokens(&self.content, &tokenizer, None)
+            + Self::tool_calls_tokens(&self.tool_calls, &tokenizer);
+    }
+
+    /// Updates the number of used tokens with the specified tokenizer
+    pub fn update_tokens_with(&mut self, tokenizer: &dyn Tokenizer) {
+        self.tokens_count = Self::count_tokens(&self.content, tokenizer, None)
+            + Self::tool_calls_tokens(&self.tool_calls, tokenizer);
+    }
+
+    /// Updates the number of used tokens with the specified tokenizer and the API images cost
+    pub fn update_tokens_for(&mut self, kind: &ApiKind, tokenizer: &dyn Tokenizer) {
+        self.tokens_count = Self::count_tokens(&self.content, tokenizer, Some(kind))
+            + Self::tool_calls_tokens(&self.tool_calls, tokenizer);
     }
 }
@@ -1,18 +1,60 @@
 pub mod kind;
 pub use kind::ApiKind;
 
+pub mod usage;
+pub use usage::Usage;
+
+pub mod cache;
+pub use cache::CacheControl;
+
+pub mod response_cache;
+pub use response_cache::{CacheEntry, DiskCache, MemoryCache, ResponseCache};
+
 pub mod completions;
 pub use completions::{AiChunk, AiStream, Completions};
 
+pub mod logprob;
+pub use logprob::{Logprob, TopLogprob};
+
 pub mod embeddings;
-pub use embeddings::{Embedding, Embeddings, EmbeddingsData, Usage};
+pub use embeddings::{Embedding, Embeddings, EmbeddingsData, QuantizedEmbedding};
+
+pub mod embedding_options;
+pub use embedding_options::{EncodingFormat, OutputDtype, Truncation};
+
+pub mod rerank;
+pub use rerank::{Rerank, RerankData, RerankResult};
+
+pub mod transcription;
+pub use transcription::{
+    TimestampGranularity, Transcription, TranscriptionData, TranscriptionFormat,
+    TranscriptionSegment, TranscriptionWord,
+};
+
+pub mod speech;
+pub use speech::{AudioFormat, AudioStream, Speech};
+
+pub mod image_generation;
+pub use image_generation::{GeneratedImage, ImageGeneration, ImagesData};
+
+pub mod lmstudio;
+pub use lmstudio::{LmLoadedModel, LmModel, LmStats, LmStudio};
+
+pub mod task_type;
+pub use task_type::TaskType;
 
 pub mod schema;
-pub use schema::{Schema, SchemaKind};
+pub use schema::{Schema, SchemaKind, SchemaMode};
 
 pub mod tool;
 pub use tool::Tool;
 
+pub mod tool_call;
+pub use tool_call::ToolCall;
+
+pub mod tool_choice;
+pub use tool_choice::ToolChoice;
+
 pub mod role;
 pub use role::Role;
 
@@ -21,3 +63,12 @@ pub use content::Content;
 
 pub mod message;
 pub use message::Message;
+
+pub mod context;
+pub use context::{ContextStrategy, DropToolResults, KeepFirstLast, SlidingWindow, Summarize};
+
+pub mod conversation;
+pub use conversation::Conversation;
+
+pub mod storage;
+pub use storage::{ConversationStore, FileStore, MemoryStore};
@@ -0,0 +1,314 @@
+use super::{ApiKind, Usage};
+use crate::{
+    AiOptions,
+    cassette::{self, Cassette},
+    chunk::ResponseError,
+    prelude::*,
+};
+use reqwest::{Client, Proxy, header};
+use std::{sync::Arc, time::Duration};
+
+/// The reranked document
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RerankResult {
+    /// The document index in request
+    pub index: usize,
+    /// The query relevance score
+    pub relevance_score: f32,
+    /// The document text (if `return_documents` is enabled)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+}
+
+/// The rerank response
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RerankData {
+    /// The documents sorted by relevance
+    pub results: Vec<RerankResult>,
+    pub model: String,
+    pub usage: Usage,
+}
+
+/// The LM API rerank request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rerank {
+    /// The API standart
+    #[serde(skip)]
+    pub api_kind: ApiKind,
+    /// The API authorization key
+    #[serde(skip)]
+    pub api_key: String,
+    /// The custom server host
+    #[serde(skip)]
+    pub host: Option<String>,
+    /// The proxy tunnel settings
+    #[serde(skip)]
+    pub proxy: Option<Proxy>,
+    /// The connection timeout
+    #[serde(skip)]
+    pub timeout: Duration,
+    /// The AI model name
+    pub model: String,
+    /// The search query
+    pub query: String,
+    /// The documents to rerank
+    pub documents: Vec<String>,
+    /// The max returned documents count
+    #[serde(skip)]
+    pub top_n: Option<usize>,
+    /// Returns the documents texts in results
+    #[serde(skip)]
+    pub return_documents: bool,
+    /// The HTTP exchanges recorder/player
+    #[serde(skip)]
+    pub cassette: Option<Arc<Cassette>>,
+}
+
+impl Rerank {
+    /// Creates a new LM rerank request
+    pub fn new(kind: ApiKind, key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            host: if kind.is_lmstudio() {
+                Some(str!("http://127.0.0.1:1234"))
+            } else {
+                None
+            },
+            api_kind: kind,
+            api_key: key.into(),
+            proxy: None,
+            timeout: Duration::from_secs(30),
+            model: model.into(),
+            query: String::new(),
+            documents: Vec::new(),
+            top_n: None,
+            return_documents: false,
+            cassette: None,
+        }
+    }
+
+    /// Creates a new Voyage rerank request
+    pub fn voyage(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::Voyage, key, model)
+    }
+
+    /// Creates a new Claude (Voyage) rerank request
+    pub fn claude(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::Voyage, key, model)
+    }
+
+    /// Creates a new Cohere rerank request
+    pub fn cohere(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::Cohere, key, model)
+    }
+
+    /// Creates a new Jina rerank request
+    pub fn jina(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::Jina, key, model)
+    }
+
+    /// Creates a new LM Studio (or other local server) rerank request
+    pub fn lmstudio(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::LmStudio, key, model)
+    }
+
+    /// Sets the LM API authorization key
+    pub fn set_key(&mut self, key: impl Into<String>) {
+        self.api_key = key.into();
+    }
+    /// Sets the LM API authorization key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.set_key(key);
+        self
+    }
+
+    /// Sets the custom API server host
+    pub fn host(mut self, url: impl Into<String>) -> Self {
+        self.host = Some(url.into());
+        self
+    }
+
+    /// Sets a proxy tunnel settings
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets a connection timeout
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Sets a connection timeout (from seconds)
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_secs(secs);
+        self
+    }
+
+    /// Sets a connection timeout (from millis)
+    pub fn timeout_ms(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_millis(secs);
+        self
+    }
+
+    /// Sets the LM model name
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the search query
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = query.into();
+        self
+    }
+
+    /// Adds the document to rerank
+    pub fn document(mut self, doc: impl Into<String>) -> Self {
+        self.documents.push(doc.into());
+        self
+    }
+
+    /// Adds the documents to rerank
+    pub fn documents(mut self, docs: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.documents.extend(docs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the max returned documents count
+    pub fn top_n(mut self, n: usize) -> Self {
+        self.top_n.replace(n);
+        self
+    }
+
+    /// Enables/disables the documents texts in results
+    pub fn return_documents(mut self, enabled: bool) -> Self {
+        self.return_documents = enabled;
+        self
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Sends the request to LM server
+    pub async fn send(&self) -> Result<RerankData> {
+        let url = if let Some(host) = &self.host {
+            str!(
+                "{host}{}{}",
+                if host.ends_with("/") { "" } else { "/" },
+                self.api_kind.rerank_path()
+            )
+        } else {
+            str!("{}/{}", self.api_kind.host(), self.api_kind.rerank_path())
+        };
+
+        // serialize request data:
+        let mut data = json::to_value(self).map_err(Error::from)?;
+        if let Some(n) = self.top_n {
+            data[if self.api_kind == ApiKind::Voyage {
+                "top_k"
+            } else {
+                "top_n"
+            }] = json!(n);
+        }
+        if self.return_documents && self.api_kind != ApiKind::Cohere {
+            data["return_documents"] = json!(true);
+        }
+
+        // create client & configure proxy:
+        let mut client = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client = client.proxy(proxy);
+            client = client.danger_accept_invalid_certs(true); // VPN SSL
+        }
+
+        // send request:
+        let request = client
+            .build()?
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, str!("Bearer {}", self.api_key))
+            .json(&data);
+
+        let output = cassette::send_text(request, &url, &data, self.cassette.as_ref()).await?;
+
+        // check for an error:
+        if let Some(e) = ResponseError::from_str(&output) {
+            return Err(Error::ResponseError(e).into());
+        }
+
+        // else parse response:
+        self.parse(json::from_str(&output)?)
+    }
+
+    /// Converts the response (Voyage `data`, Cohere/Jina `results`)
+    fn parse(&self, mut output: JsonValue) -> Result<RerankData> {
+        let list = match (output["results"].take(), output["data"].take()) {
+            (JsonValue::Array(list), _) | (_, JsonValue::Array(list)) => list,
+            _ => Vec::new(),
+        };
+
+        let mut results = list
+            .into_iter()
+            .map(|item| {
+                let index = item["index"].as_u64().unwrap_or_default() as usize;
+                RerankResult {
+                    index,
+                    relevance_score: item["relevance_score"]
+                        .as_f64()
+                        .or_else(|| item["score"].as_f64())
+                        .unwrap_or_default() as f32,
+                    document: self
+                        .return_documents
+                        .then(|| self.documents.get(index).cloned())
+                        .flatten(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        if let Some(n) = self.top_n {
+            results.truncate(n);
+        }
+
+        Ok(RerankData {
+            results,
+            model: output["model"].as_str().unwrap_or(&self.model).to_string(),
+            usage: json::from_value(output["usage"].take()).unwrap_or_default(),
+        })
+    }
+}
+
+impl TryFrom<AiOptions> for Rerank {
+    type Error = DynError;
+
+    fn try_from(ops: AiOptions) -> Result<Self> {
+        let mut this = Self::new(
+            // choose AI service
+            ops.kind,
+            // read API key
+            if let Some(v) = ops.env_var.as_ref() {
+                std::env::var(v).unwrap_or_default()
+            } else {
+                String::new()
+            },
+            // choose model
+            ops.model,
+        );
+
+        // set default server host:
+        if let Some(host) = ops.server.as_ref() {
+            this = this.host(host.to_owned());
+        }
+        // set proxy options:
+        if let Some(proxy) = ops.proxy.as_ref() {
+            this = this.proxy(Proxy::all(proxy.to_owned())?);
+        }
+
+        Ok(this)
+    }
+}
@@ -0,0 +1,195 @@
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::{
+    path::PathBuf,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+/// Returns the cache key of the normalized request data (SHA-256 hex)
+pub(crate) fn cache_key(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The cached response entry
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// The caching time
+    pub created_at: DateTime<Utc>,
+    /// The cached response data
+    pub data: JsonValue,
+}
+
+impl CacheEntry {
+    /// Creates a new cache entry
+    pub fn new(data: JsonValue) -> Self {
+        Self {
+            created_at: Utc::now(),
+            data,
+        }
+    }
+
+    /// Returns true if the entry is older than TTL
+    pub fn is_expired(&self, ttl: Option<Duration>) -> bool {
+        match ttl.and_then(|ttl| chrono::Duration::from_std(ttl).ok()) {
+            Some(ttl) => Utc::now() - self.created_at > ttl,
+            None => false,
+        }
+    }
+}
+
+/// The responses cache (implement it to plug a shared storage)
+pub trait ResponseCache: std::fmt::Debug + Send + Sync {
+    /// Returns the cached response data (None if missing or expired)
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<JsonValue>>>;
+
+    /// Saves the response data
+    fn put<'a>(&'a self, key: &'a str, data: JsonValue) -> BoxFuture<'a, Result<()>>;
+}
+
+/// The in-memory LRU responses cache
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    tick: AtomicU64,
+    items: Mutex<HashMap<String, (u64, CacheEntry)>>,
+}
+
+impl MemoryCache {
+    /// Creates a new in-memory cache with the maximum entries count
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl: None,
+            tick: AtomicU64::new(0),
+            items: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the entries lifetime
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl.replace(ttl);
+        self
+    }
+
+    /// Returns the cached entries count
+    pub fn len(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+
+    /// Returns true if there are no cached entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all the cached entries
+    pub fn clear(&self) {
+        self.items.lock().unwrap().clear();
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<JsonValue>>> {
+        Box::pin(async move {
+            let mut items = self.items.lock().unwrap();
+
+            match items.get_mut(key) {
+                Some((_, entry)) if entry.is_expired(self.ttl) => {
+                    items.remove(key);
+                    Ok(None)
+                }
+                Some((used, entry)) => {
+                    *used = self.tick.fetch_add(1, Ordering::Relaxed);
+                    Ok(Some(entry.data.clone()))
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, data: JsonValue) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut items = self.items.lock().unwrap();
+
+            // evict the least recently used entry:
+            if items.len() >= self.capacity
+                && !items.contains_key(key)
+                && let Some(lru_key) = items
+                    .iter()
+                    .min_by_key(|(_, (used, _))| *used)
+                    .map(|(k, _)| k.clone())
+            {
+                items.remove(&lru_key);
+            }
+
+            let used = self.tick.fetch_add(1, Ordering::Relaxed);
+            items.insert(key.to_string(), (used, CacheEntry::new(data)));
+            Ok(())
+        })
+    }
+}
+
+/// The on-disk responses cache (a JSON file per entry in the directory)
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+}
+
+impl DiskCache {
+    /// Creates a new on-disk cache
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: None,
+        }
+    }
+
+    /// Sets the entries lifetime
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl.replace(ttl);
+        self
+    }
+
+    /// Returns the entry file path
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(str!("{key}.json"))
+    }
+}
+
+impl ResponseCache for DiskCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<JsonValue>>> {
+        Box::pin(async move {
+            let data = match tokio::fs::read_to_string(self.path(key)).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+
+            let entry: CacheEntry = json::from_str(&data)?;
+            if entry.is_expired(self.ttl) {
+                tokio::fs::remove_file(self.path(key)).await.ok();
+                return Ok(None);
+            }
+
+            Ok(Some(entry.data))
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, data: JsonValue) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(self.path(key), json::to_string(&CacheEntry::new(data))?).await?;
+            Ok(())
+        })
+    }
+}
@@ -7,6 +7,8 @@ pub enum Role {
     System,
     User,
     Assistant,
+    /// The tool call result
+    Tool,
 }
 
 impl Role {
@@ -24,4 +26,9 @@ impl Role {
     pub fn is_assistant(&self) -> bool {
         Self::Assistant == *self
     }
+
+    /// Returns true if it's the tool call result message
+    pub fn is_tool(&self) -> bool {
+        Self::Tool == *self
+    }
 }
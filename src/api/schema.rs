@@ -1,9 +1,10 @@
 use crate::prelude::*;
 
 /// The JSON-schema kind
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum SchemaKind {
+    #[default]
     Object,
     Array,
     String,
@@ -45,9 +46,26 @@ impl SchemaKind {
     }
 }
 
-impl Default for SchemaKind {
-    fn default() -> Self {
-        Self::Object
+/// The structured output mode
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaMode {
+    /// Uses the native API response format (response_format, output_config, etc.)
+    #[default]
+    Native,
+    /// Forces a synthetic tool call and unwraps its input as the response
+    Tool,
+}
+
+impl SchemaMode {
+    /// Returns true if it's the native response format
+    pub fn is_native(&self) -> bool {
+        matches!(self, Self::Native)
+    }
+
+    /// Returns true if it's the synthetic tool call
+    pub fn is_tool(&self) -> bool {
+        matches!(self, Self::Tool)
     }
 }
 
@@ -224,10 +242,10 @@ impl Schema {
         let mut schema_json = self.to_json_schema()?;
 
         // OpenAI Strict requires `additionalProperties: false` at all levels of the object:
-        if let Some(obj) = schema_json.as_object_mut() {
-            if obj.get("type").and_then(|t| t.as_str()) == Some("object") {
-                obj.insert("additionalProperties".to_string(), json!(false));
-            }
+        if let Some(obj) = schema_json.as_object_mut()
+            && obj.get("type").and_then(|t| t.as_str()) == Some("object")
+        {
+            obj.insert("additionalProperties".to_string(), json!(false));
         }
 
         Ok(json!({
@@ -240,6 +258,14 @@ impl Schema {
         }))
     }
 
+    /// Converts into `OpenAI Responses` format (text.format)
+    pub fn to_responses_format(&self) -> Result<JsonValue> {
+        let mut format = self.to_openai_format()?["json_schema"].take();
+        format["type"] = json!("json_schema");
+
+        Ok(json!({ "format": format }))
+    }
+
     /// Converts into `Anthropic` (and others) format (output_config)
     pub fn to_anthropic_format(&self) -> Result<JsonValue> {
         let mut schema_json = self.to_json_schema()?;
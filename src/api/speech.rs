@@ -0,0 +1,317 @@
+use super::ApiKind;
+use crate::{
+    AiOptions,
+    cassette::{self, BodyStream, Cassette},
+    chunk::ResponseError,
+    prelude::*,
+};
+use futures::StreamExt;
+use reqwest::{Client, Proxy, header};
+use std::{path::Path, sync::Arc, time::Duration};
+
+/// The speech audio format
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    Opus,
+    Aac,
+    Flac,
+    Wav,
+    /// The raw 24kHz 16-bit mono samples
+    Pcm,
+}
+
+impl AudioFormat {
+    /// Returns the MIME type
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "audio/mpeg",
+            Self::Opus => "audio/opus",
+            Self::Aac => "audio/aac",
+            Self::Flac => "audio/flac",
+            Self::Wav => "audio/wav",
+            Self::Pcm => "audio/pcm",
+        }
+    }
+}
+
+/// The speech audio stream
+pub struct AudioStream {
+    stream: BodyStream,
+}
+
+impl std::fmt::Debug for AudioStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioStream").finish_non_exhaustive()
+    }
+}
+
+impl AudioStream {
+    /// Reads the next audio bytes chunk
+    pub async fn next(&mut self) -> Option<Result<Bytes>> {
+        self.stream.next().await
+    }
+
+    /// Reads all the audio bytes
+    pub async fn bytes(mut self) -> Result<Bytes> {
+        let mut output = Vec::new();
+        while let Some(bytes) = self.next().await {
+            output.extend_from_slice(&bytes?);
+        }
+        Ok(Bytes::from(output))
+    }
+
+    /// Writes the audio into file
+    pub async fn save(self, path: impl AsRef<Path>) -> Result<()> {
+        tokio::fs::write(path, self.bytes().await?).await?;
+        Ok(())
+    }
+}
+
+/// The LM API text-to-speech request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Speech {
+    /// The API standart
+    #[serde(skip)]
+    pub api_kind: ApiKind,
+    /// The API authorization key
+    #[serde(skip)]
+    pub api_key: String,
+    /// The custom server host
+    #[serde(skip)]
+    pub host: Option<String>,
+    /// The proxy tunnel settings
+    #[serde(skip)]
+    pub proxy: Option<Proxy>,
+    /// The connection timeout
+    #[serde(skip)]
+    pub timeout: Duration,
+    /// The AI model name
+    pub model: String,
+    /// The text to speak
+    pub input: String,
+    /// The voice name
+    pub voice: String,
+    /// The voice style instructions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// The audio format
+    pub response_format: AudioFormat,
+    /// The speech speed (0.25 - 4.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    /// The HTTP exchanges recorder/player
+    #[serde(skip)]
+    pub cassette: Option<Arc<Cassette>>,
+}
+
+impl Speech {
+    /// Creates a new LM text-to-speech request
+    pub fn new(kind: ApiKind, key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            host: if kind.is_lmstudio() {
+                Some(str!("http://127.0.0.1:1234"))
+            } else {
+                None
+            },
+            api_kind: kind,
+            api_key: key.into(),
+            proxy: None,
+            timeout: Duration::from_secs(120),
+            model: model.into(),
+            input: String::new(),
+            voice: str!("alloy"),
+            instructions: None,
+            response_format: AudioFormat::default(),
+            speed: None,
+            cassette: None,
+        }
+    }
+
+    /// Creates a new OpenAI (ChatGPT) text-to-speech request
+    pub fn openai(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::OpenAI, key, model)
+    }
+
+    /// Creates a new LM Studio text-to-speech request
+    pub fn lmstudio(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::LmStudio, key, model)
+    }
+
+    /// Creates a new ChatGPT text-to-speech request
+    pub fn chatgpt(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::ChatGpt, key, model)
+    }
+
+    /// Sets the LM API authorization key
+    pub fn set_key(&mut self, key: impl Into<String>) {
+        self.api_key = key.into();
+    }
+    /// Sets the LM API authorization key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.set_key(key);
+        self
+    }
+
+    /// Sets the custom API server host
+    pub fn host(mut self, url: impl Into<String>) -> Self {
+        self.host = Some(url.into());
+        self
+    }
+
+    /// Sets a proxy tunnel settings
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets a connection timeout
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Sets a connection timeout (from seconds)
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_secs(secs);
+        self
+    }
+
+    /// Sets a connection timeout (from millis)
+    pub fn timeout_ms(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_millis(secs);
+        self
+    }
+
+    /// Sets the LM model name
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the text to speak
+    pub fn input(mut self, text: impl Into<String>) -> Self {
+        self.input = text.into();
+        self
+    }
+
+    /// Sets the voice name
+    pub fn voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = voice.into();
+        self
+    }
+
+    /// Sets the voice style instructions
+    pub fn instructions(mut self, text: impl Into<String>) -> Self {
+        self.instructions.replace(text.into());
+        self
+    }
+
+    /// Sets the audio format
+    pub fn response_format(mut self, format: AudioFormat) -> Self {
+        self.response_format = format;
+        self
+    }
+
+    /// Sets the speech speed (0.25 - 4.0)
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed.replace(speed);
+        self
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Sends the request to LM server (the audio is streamed as it's generated)
+    pub async fn send(&self) -> Result<AudioStream> {
+        if !self.api_kind.is_openai() {
+            return Err(Error::Unsupported(str!("text-to-speech"), self.api_kind.clone()).into());
+        }
+
+        let url = if let Some(host) = &self.host {
+            str!(
+                "{host}{}{}",
+                if host.ends_with("/") { "" } else { "/" },
+                self.api_kind.speech_path()
+            )
+        } else {
+            str!("{}/{}", self.api_kind.host(), self.api_kind.speech_path())
+        };
+        let data = json::to_value(self).map_err(Error::from)?;
+
+        // create client & configure proxy:
+        let mut client = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client = client.proxy(proxy);
+            client = client.danger_accept_invalid_certs(true); // VPN SSL
+        }
+
+        // send request:
+        let request = client
+            .build()?
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, str!("Bearer {}", self.api_key))
+            .json(&data);
+
+        let mut stream = cassette::send(request, &url, &data, self.cassette.as_ref()).await?;
+
+        // check for an error (the JSON body instead of audio):
+        let first = match stream.next().await {
+            Some(bytes) => bytes?,
+            None => Bytes::new(),
+        };
+        if first.starts_with(b"{") {
+            let mut output = first.to_vec();
+            while let Some(bytes) = stream.next().await {
+                output.extend_from_slice(&bytes?);
+            }
+            let text = String::from_utf8_lossy(&output);
+            if let Some(e) = ResponseError::from_str(&text) {
+                return Err(Error::ResponseError(e).into());
+            }
+            stream = futures::stream::once(async move { Ok(Bytes::from(output)) }).boxed();
+        } else {
+            stream = futures::stream::once(async move { Ok(first) })
+                .chain(stream)
+                .boxed();
+        }
+
+        Ok(AudioStream { stream })
+    }
+}
+
+impl TryFrom<AiOptions> for Speech {
+    type Error = DynError;
+
+    fn try_from(ops: AiOptions) -> Result<Self> {
+        let mut this = Self::new(
+            // choose AI service
+            ops.kind,
+            // read API key
+            if let Some(v) = ops.env_var.as_ref() {
+                std::env::var(v).unwrap_or_default()
+            } else {
+                String::new()
+            },
+            // choose model
+            ops.model,
+        );
+
+        // set default server host:
+        if let Some(host) = ops.server.as_ref() {
+            this = this.host(host.to_owned());
+        }
+        // set proxy options:
+        if let Some(proxy) = ops.proxy.as_ref() {
+            this = this.proxy(Proxy::all(proxy.to_owned())?);
+        }
+
+        Ok(this)
+    }
+}
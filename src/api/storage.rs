@@ -0,0 +1,166 @@
+use super::Conversation;
+use crate::prelude::*;
+use futures::future::BoxFuture;
+use std::{path::PathBuf, sync::Mutex};
+
+/// The conversations storage (implement it to plug a database)
+pub trait ConversationStore: Send + Sync {
+    /// Saves the conversation (replaces the existing one)
+    fn save<'a>(&'a self, conv: &'a Conversation) -> BoxFuture<'a, Result<()>>;
+
+    /// Loads the conversation by ID
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<Conversation>>>;
+
+    /// Deletes the conversation by ID
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Returns the stored conversation IDs
+    fn list(&self) -> BoxFuture<'_, Result<Vec<String>>>;
+}
+
+/// The in-memory conversations storage
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    items: Mutex<HashMap<String, Conversation>>,
+}
+
+impl MemoryStore {
+    /// Creates a new in-memory storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConversationStore for MemoryStore {
+    fn save<'a>(&'a self, conv: &'a Conversation) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.items
+                .lock()
+                .unwrap()
+                .insert(conv.id.clone(), conv.clone());
+            Ok(())
+        })
+    }
+
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<Conversation>>> {
+        Box::pin(async move { Ok(self.items.lock().unwrap().get(id).cloned()) })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.items.lock().unwrap().remove(id);
+            Ok(())
+        })
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<String>>> {
+        Box::pin(async move {
+            let mut ids: Vec<String> = self.items.lock().unwrap().keys().cloned().collect();
+            ids.sort();
+            Ok(ids)
+        })
+    }
+}
+
+/// The file conversations storage (a file per conversation in the directory)
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+    jsonl: bool,
+}
+
+impl FileStore {
+    /// Creates a new JSON files storage
+    pub fn json(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            jsonl: false,
+        }
+    }
+
+    /// Creates a new JSONL files storage
+    pub fn jsonl(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            jsonl: true,
+        }
+    }
+
+    /// Returns the conversation file extension
+    fn extension(&self) -> &'static str {
+        if self.jsonl { "jsonl" } else { "json" }
+    }
+
+    /// Returns the conversation file path (the IDs leaving the storage directory are rejected)
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty() || id.contains(['/', '\\', '\0']) || id.contains("..") {
+            return Err(Error::InvalidConversationId(id.to_string()).into());
+        }
+        Ok(self.dir.join(str!("{id}.{}", self.extension())))
+    }
+}
+
+impl ConversationStore for FileStore {
+    fn save<'a>(&'a self, conv: &'a Conversation) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let data = if self.jsonl {
+                conv.to_jsonl()?
+            } else {
+                conv.to_json()?
+            };
+
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(self.path(&conv.id)?, data).await?;
+            Ok(())
+        })
+    }
+
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<Conversation>>> {
+        Box::pin(async move {
+            let data = match tokio::fs::read_to_string(self.path(id)?).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+
+            let conv = if self.jsonl {
+                Conversation::from_jsonl(&data)?
+            } else {
+                Conversation::from_json(&data)?
+            };
+            Ok(Some(conv))
+        })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            match tokio::fs::remove_file(self.path(id)?).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<String>>> {
+        Box::pin(async move {
+            let mut ids = Vec::new();
+            let mut entries = match tokio::fs::read_dir(&self.dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ids),
+                Err(e) => return Err(e.into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) == Some(self.extension())
+                    && let Some(id) = path.file_stem().and_then(|s| s.to_str())
+                {
+                    ids.push(id.to_string());
+                }
+            }
+
+            ids.sort();
+            Ok(ids)
+        })
+    }
+}
@@ -0,0 +1,23 @@
+use crate::prelude::*;
+
+/// The embeddings task type (Gemini `taskType`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaskType {
+    /// The search query
+    RetrievalQuery,
+    /// The searched document
+    RetrievalDocument,
+    /// The texts similarity
+    SemanticSimilarity,
+    /// The texts classification
+    Classification,
+    /// The texts clustering
+    Clustering,
+    /// The question for answering
+    QuestionAnswering,
+    /// The statement for verification
+    FactVerification,
+    /// The code search query
+    CodeRetrievalQuery,
+}
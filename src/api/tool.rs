@@ -1,4 +1,4 @@
-use super::Schema;
+use super::{CacheControl, Schema};
 use crate::prelude::*;
 
 /// The tool call structure
@@ -11,6 +11,8 @@ pub struct Tool {
     parameters: Schema,
     #[serde(default)]
     properties: HashMap<String, Schema>,
+    #[serde(skip)]
+    cache_control: Option<CacheControl>,
 }
 
 impl Tool {
@@ -28,9 +30,28 @@ impl Tool {
             },
             parameters: Schema::object(""),
             properties: HashMap::new(),
+            cache_control: None,
         }
     }
 
+    /// Creates a new tool call from the arguments schema
+    pub fn from_schema(name: impl Into<String>, schema: Schema) -> Self {
+        let mut this = Self::new(name, schema.description.clone().unwrap_or_default());
+        this.parameters = schema;
+        this
+    }
+
+    /// Returns the tool name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the prompt cache breakpoint (Anthropic caches the tools up to this one)
+    pub fn cache_control(mut self, cache: CacheControl) -> Self {
+        self.cache_control.replace(cache);
+        self
+    }
+
     /// Adds an argument
     pub fn property(mut self, name: impl Into<String>, schema: Schema, required: bool) -> Self {
         self.parameters = self.parameters.property(name, schema, required);
@@ -61,14 +82,27 @@ impl Tool {
         }))
     }
 
+    /// Converts into `OpenAI Responses` format: the flat function fields with "type"
+    pub fn to_responses_format(&self) -> Result<JsonValue> {
+        let mut tool_json = self.to_json_tool()?;
+        tool_json["type"] = json!("function");
+
+        Ok(tool_json)
+    }
+
     /// Converts into `Anthropic` format: replace "parameters" to "input_schema"
     pub fn to_anthropic_format(&self) -> Result<JsonValue> {
         let mut tool_json = self.to_json_tool()?;
 
-        if let Some(obj) = tool_json.as_object_mut() {
-            if let Some(params) = obj.remove("parameters") {
-                obj.insert("input_schema".to_string(), params);
-            }
+        if let Some(obj) = tool_json.as_object_mut()
+            && let Some(params) = obj.remove("parameters")
+        {
+            obj.insert("input_schema".to_string(), params);
+        }
+        if let Some(obj) = tool_json.as_object_mut()
+            && let Some(cache) = &self.cache_control
+        {
+            obj.insert("cache_control".to_string(), json::to_value(cache)?);
         }
 
         Ok(tool_json)
@@ -0,0 +1,52 @@
+use super::AiChunk;
+use crate::prelude::*;
+
+/// The assistant tool call (kept in the messages history before its result)
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct ToolCall {
+    /// The tool call ID (the tool result message refers to it)
+    pub id: String,
+    /// The tool name
+    pub name: String,
+    /// The tool arguments JSON
+    pub json_str: String,
+}
+
+impl ToolCall {
+    /// Creates a new tool call
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        json_str: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            json_str: json_str.into(),
+        }
+    }
+
+    /// Parses the tool arguments (the empty arguments are an empty object)
+    pub fn args(&self) -> JsonValue {
+        json::from_str(&self.json_str).unwrap_or_else(|_| json!({}))
+    }
+
+    /// Converts into `OpenAI` format: {"id": ..., "type": "function", "function": {"name": ..., "arguments": ...}}
+    pub fn to_openai_format(&self) -> JsonValue {
+        json!({
+            "id": self.id,
+            "type": "function",
+            "function": { "name": self.name, "arguments": self.json_str }
+        })
+    }
+}
+
+impl AiChunk {
+    /// Returns the tool call of the tool chunk (to add it into the messages history)
+    pub fn to_tool_call(&self) -> Option<ToolCall> {
+        match self {
+            Self::Tool { id, name, json_str } => Some(ToolCall::new(id, name, json_str)),
+            _ => None,
+        }
+    }
+}
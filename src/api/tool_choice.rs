@@ -0,0 +1,94 @@
+use crate::prelude::*;
+
+/// The tool choice mode
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoice {
+    /// The model decides itself whether to call tools
+    #[default]
+    Auto,
+    /// The model can't call any tools
+    None,
+    /// The model must call at least one tool
+    Required,
+    /// The model must call the specified tool
+    Tool(String),
+}
+
+impl ToolChoice {
+    /// Creates a specific tool choice
+    pub fn tool(name: impl Into<String>) -> Self {
+        Self::Tool(name.into())
+    }
+
+    /// Returns true if it's `auto` mode
+    pub fn is_auto(&self) -> bool {
+        matches!(self, Self::Auto)
+    }
+
+    /// Returns true if it's `none` mode (the tool calls are forbidden)
+    pub fn is_forbidden(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    /// Returns true if it's `required` mode
+    pub fn is_required(&self) -> bool {
+        matches!(self, Self::Required)
+    }
+
+    /// Returns the specified tool name
+    pub fn tool_name(&self) -> Option<&str> {
+        match self {
+            Self::Tool(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl ToolChoice {
+    /// Converts into `OpenAI` format: "auto" | "none" | "required" | {"type": "function", ...}
+    pub fn to_openai_format(&self) -> JsonValue {
+        match self {
+            Self::Auto => json!("auto"),
+            Self::None => json!("none"),
+            Self::Required => json!("required"),
+            Self::Tool(name) => json!({
+                "type": "function",
+                "function": { "name": name }
+            }),
+        }
+    }
+
+    /// Converts into `OpenAI Responses` format: "auto" | "none" | "required" | {"type": "function", "name": ...}
+    pub fn to_responses_format(&self) -> JsonValue {
+        match self {
+            Self::Tool(name) => json!({ "type": "function", "name": name }),
+            _ => self.to_openai_format(),
+        }
+    }
+
+    /// Converts into `Anthropic` format: {"type": "auto" | "none" | "any" | "tool", ...}
+    pub fn to_anthropic_format(&self) -> JsonValue {
+        match self {
+            Self::Auto => json!({ "type": "auto" }),
+            Self::None => json!({ "type": "none" }),
+            Self::Required => json!({ "type": "any" }),
+            Self::Tool(name) => json!({ "type": "tool", "name": name }),
+        }
+    }
+
+    /// Converts into `Google` format: {"functionCallingConfig": {...}}
+    pub fn to_google_format(&self) -> JsonValue {
+        match self {
+            Self::Auto => json!({ "functionCallingConfig": { "mode": "AUTO" } }),
+            Self::None => json!({ "functionCallingConfig": { "mode": "NONE" } }),
+            Self::Required => json!({ "functionCallingConfig": { "mode": "ANY" } }),
+            Self::Tool(name) => json!({
+                "functionCallingConfig": {
+                    "mode": "ANY",
+                    "allowedFunctionNames": [ name ]
+                }
+            }),
+        }
+    }
+}
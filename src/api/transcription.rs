@@ -0,0 +1,380 @@
+use super::{ApiKind, Usage};
+use crate::{
+    AiOptions,
+    cassette::{self, Cassette},
+    chunk::ResponseError,
+    media,
+    prelude::*,
+};
+use reqwest::{
+    Client, Proxy, header,
+    multipart::{Form, Part},
+};
+use std::{path::Path, sync::Arc, time::Duration};
+
+/// The transcription response format
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionFormat {
+    /// The JSON with text
+    #[default]
+    Json,
+    /// The JSON with text, language, duration & timestamps
+    VerboseJson,
+    /// The plain text
+    Text,
+    /// The SubRip subtitles
+    Srt,
+    /// The WebVTT subtitles
+    Vtt,
+}
+
+impl TranscriptionFormat {
+    /// Returns true if it's a JSON format
+    pub fn is_json(&self) -> bool {
+        matches!(self, Self::Json | Self::VerboseJson)
+    }
+}
+
+/// The transcription timestamps granularity
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampGranularity {
+    /// The segment timestamps
+    Segment,
+    /// The word timestamps
+    Word,
+}
+
+/// The transcribed segment
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptionSegment {
+    #[serde(default)]
+    pub id: usize,
+    /// The start time (in seconds)
+    pub start: f32,
+    /// The end time (in seconds)
+    pub end: f32,
+    pub text: String,
+}
+
+/// The transcribed word
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptionWord {
+    pub word: String,
+    /// The start time (in seconds)
+    pub start: f32,
+    /// The end time (in seconds)
+    pub end: f32,
+}
+
+/// The transcription response
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TranscriptionData {
+    /// The transcribed text (or subtitles for `srt`/`vtt` formats)
+    pub text: String,
+    /// The detected language
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The audio duration (in seconds)
+    #[serde(default)]
+    pub duration: Option<f32>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptionSegment>,
+    #[serde(default)]
+    pub words: Vec<TranscriptionWord>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// The LM API audio transcription request
+#[derive(Clone, Debug)]
+pub struct Transcription {
+    /// The API standart
+    pub api_kind: ApiKind,
+    /// The API authorization key
+    pub api_key: String,
+    /// The custom server host
+    pub host: Option<String>,
+    /// The proxy tunnel settings
+    pub proxy: Option<Proxy>,
+    /// The connection timeout
+    pub timeout: Duration,
+    /// The AI model name
+    pub model: String,
+    /// The audio file name
+    pub file_name: String,
+    /// The audio file bytes
+    pub file: Bytes,
+    /// The audio language (ISO-639-1)
+    pub language: Option<String>,
+    /// The style or vocabulary hint
+    pub prompt: Option<String>,
+    /// The sampling temperature
+    pub temperature: Option<f32>,
+    /// The response format
+    pub response_format: TranscriptionFormat,
+    /// The timestamps granularities (`verbose_json` format only)
+    pub timestamp_granularities: Vec<TimestampGranularity>,
+    /// The HTTP exchanges recorder/player
+    pub cassette: Option<Arc<Cassette>>,
+}
+
+impl Transcription {
+    /// Creates a new LM transcription request
+    pub fn new(kind: ApiKind, key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            host: if kind.is_lmstudio() {
+                Some(str!("http://127.0.0.1:1234"))
+            } else {
+                None
+            },
+            api_kind: kind,
+            api_key: key.into(),
+            proxy: None,
+            timeout: Duration::from_secs(120),
+            model: model.into(),
+            file_name: str!("audio.wav"),
+            file: Bytes::new(),
+            language: None,
+            prompt: None,
+            temperature: None,
+            response_format: TranscriptionFormat::default(),
+            timestamp_granularities: Vec::new(),
+            cassette: None,
+        }
+    }
+
+    /// Creates a new OpenAI (ChatGPT) transcription request
+    pub fn openai(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::OpenAI, key, model)
+    }
+
+    /// Creates a new LM Studio transcription request
+    pub fn lmstudio(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::LmStudio, key, model)
+    }
+
+    /// Creates a new ChatGPT transcription request
+    pub fn chatgpt(key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(ApiKind::ChatGpt, key, model)
+    }
+
+    /// Sets the LM API authorization key
+    pub fn set_key(&mut self, key: impl Into<String>) {
+        self.api_key = key.into();
+    }
+    /// Sets the LM API authorization key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.set_key(key);
+        self
+    }
+
+    /// Sets the custom API server host
+    pub fn host(mut self, url: impl Into<String>) -> Self {
+        self.host = Some(url.into());
+        self
+    }
+
+    /// Sets a proxy tunnel settings
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets a connection timeout
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Sets a connection timeout (from seconds)
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_secs(secs);
+        self
+    }
+
+    /// Sets a connection timeout (from millis)
+    pub fn timeout_ms(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_millis(secs);
+        self
+    }
+
+    /// Sets the LM model name
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the audio file bytes (the file name extension defines the audio format)
+    pub fn audio(mut self, file_name: impl Into<String>, bytes: impl Into<Bytes>) -> Self {
+        self.file_name = file_name.into();
+        self.file = bytes.into();
+        self
+    }
+
+    /// Reads the audio file
+    pub fn file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| str!("audio.wav"));
+
+        Ok(self.audio(name, std::fs::read(path)?))
+    }
+
+    /// Sets the audio language (ISO-639-1)
+    pub fn language(mut self, lang: impl Into<String>) -> Self {
+        self.language.replace(lang.into());
+        self
+    }
+
+    /// Sets the style or vocabulary hint
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt.replace(prompt.into());
+        self
+    }
+
+    /// Sets the sampling temperature
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature.replace(temperature);
+        self
+    }
+
+    /// Sets the response format
+    pub fn response_format(mut self, format: TranscriptionFormat) -> Self {
+        self.response_format = format;
+        self
+    }
+
+    /// Adds the timestamps granularity (switches to `verbose_json` format)
+    pub fn timestamps(mut self, granularity: TimestampGranularity) -> Self {
+        if !self.timestamp_granularities.contains(&granularity) {
+            self.timestamp_granularities.push(granularity);
+        }
+        self.response_format = TranscriptionFormat::VerboseJson;
+        self
+    }
+
+    /// Sets the HTTP exchanges recorder/player (for offline tests)
+    pub fn cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette.replace(cassette);
+        self
+    }
+
+    /// Sends the request to LM server
+    pub async fn send(&self) -> Result<TranscriptionData> {
+        if !self.api_kind.is_openai() {
+            return Err(
+                Error::Unsupported(str!("audio transcriptions"), self.api_kind.clone()).into(),
+            );
+        }
+
+        let url = if let Some(host) = &self.host {
+            str!(
+                "{host}{}{}",
+                if host.ends_with("/") { "" } else { "/" },
+                self.api_kind.transcriptions_path()
+            )
+        } else {
+            str!(
+                "{}/{}",
+                self.api_kind.host(),
+                self.api_kind.transcriptions_path()
+            )
+        };
+
+        // the form fields (the JSON copy is used by cassette):
+        let data = json!({
+            "file": self.file_name,
+            "model": self.model,
+            "response_format": self.response_format,
+            "language": self.language,
+            "prompt": self.prompt,
+            "temperature": self.temperature,
+            "timestamp_granularities": self.timestamp_granularities,
+        });
+
+        let mut form = Form::new();
+        for (name, value) in data.as_object().into_iter().flatten() {
+            match value {
+                JsonValue::String(s) if name != "file" => form = form.text(name.clone(), s.clone()),
+                JsonValue::Number(n) => form = form.text(name.clone(), n.to_string()),
+                JsonValue::Array(list) => {
+                    for item in list.iter().filter_map(|v| v.as_str()) {
+                        form = form.text(str!("{name}[]"), item.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        form = form.part(
+            "file",
+            Part::bytes(self.file.to_vec())
+                .file_name(self.file_name.clone())
+                .mime_str(media::mime_type(&self.file_name))?,
+        );
+
+        // create client & configure proxy:
+        let mut client = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            client = client.proxy(proxy);
+            client = client.danger_accept_invalid_certs(true); // VPN SSL
+        }
+
+        // send request:
+        let request = client
+            .build()?
+            .post(&url)
+            .header(header::AUTHORIZATION, str!("Bearer {}", self.api_key))
+            .multipart(form);
+
+        let output = cassette::send_text(request, &url, &data, self.cassette.as_ref()).await?;
+
+        // check for an error:
+        if let Some(e) = ResponseError::from_str(&output) {
+            return Err(Error::ResponseError(e).into());
+        }
+
+        // else parse response:
+        if self.response_format.is_json() {
+            Ok(json::from_str(&output)?)
+        } else {
+            Ok(TranscriptionData {
+                text: output,
+                ..Default::default()
+            })
+        }
+    }
+}
+
+impl TryFrom<AiOptions> for Transcription {
+    type Error = DynError;
+
+    fn try_from(ops: AiOptions) -> Result<Self> {
+        let mut this = Self::new(
+            // choose AI service
+            ops.kind,
+            // read API key
+            if let Some(v) = ops.env_var.as_ref() {
+                std::env::var(v).unwrap_or_default()
+            } else {
+                String::new()
+            },
+            // choose model
+            ops.model,
+        );
+
+        // set default server host:
+        if let Some(host) = ops.server.as_ref() {
+            this = this.host(host.to_owned());
+        }
+        // set proxy options:
+        if let Some(proxy) = ops.proxy.as_ref() {
+            this = this.proxy(Proxy::all(proxy.to_owned())?);
+        }
+
+        Ok(this)
+    }
+}
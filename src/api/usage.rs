@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+/// The tokens usage info
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Usage {
+    /// The input (prompt) tokens count
+    #[serde(default)]
+    #[serde(alias = "prompt_tokens")]
+    pub input_tokens: usize,
+    /// The output (completion) tokens count
+    #[serde(default)]
+    #[serde(alias = "completion_tokens")]
+    pub output_tokens: usize,
+    /// The input tokens read from the prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: usize,
+    /// The input tokens written into the prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: usize,
+    /// The total tokens count
+    #[serde(default)]
+    pub total_tokens: usize,
+}
+
+impl std::ops::AddAssign<&Usage> for Usage {
+    fn add_assign(&mut self, other: &Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
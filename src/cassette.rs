@@ -0,0 +1,456 @@
+use crate::{chunk::ResponseError, prelude::*};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use futures::{StreamExt, stream::BoxStream};
+use reqwest::RequestBuilder;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The response body stream
+pub(crate) type BodyStream = BoxStream<'static, Result<Bytes>>;
+
+/// The recorded request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The request URL
+    pub url: String,
+    /// The request body
+    #[serde(default)]
+    pub body: JsonValue,
+    /// Does the request match any body on replay (the hand-written fixtures)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub any_body: bool,
+}
+
+/// The recorded response body chunk
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedChunk {
+    /// The delay after the previous chunk
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// The chunk data (UTF-8 text or base64 string)
+    pub data: String,
+    /// Is the data encoded into base64 (it isn't a valid UTF-8 text)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub base64: bool,
+}
+
+impl RecordedChunk {
+    /// Creates a new recorded chunk
+    pub fn new(delay: Duration, bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self {
+                delay_ms: delay.as_millis() as u64,
+                data: text.to_string(),
+                base64: false,
+            },
+            Err(_) => Self {
+                delay_ms: delay.as_millis() as u64,
+                data: BASE64.encode(bytes),
+                base64: true,
+            },
+        }
+    }
+
+    /// Returns the chunk bytes
+    pub fn bytes(&self) -> Result<Bytes> {
+        if self.base64 {
+            Ok(Bytes::from(BASE64.decode(&self.data)?))
+        } else {
+            Ok(Bytes::from(self.data.clone()))
+        }
+    }
+}
+
+/// The recorded response
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// The HTTP status code
+    #[serde(default = "RecordedResponse::default_status")]
+    pub status: u16,
+    /// The response content type
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// The response body chunks (as they were received)
+    #[serde(default)]
+    pub chunks: Vec<RecordedChunk>,
+}
+
+impl RecordedResponse {
+    fn default_status() -> u16 {
+        200
+    }
+}
+
+/// The recorded request/response exchange
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// The cassette file data
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct CassetteData {
+    interactions: Vec<Interaction>,
+}
+
+/// The HTTP exchanges recorder/player (for offline tests)
+#[derive(Debug)]
+pub struct Cassette {
+    path: Option<PathBuf>,
+    replay: bool,
+    delays: bool,
+    interactions: Mutex<Vec<(bool, Interaction)>>,
+}
+
+impl Cassette {
+    /// Creates a new cassette recording the exchanges into file (it's written by `save` or when the cassette is dropped)
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+            replay: false,
+            delays: true,
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Loads the cassette file for replay
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let mut this = Self::from_json(&std::fs::read_to_string(path.as_ref())?)?;
+        this.path.replace(path.as_ref().to_path_buf());
+        Ok(this)
+    }
+
+    /// Parses the cassette JSON for replay
+    pub fn from_json(s: &str) -> Result<Self> {
+        let data: CassetteData = json::from_str(s)?;
+
+        Ok(Self {
+            path: None,
+            replay: true,
+            delays: true,
+            interactions: Mutex::new(data.interactions.into_iter().map(|i| (false, i)).collect()),
+        })
+    }
+
+    /// Enables/disables the recorded chunk delays on replay (enabled by default)
+    pub fn delays(mut self, enabled: bool) -> Self {
+        self.delays = enabled;
+        self
+    }
+
+    /// Returns true if it's in replay mode
+    pub fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    /// Returns the recorded interactions
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, i)| i.clone())
+            .collect()
+    }
+
+    /// Serializes the cassette into JSON string
+    pub fn to_json(&self) -> Result<String> {
+        let data = CassetteData {
+            interactions: self.interactions(),
+        };
+        Ok(json::to_string_pretty(&data)?)
+    }
+
+    /// Saves the cassette into its file
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, self.to_json()?)?;
+        }
+        Ok(())
+    }
+
+    /// Replays the recorded response (the first unused interaction with the same request)
+    fn play(&self, url: &str, body: &JsonValue) -> Result<Response> {
+        let (status, content_type, chunks) = {
+            let mut interactions = self.interactions.lock().unwrap();
+            let (used, interaction) = interactions
+                .iter_mut()
+                .find(|(used, i)| {
+                    !*used
+                        && i.request.url == url
+                        && (i.request.any_body || i.request.body == *body)
+                })
+                .ok_or_else(|| Error::CassetteMismatch(url.to_string()))?;
+
+            *used = true;
+            let chunks = interaction
+                .response
+                .chunks
+                .iter()
+                .map(|chunk| Ok((Duration::from_millis(chunk.delay_ms), chunk.bytes()?)))
+                .collect::<Result<Vec<_>>>()?;
+            let response = &interaction.response;
+            (response.status, response.content_type.clone(), chunks)
+        };
+
+        let delays = self.delays;
+        let stream = futures::stream::iter(chunks)
+            .then(move |(delay, bytes)| async move {
+                if delays && !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                Ok(bytes)
+            })
+            .boxed();
+
+        Ok(Response {
+            status,
+            content_type,
+            stream,
+        })
+    }
+
+    /// Records the response stream (the interaction is added when the stream is dropped)
+    fn recorder(
+        self: Arc<Self>,
+        url: &str,
+        body: &JsonValue,
+        response: reqwest::Response,
+    ) -> Response {
+        let status = response.status().as_u16();
+        let content_type = content_type(&response);
+        let recording = Recording {
+            cassette: self,
+            interaction: Some(Interaction {
+                request: RecordedRequest {
+                    url: url.to_string(),
+                    body: body.clone(),
+                    any_body: false,
+                },
+                response: RecordedResponse {
+                    status,
+                    content_type: content_type.clone(),
+                    chunks: Vec::new(),
+                },
+            }),
+        };
+
+        let state = (response.bytes_stream(), Instant::now(), recording);
+        let stream =
+            futures::stream::unfold(state, |(mut inner, last, mut recording)| async move {
+                match inner.next().await {
+                    Some(Ok(bytes)) => {
+                        if let Some(interaction) = &mut recording.interaction {
+                            let chunk = RecordedChunk::new(last.elapsed(), &bytes);
+                            interaction.response.chunks.push(chunk);
+                        }
+                        Some((Ok(bytes), (inner, Instant::now(), recording)))
+                    }
+                    // the broken exchanges aren't recorded:
+                    Some(Err(e)) => {
+                        recording.interaction.take();
+                        Some((Err(e.into()), (inner, last, recording)))
+                    }
+                    None => None,
+                }
+            })
+            .boxed();
+
+        Response {
+            status,
+            content_type,
+            stream,
+        }
+    }
+}
+
+/// The recording exchange (the readers may stop before the stream end, so it's added on drop)
+struct Recording {
+    cassette: Arc<Cassette>,
+    interaction: Option<Interaction>,
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        if let Some(interaction) = self.interaction.take() {
+            self.cassette
+                .interactions
+                .lock()
+                .unwrap()
+                .push((true, interaction));
+        }
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        let recorded = !self.replay && !self.interactions.lock().unwrap().is_empty();
+
+        if recorded && let Err(e) = self.save() {
+            log::warn!("Failed to save the cassette: {e}");
+        }
+    }
+}
+
+/// The response head & body stream (live, recorded or replayed)
+struct Response {
+    status: u16,
+    content_type: Option<String>,
+    stream: BodyStream,
+}
+
+impl Response {
+    /// Returns the body stream, or reads the error body if the status isn't successful
+    async fn check(self, sse: bool) -> Result<BodyStream> {
+        let Self {
+            status,
+            content_type,
+            mut stream,
+        } = self;
+
+        let event_stream = content_type
+            .as_deref()
+            .is_none_or(|ct| ct.contains("text/event-stream"));
+        if status < 400 && (!sse || event_stream) {
+            return Ok(stream);
+        }
+
+        // some local servers send SSE as JSON or plain text, so the body framing is checked:
+        let mut head = Vec::new();
+        while status < 400 && head.iter().all(u8::is_ascii_whitespace) {
+            match stream.next().await {
+                Some(bytes) => head.extend_from_slice(&bytes?),
+                None => break,
+            }
+        }
+        if status < 400 && is_sse_framed(&head) {
+            return Ok(futures::stream::iter([Ok(Bytes::from(head))])
+                .chain(stream)
+                .boxed());
+        }
+
+        head.extend_from_slice(&read_all(stream).await?);
+        let body = String::from_utf8_lossy(&head).into_owned();
+
+        // the error body is plain JSON (or a single SSE event):
+        let error = std::iter::once(body.as_str())
+            .chain(body.lines().filter_map(|l| l.trim().strip_prefix("data:")))
+            .find_map(|s| ResponseError::from_str(s.trim()));
+
+        // the error statuses are kept (to decide on retries):
+        match error {
+            Some(err) if status >= 400 => Err(Error::HttpStatus(status, err.to_string()).into()),
+            Some(err) => Err(Error::ResponseError(err).into()),
+            None => Err(Error::HttpStatus(status, body).into()),
+        }
+    }
+}
+
+/// Returns the response content type
+fn content_type(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Returns true if the body starts with the SSE field (`data:`, `event:`, etc.)
+fn is_sse_framed(body: &[u8]) -> bool {
+    let body = String::from_utf8_lossy(body);
+    let body = body.trim_start();
+
+    ["data:", "event:", "id:", "retry:", ":"]
+        .iter()
+        .any(|field| body.starts_with(field))
+}
+
+/// Reads all the body stream bytes
+async fn read_all(mut stream: BodyStream) -> Result<Bytes> {
+    let mut output = Vec::new();
+    while let Some(bytes) = stream.next().await {
+        output.extend_from_slice(&bytes?);
+    }
+    Ok(Bytes::from(output))
+}
+
+/// Sends the request (or replays/records it with the cassette)
+async fn exchange(
+    request: RequestBuilder,
+    url: &str,
+    body: &JsonValue,
+    cassette: Option<&Arc<Cassette>>,
+) -> Result<Response> {
+    match cassette {
+        Some(cassette) if cassette.is_replay() => cassette.play(url, body),
+        Some(cassette) => Ok(cassette.clone().recorder(url, body, request.send().await?)),
+        None => {
+            let response = request.send().await?;
+            Ok(Response {
+                status: response.status().as_u16(),
+                content_type: content_type(&response),
+                stream: response
+                    .bytes_stream()
+                    .map(|r| r.map_err(Into::into))
+                    .boxed(),
+            })
+        }
+    }
+}
+
+/// Sends the request and returns the response body stream (the error statuses are returned as errors)
+pub(crate) async fn send(
+    request: RequestBuilder,
+    url: &str,
+    body: &JsonValue,
+    cassette: Option<&Arc<Cassette>>,
+) -> Result<BodyStream> {
+    exchange(request, url, body, cassette)
+        .await?
+        .check(false)
+        .await
+}
+
+/// Sends the request and returns the SSE body stream (the non-SSE responses are returned as errors)
+pub(crate) async fn send_sse(
+    request: RequestBuilder,
+    url: &str,
+    body: &JsonValue,
+    cassette: Option<&Arc<Cassette>>,
+) -> Result<BodyStream> {
+    exchange(request, url, body, cassette)
+        .await?
+        .check(true)
+        .await
+}
+
+/// Sends the request and reads the response bytes
+pub(crate) async fn send_bytes(
+    request: RequestBuilder,
+    url: &str,
+    body: &JsonValue,
+    cassette: Option<&Arc<Cassette>>,
+) -> Result<Bytes> {
+    read_all(send(request, url, body, cassette).await?).await
+}
+
+/// Sends the request and reads the response text
+pub(crate) async fn send_text(
+    request: RequestBuilder,
+    url: &str,
+    body: &JsonValue,
+    cassette: Option<&Arc<Cassette>>,
+) -> Result<String> {
+    let output = send_bytes(request, url, body, cassette).await?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
@@ -1,12 +1,19 @@
-use crate::prelude::*;
+use crate::{
+    AiChunk, LmStats, Logprob, TopLogprob, Usage, api::completions::SCHEMA_TOOL_NAME,
+    cassette::BodyStream, media, prelude::*,
+};
+use futures::StreamExt;
+use std::collections::BTreeMap;
 
 /// The AI response chunk
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum ResponseChunk {
     OpenAi(OpenAIChunk),
-    Anthropic(AnthropicChunk),
+    Responses(Box<ResponsesEvent>),
+    Anthropic(Box<AnthropicChunk>),
     Google(GoogleChunk),
+    WrappedError(ResponseError),
     Error(ResponseErrorMessage),
 }
 
@@ -15,6 +22,29 @@ pub enum ResponseChunk {
 #[derive(Debug, Deserialize)]
 pub struct OpenAIChunk {
     pub choices: Vec<OpenAIChoice>,
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+    /// The generation stats (LM Studio native API)
+    #[serde(default)]
+    pub stats: Option<LmStats>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIUsage {
+    #[serde(default)]
+    pub prompt_tokens: usize,
+    #[serde(default)]
+    pub completion_tokens: usize,
+    #[serde(default)]
+    pub total_tokens: usize,
+    #[serde(default)]
+    pub prompt_tokens_details: Option<OpenAIPromptDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIPromptDetails {
+    #[serde(default)]
+    pub cached_tokens: usize,
 }
 
 #[allow(dead_code)]
@@ -23,6 +53,14 @@ pub struct OpenAIChoice {
     pub delta: OpenAIDelta,
     #[serde(default)]
     pub finish_reason: Option<String>,
+    #[serde(default)]
+    pub logprobs: Option<OpenAILogprobs>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAILogprobs {
+    #[serde(default)]
+    pub content: Option<Vec<Logprob>>,
 }
 #[derive(Debug, Deserialize)]
 pub struct OpenAIDelta {
@@ -30,6 +68,19 @@ pub struct OpenAIDelta {
     pub content: Option<String>,
     #[serde(default)]
     pub tool_calls: Option<Vec<ToolCallDelta>>,
+    /// The generated images (OpenRouter)
+    #[serde(default)]
+    pub images: Option<Vec<OpenAIImage>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIImage {
+    pub image_url: OpenAIImageUrl,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIImageUrl {
+    pub url: String,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -39,6 +90,8 @@ pub struct ToolCallDelta {
     #[serde(default)]
     pub index: Option<usize>,
     #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
     pub function: Option<FunctionDelta>,
 }
 #[allow(dead_code)]
@@ -50,6 +103,89 @@ pub struct FunctionDelta {
     pub arguments: Option<String>,
 }
 
+//       OPENAI RESPONSES:
+
+#[derive(Debug, Deserialize)]
+pub struct ResponsesEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub sequence_number: u64,
+    #[serde(default)]
+    pub output_index: Option<usize>,
+    #[serde(default)]
+    pub delta: Option<String>,
+    #[serde(default)]
+    pub logprobs: Option<Vec<Logprob>>,
+    #[serde(default)]
+    pub item: Option<ResponsesItem>,
+    #[serde(default)]
+    pub response: Option<ResponsesObject>,
+    #[serde(default)]
+    pub code: Option<JsonValue>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponsesItem {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub call_id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponsesObject {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub usage: Option<ResponsesUsage>,
+    #[serde(default)]
+    pub error: Option<ResponsesError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponsesUsage {
+    #[serde(default)]
+    pub input_tokens: usize,
+    #[serde(default)]
+    pub output_tokens: usize,
+    #[serde(default)]
+    pub total_tokens: usize,
+    #[serde(default)]
+    pub input_tokens_details: Option<OpenAIPromptDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponsesError {
+    #[serde(default)]
+    pub code: Option<JsonValue>,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl ResponsesError {
+    /// Converts into the response error
+    fn into_error(self) -> Error {
+        let mut extra = HashMap::new();
+        if let Some(code) = self.code.filter(|c| !c.is_null()) {
+            extra.insert(str!("code"), code);
+        }
+
+        Error::ResponseError(ResponseError {
+            error: ResponseErrorMessage {
+                code: None,
+                message: self.message,
+                extra,
+            },
+        })
+    }
+}
+
 //       ANTHROPIC:
 
 #[derive(Debug, Deserialize)]
@@ -60,12 +196,37 @@ pub struct AnthropicChunk {
     pub index: Option<usize>,
     pub delta: Option<AnthropicDelta>,
     pub content_block: Option<ContentBlock>,
+    #[serde(default)]
+    pub message: Option<AnthropicMessage>,
+    #[serde(default)]
+    pub error: Option<ResponseErrorMessage>,
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnthropicMessage {
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnthropicUsage {
+    #[serde(default)]
+    pub input_tokens: Option<usize>,
+    #[serde(default)]
+    pub output_tokens: Option<usize>,
+    #[serde(default)]
+    pub cache_creation_input_tokens: Option<usize>,
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AnthropicDelta {
+    #[serde(default)]
     #[serde(rename = "type")]
-    pub kind: String,
+    pub kind: Option<String>,
     pub text: Option<String>,
     #[serde(rename = "partial_json")]
     pub partial_json: Option<String>,
@@ -75,7 +236,10 @@ pub struct AnthropicDelta {
 pub struct ContentBlock {
     #[serde(rename = "type")]
     pub kind: String,
-    pub name: String,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 //       GOOGLE:
@@ -84,7 +248,21 @@ pub struct ContentBlock {
 pub struct GoogleChunk {
     pub candidates: Vec<GeminiCandidate>,
     #[serde(default)]
-    pub usage_metadata: Option<JsonValue>,
+    #[serde(rename = "usageMetadata")]
+    pub usage_metadata: Option<GeminiUsage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiUsage {
+    #[serde(default)]
+    pub prompt_token_count: usize,
+    #[serde(default)]
+    pub candidates_token_count: usize,
+    #[serde(default)]
+    pub cached_content_token_count: usize,
+    #[serde(default)]
+    pub total_token_count: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -92,6 +270,60 @@ pub struct GeminiCandidate {
     pub content: Option<GeminiContent>,
     #[serde(rename = "finishReason")]
     pub finish_reason: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "logprobsResult")]
+    pub logprobs_result: Option<GeminiLogprobsResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiLogprobsResult {
+    #[serde(default)]
+    pub top_candidates: Vec<GeminiTopCandidates>,
+    #[serde(default)]
+    pub chosen_candidates: Vec<GeminiLogprob>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GeminiTopCandidates {
+    #[serde(default)]
+    pub candidates: Vec<GeminiLogprob>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiLogprob {
+    #[serde(default)]
+    pub token: String,
+    #[serde(default)]
+    pub log_probability: f32,
+}
+
+impl GeminiLogprobsResult {
+    /// Converts into the token log probabilities
+    pub fn into_logprobs(self) -> Vec<Logprob> {
+        let mut top_candidates = self.top_candidates.into_iter();
+
+        self.chosen_candidates
+            .into_iter()
+            .map(|chosen| Logprob {
+                token: chosen.token,
+                logprob: chosen.log_probability,
+                top_logprobs: top_candidates
+                    .next()
+                    .map(|top| {
+                        top.candidates
+                            .into_iter()
+                            .map(|c| TopLogprob {
+                                token: c.token,
+                                logprob: c.log_probability,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -103,8 +335,459 @@ pub struct GeminiContent {
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum GeminiPart {
-    Text { text: String },
-    FunctionCall { function_call: JsonValue },
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(rename = "inlineData", alias = "inline_data")]
+        inline_data: GeminiBlob,
+    },
+    FunctionCall {
+        #[serde(rename = "functionCall", alias = "function_call")]
+        function_call: JsonValue,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeminiBlob {
+    #[serde(rename = "mimeType", alias = "mime_type")]
+    pub mime_type: String,
+    pub data: String,
+}
+
+//       SSE:
+
+/// Decodes the base64 image into chunk
+fn image_chunk(mime: &str, data: &str) -> StdResult<AiChunk, Error> {
+    let bytes = media::decode(data).map_err(|_| Error::InvalidBase64Url)?;
+    Ok(AiChunk::Image {
+        mime: mime.to_string(),
+        bytes: Bytes::from(bytes),
+    })
+}
+
+/// Keeps only the SSE `data:` lines as separate events (drops `event:` lines, normalizes CRLF)
+pub(crate) fn sse_data_lines(stream: BodyStream) -> BodyStream {
+    futures::stream::unfold(
+        (stream, Vec::<u8>::new(), false),
+        |(mut stream, mut buffer, mut ended)| async move {
+            loop {
+                // output the complete data lines:
+                if let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                    let line = buffer.drain(..=pos).collect::<Vec<u8>>();
+                    let line = line.trim_ascii();
+                    if line.starts_with(b"data:") {
+                        let event = Bytes::from([line, b"\n\n"].concat());
+                        return Some((Ok(event), (stream, buffer, ended)));
+                    }
+                    continue;
+                }
+                if ended {
+                    return None;
+                }
+
+                match stream.next().await {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(e)) => return Some((Err(e), (stream, buffer, true))),
+                    // the last line without a line break:
+                    None => {
+                        ended = true;
+                        buffer.push(b'\n');
+                    }
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+//       READER:
+
+/// The streamed tool call buffer
+#[derive(Debug, Default)]
+struct ToolBuffer {
+    id: String,
+    name: String,
+    args: String,
+    streamed: bool,
+}
+
+/// The response chunks reader (collects streamed tool calls until their block ends)
+#[derive(Debug, Default)]
+pub struct ChunkReader {
+    tools: BTreeMap<usize, ToolBuffer>,
+    usage: Option<Usage>,
+    stats: Option<LmStats>,
+    /// The server-side response ID (OpenAI Responses)
+    pub response_id: Option<String>,
+    /// Emits the partial tool call arguments
+    pub tool_deltas: bool,
+    /// The structured output tool (is the schema wrapped into `value` property)
+    pub schema_tool: Option<bool>,
+}
+
+impl ChunkReader {
+    /// Creates a new chunks reader
+    pub fn new(schema_tool: Option<bool>, tool_deltas: bool) -> Self {
+        Self {
+            tools: BTreeMap::new(),
+            usage: None,
+            stats: None,
+            response_id: None,
+            tool_deltas,
+            schema_tool,
+        }
+    }
+
+    /// Reads the response chunk into the output chunks
+    pub fn read(&mut self, chunk: ResponseChunk) -> StdResult<Vec<AiChunk>, Error> {
+        let mut output = Vec::new();
+        let mut text_output = String::new();
+        let mut logprobs = Vec::new();
+
+        match chunk {
+            ResponseChunk::OpenAi(OpenAIChunk {
+                choices,
+                usage,
+                stats,
+            }) => {
+                let mut finished = false;
+
+                if let Some(stats) = stats {
+                    self.stats.replace(stats);
+                }
+
+                if let Some(usage) = usage {
+                    let cached = usage
+                        .prompt_tokens_details
+                        .map(|d| d.cached_tokens)
+                        .unwrap_or_default();
+
+                    self.usage.replace(Usage {
+                        input_tokens: usage.prompt_tokens,
+                        output_tokens: usage.completion_tokens,
+                        cache_read_tokens: cached,
+                        cache_write_tokens: 0,
+                        total_tokens: usage.total_tokens,
+                    });
+                }
+
+                for choice in choices {
+                    if let Some(content) = choice.delta.content {
+                        text_output.push_str(&content);
+                    }
+                    for image in choice.delta.images.unwrap_or_default() {
+                        let (mime, data) = media::split_data_url(&image.image_url.url)
+                            .ok_or(Error::InvalidBase64Url)?;
+                        output.push(image_chunk(mime, data)?);
+                    }
+                    if let Some(content) = choice.logprobs.and_then(|l| l.content) {
+                        logprobs.extend(content);
+                    }
+                    for tc in choice.delta.tool_calls.unwrap_or_default() {
+                        let idx = tc.index.unwrap_or(0);
+                        let entry = self.tools.entry(idx).or_default();
+
+                        if let Some(id) = tc.id {
+                            entry.id = id;
+                        }
+                        if let Some(fn_delta) = tc.function {
+                            if let Some(name) = fn_delta.name {
+                                entry.name = name;
+                            }
+                            if let Some(args) = fn_delta.arguments
+                                && !args.is_empty()
+                            {
+                                entry.args.push_str(&args);
+                                output.extend(self.tool_delta(idx, args));
+                            }
+                        }
+                    }
+                    finished |= choice.finish_reason.is_some();
+                }
+
+                // the tool calls are finished with the choice:
+                if finished {
+                    output.extend(self.finish());
+                }
+            }
+            ResponseChunk::Responses(event) => {
+                let idx = event.output_index.unwrap_or(0);
+
+                if let Some(response) = event.response {
+                    if let Some(id) = response.id {
+                        self.response_id.replace(id);
+                    }
+                    if let Some(error) = response.error {
+                        return Err(error.into_error());
+                    }
+                    if let Some(usage) = response.usage {
+                        let cached = usage
+                            .input_tokens_details
+                            .map(|d| d.cached_tokens)
+                            .unwrap_or_default();
+
+                        self.usage.replace(Usage {
+                            input_tokens: usage.input_tokens,
+                            output_tokens: usage.output_tokens,
+                            cache_read_tokens: cached,
+                            cache_write_tokens: 0,
+                            total_tokens: usage.total_tokens,
+                        });
+                    }
+                }
+
+                match event.kind.as_str() {
+                    "response.output_text.delta" => {
+                        text_output.push_str(&event.delta.unwrap_or_default());
+                        logprobs.extend(event.logprobs.unwrap_or_default());
+                    }
+                    "response.output_item.added" => {
+                        if let Some(item) = event.item
+                            && item.kind == "function_call"
+                        {
+                            let entry = self.tools.entry(idx).or_default();
+                            entry.id = item.call_id.unwrap_or_default();
+                            entry.name = item.name.unwrap_or_default();
+                        }
+                    }
+                    "response.function_call_arguments.delta" => {
+                        if let Some(args) = event.delta
+                            && !args.is_empty()
+                        {
+                            self.tools.entry(idx).or_default().args.push_str(&args);
+                            output.extend(self.tool_delta(idx, args));
+                        }
+                    }
+                    // the tool call is finished with its output item:
+                    "response.output_item.done" => {
+                        if let Some(item) = event.item
+                            && item.kind == "function_call"
+                        {
+                            let entry = self.tools.entry(idx).or_default();
+                            if entry.args.is_empty() {
+                                entry.args = item.arguments.unwrap_or_default();
+                            }
+                            output.extend(self.finish_tool(idx));
+                        }
+                    }
+                    "response.completed" | "response.incomplete" => output.extend(self.finish()),
+                    "error" => {
+                        return Err(ResponsesError {
+                            code: event.code,
+                            message: event.message.unwrap_or_default(),
+                        }
+                        .into_error());
+                    }
+                    // the reasoning items and other events are skipped:
+                    _ => {}
+                }
+            }
+            ResponseChunk::Anthropic(anth) => {
+                let idx = anth.index.unwrap_or(0);
+
+                if let Some(error) = anth.error {
+                    return Err(Error::ResponseError(ResponseError { error }));
+                }
+
+                // the usage is sent on the message start and updated on the message delta:
+                if let Some(usage) = anth.message.and_then(|m| m.usage).or(anth.usage) {
+                    let total = self.usage.get_or_insert_default();
+                    if let Some(n) = usage.input_tokens {
+                        total.input_tokens = n;
+                    }
+                    if let Some(n) = usage.output_tokens {
+                        total.output_tokens = n;
+                    }
+                    if let Some(n) = usage.cache_read_input_tokens {
+                        total.cache_read_tokens = n;
+                    }
+                    if let Some(n) = usage.cache_creation_input_tokens {
+                        total.cache_write_tokens = n;
+                    }
+                    total.total_tokens = total.input_tokens
+                        + total.cache_read_tokens
+                        + total.cache_write_tokens
+                        + total.output_tokens;
+                }
+
+                if let Some(block) = anth.content_block
+                    && block.kind == "tool_use"
+                {
+                    let entry = self.tools.entry(idx).or_default();
+                    entry.id = block.id.unwrap_or_default();
+                    entry.name = block.name.unwrap_or_default();
+                }
+                if let Some(delta) = anth.delta {
+                    if let Some(t) = delta.text {
+                        text_output.push_str(&t);
+                    }
+                    if let Some(pj) = delta.partial_json
+                        && !pj.is_empty()
+                    {
+                        self.tools.entry(idx).or_default().args.push_str(&pj);
+                        output.extend(self.tool_delta(idx, pj));
+                    }
+                }
+
+                // the tool call is finished with its content block:
+                match anth.kind.as_str() {
+                    "content_block_stop" => output.extend(self.finish_tool(idx)),
+                    "message_stop" => output.extend(self.finish()),
+                    _ => {}
+                }
+            }
+            ResponseChunk::Google(google) => {
+                if let Some(usage) = google.usage_metadata {
+                    self.usage.replace(Usage {
+                        input_tokens: usage.prompt_token_count,
+                        output_tokens: usage.candidates_token_count,
+                        cache_read_tokens: usage.cached_content_token_count,
+                        cache_write_tokens: 0,
+                        total_tokens: usage.total_token_count,
+                    });
+                }
+
+                for cand in google.candidates {
+                    if let Some(result) = cand.logprobs_result {
+                        logprobs.extend(result.into_logprobs());
+                    }
+                    if let Some(content) = cand.content {
+                        for part in content.parts {
+                            match part {
+                                GeminiPart::Text { text } => text_output.push_str(&text),
+                                GeminiPart::InlineData { inline_data } => {
+                                    output.push(image_chunk(
+                                        &inline_data.mime_type,
+                                        &inline_data.data,
+                                    )?);
+                                }
+                                GeminiPart::FunctionCall { function_call } => {
+                                    // the function call is always sent in one piece:
+                                    let idx = self.tools.len();
+                                    self.tools.insert(
+                                        idx,
+                                        ToolBuffer {
+                                            id: function_call["id"]
+                                                .as_str()
+                                                .unwrap_or("")
+                                                .to_string(),
+                                            name: function_call["name"]
+                                                .as_str()
+                                                .unwrap_or("")
+                                                .to_string(),
+                                            args: function_call["args"].to_string(),
+                                            streamed: false,
+                                        },
+                                    );
+                                    output.extend(self.finish_tool(idx));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            ResponseChunk::WrappedError(err) => {
+                return Err(Error::ResponseError(err));
+            }
+            ResponseChunk::Error(err) => {
+                return Err(Error::ResponseError(ResponseError { error: err }));
+            }
+        }
+
+        // the text goes first, then its log probabilities:
+        if !logprobs.is_empty() {
+            output.insert(0, AiChunk::Logprobs { logprobs });
+        }
+        if !text_output.is_empty() {
+            output.insert(0, AiChunk::Text { text: text_output });
+        }
+
+        Ok(output)
+    }
+
+    /// Finishes all the buffered tool calls
+    pub fn finish(&mut self) -> Vec<AiChunk> {
+        let indexes: Vec<usize> = self.tools.keys().copied().collect();
+        indexes
+            .into_iter()
+            .filter_map(|idx| self.finish_tool(idx))
+            .collect()
+    }
+
+    /// Finishes the response stream (the buffered tool calls, then the tokens usage & stats)
+    pub fn finish_stream(&mut self) -> Vec<AiChunk> {
+        let mut output = self.finish();
+        if let Some(usage) = self.usage.take() {
+            output.push(AiChunk::Usage { usage });
+        }
+        if let Some(stats) = self.stats.take() {
+            output.push(AiChunk::Stats { stats });
+        }
+        output
+    }
+
+    /// Finishes the buffered tool call
+    fn finish_tool(&mut self, idx: usize) -> Option<AiChunk> {
+        let ToolBuffer {
+            id,
+            name,
+            args,
+            streamed,
+        } = self.tools.remove(&idx)?;
+        let id = if id.is_empty() {
+            str!("call_{idx}")
+        } else {
+            id
+        };
+
+        match self.schema_tool {
+            // the unwrapped structured output is already streamed as a text:
+            Some(false) if name == SCHEMA_TOOL_NAME && streamed => None,
+            Some(wrapped) if name == SCHEMA_TOOL_NAME => {
+                let text = if wrapped {
+                    json::from_str::<JsonValue>(&args)
+                        .ok()
+                        .and_then(|mut v| v.get_mut("value").map(JsonValue::take))
+                        .map(|v| v.to_string())
+                        .unwrap_or(args)
+                } else {
+                    args
+                };
+
+                Some(AiChunk::Text { text })
+            }
+            _ => Some(AiChunk::Tool {
+                id,
+                name,
+                json_str: if args.is_empty() { str!("{{}}") } else { args },
+            }),
+        }
+    }
+
+    /// Returns the partial tool call arguments chunk
+    fn tool_delta(&mut self, idx: usize, partial_json: String) -> Option<AiChunk> {
+        let tool_deltas = self.tool_deltas;
+        let tool = self.tools.get_mut(&idx)?;
+
+        match self.schema_tool {
+            // the unwrapped structured output is streamed as a text:
+            Some(false) if tool.name == SCHEMA_TOOL_NAME => {
+                tool.streamed = true;
+                Some(AiChunk::Text { text: partial_json })
+            }
+            Some(true) if tool.name == SCHEMA_TOOL_NAME => None,
+            _ if tool_deltas => Some(AiChunk::ToolDelta {
+                id: if tool.id.is_empty() {
+                    str!("call_{idx}")
+                } else {
+                    tool.id.clone()
+                },
+                partial_json,
+            }),
+            _ => None,
+        }
+    }
 }
 
 //       ERROR
@@ -155,14 +838,15 @@ pub struct ResponseSimpleError {
 
 impl ResponseError {
     /// Parse string from response buffer
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
-        if let Ok(error) = json::from_str::<ResponseError>(&s) {
+        if let Ok(error) = json::from_str::<ResponseError>(s) {
             Some(error)
-        } else if let Ok(error) = json::from_str::<ResponseErrorMessage>(&s)
+        } else if let Ok(error) = json::from_str::<ResponseErrorMessage>(s)
             && !error.message.is_empty()
         {
             Some(Self { error })
-        } else if let Ok(error) = json::from_str::<ResponseSimpleError>(&s) {
+        } else if let Ok(error) = json::from_str::<ResponseSimpleError>(s) {
             Some(Self {
                 error: ResponseErrorMessage {
                     code: error.code,
@@ -1,4 +1,4 @@
-use crate::chunk::ResponseError;
+use crate::{ApiKind, chunk::ResponseError};
 use macron::{Display, Error, From};
 
 /// The error
@@ -16,9 +16,30 @@ pub enum Error {
     #[display = "Incorrect context - missing a new user request"]
     IncorrectContext,
 
+    #[display = "Invalid conversation data - missing the header line"]
+    InvalidConversation,
+
+    #[display = "Invalid conversation ID '{0}' - it can't be used as a file name"]
+    InvalidConversationId(String),
+
     #[display = "Encoded base64 string is invalid"]
     InvalidBase64Url,
 
-    #[display = "AI-generation error: {}"]
+    #[display = "The {0} aren't supported by {1} API"]
+    Unsupported(String, ApiKind),
+
+    #[display = "HTTP error {0}: {1}"]
+    HttpStatus(u16, String),
+
+    #[display = "The cassette has no recorded response for request to '{0}'"]
+    CassetteMismatch(String),
+
+    #[display = "The response has no embeddings for the inputs [{0}]"]
+    MissingEmbeddings(String),
+
+    #[display = "The vector has {0} dimensions, but the index expects {1}"]
+    InvalidVectorDims(usize, usize),
+
+    #[display = "AI-generation error: {0}"]
     ResponseError(ResponseError),
 }
@@ -1,6 +1,14 @@
-use crate::prelude::*;
+pub mod tokens;
+pub use tokens::{anthropic_tokens, gemini_tokens, image_tokens, openai_tokens};
+
+#[cfg(feature = "image")]
+pub mod process;
+#[cfg(feature = "image")]
+pub use process::{ImageFormat, ImageOptions, dimensions, process, read_with};
+
+use crate::{media, prelude::*};
 use base64::{Engine as _, engine};
-use std::{fs, path::Path};
+use std::path::Path;
 
 /// Validates a base64 URL
 pub fn validate_base64(base64_url: &str) -> bool {
@@ -11,30 +19,18 @@ pub fn validate_base64(base64_url: &str) -> bool {
 pub fn base64(base64_url: impl Into<String>) -> Result<String> {
     let base64_url = base64_url.into();
 
-    if validate_base64(&base64_url.split_once(",").ok_or(Error::InvalidBase64Url)?.1) {
+    if validate_base64(base64_url.split_once(",").ok_or(Error::InvalidBase64Url)?.1) {
         Ok(base64_url)
     } else {
         Err(Error::InvalidBase64Url.into())
     }
 }
 
-/// Creates a new base64 image from file path
+/// Creates a new base64 image from file path (the MIME type is detected by file signature)
 pub fn read(file_path: impl AsRef<Path>) -> Result<String> {
-    // reading file:
     let file_path = file_path.as_ref();
-    let file_content = fs::read(&file_path)?;
-
-    // reading mime-type:
-    let mime_type = match file_path.extension().and_then(|e| e.to_str()) {
-        Some("png") => "image/png",
-        Some("jpg") | Some("jpeg") => "image/jpeg",
-        Some("gif") => "image/gif",
-        _ => "application/octet-stream",
-    };
-
-    // encoding into base64:
-    let encoded = engine::general_purpose::STANDARD.encode(&file_content);
-    let base64_url = str!("data:{};base64,{}", mime_type, encoded);
+    let bytes = std::fs::read(file_path)?;
+    let mime_type = media::image_mime(&bytes).unwrap_or_else(|| media::mime_type(file_path));
 
-    Ok(base64_url)
+    Ok(media::data_url(mime_type, bytes))
 }
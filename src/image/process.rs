@@ -0,0 +1,157 @@
+use super::tokens::{fit, max_size};
+use crate::{ApiKind, prelude::*};
+use ::image::{
+    DynamicImage, ImageDecoder, ImageReader,
+    codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    metadata::Orientation,
+};
+use std::{io::Cursor, path::Path};
+
+/// The re-encoded image format
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ImageFormat {
+    /// The lossy JPEG (without transparency)
+    #[default]
+    Jpeg,
+    /// The lossless WebP (the quality is ignored)
+    WebP,
+    /// The lossless PNG (the quality is ignored)
+    Png,
+}
+
+impl ImageFormat {
+    /// Returns the MIME type
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+            Self::Png => "image/png",
+        }
+    }
+}
+
+/// The image preprocessing options
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ImageOptions {
+    /// The max image width (the larger images are downsized)
+    pub max_width: u32,
+    /// The max image height (the larger images are downsized)
+    pub max_height: u32,
+    /// The output format
+    pub format: ImageFormat,
+    /// The JPEG quality (1-100)
+    pub quality: u8,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImageOptions {
+    /// Creates the default options (2048x2048 JPEG with 85% quality)
+    pub fn new() -> Self {
+        Self {
+            max_width: 2048,
+            max_height: 2048,
+            format: ImageFormat::default(),
+            quality: 85,
+        }
+    }
+
+    /// Creates the options with the API max image size
+    pub fn for_kind(kind: &ApiKind) -> Self {
+        let (max_width, max_height) = max_size(kind);
+        Self {
+            max_width,
+            max_height,
+            ..Self::new()
+        }
+    }
+
+    /// Sets the max image size
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_width = width.max(1);
+        self.max_height = height.max(1);
+        self
+    }
+
+    /// Sets the output format
+    pub fn format(mut self, format: ImageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the JPEG quality (1-100)
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.quality = quality.clamp(1, 100);
+        self
+    }
+}
+
+/// Reads the image size in pixels (after the EXIF orientation, without decoding pixels)
+pub fn dimensions(bytes: &[u8]) -> Result<(u32, u32)> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let (width, height) = decoder.dimensions();
+
+    Ok(match decoder.orientation()? {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => (height, width),
+        _ => (width, height),
+    })
+}
+
+/// Decodes, downsizes and re-encodes the image (the EXIF metadata is applied & stripped)
+pub fn process(bytes: &[u8], options: &ImageOptions) -> Result<(&'static str, Vec<u8>)> {
+    // decode with the EXIF orientation:
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    // downsize:
+    let (width, height) = fit(
+        image.width(),
+        image.height(),
+        options.max_width,
+        options.max_height,
+    );
+    if (width, height) != (image.width(), image.height()) {
+        image = image.resize_exact(width, height, FilterType::Lanczos3);
+    }
+
+    // re-encode:
+    let mut output = Vec::new();
+    match options.format {
+        ImageFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut output, options.quality);
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?;
+        }
+        ImageFormat::WebP => {
+            let encoder = WebPEncoder::new_lossless(&mut output);
+            DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(encoder)?;
+        }
+        ImageFormat::Png => {
+            let encoder = PngEncoder::new(&mut output);
+            DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(encoder)?;
+        }
+    }
+
+    Ok((options.format.mime(), output))
+}
+
+/// Reads the image file as preprocessed base64 url
+pub fn read_with(file_path: impl AsRef<Path>, options: &ImageOptions) -> Result<String> {
+    let bytes = std::fs::read(file_path)?;
+    let (mime_type, output) = process(&bytes, options)?;
+
+    Ok(crate::media::data_url(mime_type, output))
+}
@@ -0,0 +1,68 @@
+use crate::ApiKind;
+
+/// Returns the image tokens cost for the API (by the image size in pixels)
+pub fn image_tokens(kind: &ApiKind, width: u32, height: u32, detail: Option<&str>) -> usize {
+    if kind.is_anthropic() {
+        anthropic_tokens(width, height)
+    } else if kind.is_google() {
+        gemini_tokens(width, height)
+    } else {
+        openai_tokens(width, height, detail)
+    }
+}
+
+/// Returns the OpenAI image tokens cost (85 base tokens + 170 per 512px tile)
+pub fn openai_tokens(width: u32, height: u32, detail: Option<&str>) -> usize {
+    if detail == Some("low") || width == 0 || height == 0 {
+        return 85;
+    }
+
+    // fit into 2048x2048, then scale the shortest side to 768px:
+    let (w, h) = fit(width, height, 2048, 2048);
+    let scale = (768.0 / w.min(h) as f64).min(1.0);
+    let (w, h) = (w as f64 * scale, h as f64 * scale);
+
+    let tiles = (w / 512.0).ceil() as usize * (h / 512.0).ceil() as usize;
+    85 + 170 * tiles
+}
+
+/// Returns the Anthropic image tokens cost (width * height / 750, after the 1568px long edge limit)
+pub fn anthropic_tokens(width: u32, height: u32) -> usize {
+    let (w, h) = fit(width, height, 1568, 1568);
+    ((w as usize * h as usize) / 750).max(1)
+}
+
+/// Returns the Gemini image tokens cost (258 tokens per small image or per crop tile)
+pub fn gemini_tokens(width: u32, height: u32) -> usize {
+    if width <= 384 && height <= 384 {
+        return 258;
+    }
+
+    let unit = (width.min(height) as f64 / 1.5).clamp(256.0, 768.0);
+    let tiles = (width as f64 / unit).ceil() as usize * (height as f64 / unit).ceil() as usize;
+    258 * tiles
+}
+
+/// Returns the size fitted into the max width & height (keeping aspect ratio, without upscaling)
+pub fn fit(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+
+    let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64);
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Returns the max image size for the API (the larger images are downsized by the server)
+pub fn max_size(kind: &ApiKind) -> (u32, u32) {
+    if kind.is_anthropic() {
+        (1568, 1568)
+    } else if kind.is_google() {
+        (3072, 3072)
+    } else {
+        (2048, 2048)
+    }
+}
@@ -6,17 +6,39 @@ pub mod utils;
 pub use utils::*;
 
 pub mod image;
+pub mod media;
 
 pub mod options;
 pub use options::AiOptions;
 
 pub mod chunk;
 
+pub mod cassette;
+pub use cassette::Cassette;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "mock")]
+pub use mock::{MockEvent, MockRequest, MockResponse, MockServer};
+
 pub mod api;
 pub use api::{
-    AiChunk, AiStream, ApiKind, Completions, Content, Embedding, Embeddings, EmbeddingsData,
-    Message, Role, Schema, SchemaKind, Tool, Usage,
+    AiChunk, AiStream, ApiKind, AudioFormat, AudioStream, CacheControl, CacheEntry, Completions,
+    Content, ContextStrategy, Conversation, ConversationStore, DiskCache, DropToolResults,
+    Embedding, Embeddings, EmbeddingsData, EncodingFormat, FileStore, GeneratedImage,
+    ImageGeneration, ImagesData, KeepFirstLast, LmLoadedModel, LmModel, LmStats, LmStudio, Logprob,
+    MemoryCache, MemoryStore, Message, OutputDtype, QuantizedEmbedding, Rerank, RerankData,
+    RerankResult, ResponseCache, Role, Schema, SchemaKind, SchemaMode, SlidingWindow, Speech,
+    Summarize, TaskType, TimestampGranularity, Tool, ToolCall, ToolChoice, TopLogprob,
+    Transcription, TranscriptionData, TranscriptionFormat, TranscriptionSegment, TranscriptionWord,
+    Truncation, Usage,
 };
 
+pub mod vector;
+
+pub mod rag;
+
+pub mod text;
+
 pub use bytes::{self, Bytes};
 pub use reqwest::{self, Proxy};
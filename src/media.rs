@@ -0,0 +1,145 @@
+use crate::prelude::*;
+use base64::{Engine as _, engine};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Returns the MIME type by file extension
+pub fn mime_type(file_path: impl AsRef<Path>) -> &'static str {
+    let ext = file_path
+        .as_ref()
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match ext.as_str() {
+        // images:
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        // audio:
+        "mp3" | "mpga" | "mpeg" => "audio/mpeg",
+        "mp4" | "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "ogg" | "oga" => "audio/ogg",
+        "webm" => "audio/webm",
+        "flac" => "audio/flac",
+        "aac" => "audio/aac",
+        // documents:
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Returns the file extension by MIME type (`png`, `mp3`, etc.)
+pub fn extension(mime_type: &str) -> &str {
+    match mime_type {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        "audio/mpeg" => "mp3",
+        "audio/mp4" => "m4a",
+        "text/plain" => "txt",
+        "text/markdown" => "md",
+        "application/octet-stream" => "bin",
+        _ => mime_type
+            .split_once('/')
+            .map(|(_, ext)| ext)
+            .unwrap_or(mime_type),
+    }
+}
+
+/// Detects the image MIME type by file signature
+pub fn image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG") {
+        Some("image/png")
+    } else if bytes.starts_with(b"\xFF\xD8\xFF") {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("image/gif")
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Returns the audio format name by MIME type (`wav`, `mp3`, etc.)
+pub fn audio_format(mime_type: &str) -> &str {
+    match mime_type {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" => "m4a",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        _ => mime_type.strip_prefix("audio/").unwrap_or(mime_type),
+    }
+}
+
+/// Returns the audio MIME type by format name (`wav`, `mp3`, etc.)
+pub fn audio_mime(format: &str) -> &'static str {
+    mime_type(str!("audio.{format}"))
+}
+
+/// Encodes bytes into base64
+pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+    engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Decodes base64 into bytes
+pub fn decode(base64: &str) -> Result<Vec<u8>> {
+    Ok(engine::general_purpose::STANDARD
+        .decode(base64)
+        .map_err(|_| Error::InvalidBase64Url)?)
+}
+
+/// Creates a new base64 data url (example: "data:application/pdf;base64,JVBERi0x...")
+pub fn data_url(mime_type: &str, bytes: impl AsRef<[u8]>) -> String {
+    str!("data:{mime_type};base64,{}", encode(bytes))
+}
+
+/// Splits the base64 data url into MIME type & data
+pub fn split_data_url(url: &str) -> Option<(&str, &str)> {
+    let (head, data) = url.strip_prefix("data:")?.split_once(',')?;
+    Some((head.strip_suffix(";base64").unwrap_or(head), data))
+}
+
+/// Reads the file as MIME type & base64 data
+pub fn read(file_path: impl AsRef<Path>) -> Result<(&'static str, String)> {
+    let file_path = file_path.as_ref();
+    let bytes = fs::read(file_path)?;
+
+    Ok((mime_type(file_path), encode(bytes)))
+}
+
+/// Saves the bytes into file (the extension is added by MIME type if the path doesn't have it)
+pub fn save(path: impl AsRef<Path>, mime_type: &str, bytes: impl AsRef<[u8]>) -> Result<PathBuf> {
+    let mut path = path.as_ref().to_path_buf();
+    if path.extension().is_none() {
+        path.set_extension(extension(mime_type));
+    }
+    fs::write(&path, bytes)?;
+
+    Ok(path)
+}
+
+/// The bytes (de)serializer as base64 string
+pub(crate) mod base64_bytes {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &Bytes, se: S) -> StdResult<S::Ok, S::Error> {
+        se.serialize_str(&encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> StdResult<Bytes, D::Error> {
+        let data = String::deserialize(de)?;
+        decode(&data)
+            .map(Bytes::from)
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,631 @@
+use crate::{
+    ApiKind, Completions, Embeddings, ImageGeneration, LmStats, LmStudio, Rerank, Speech,
+    Transcription, Usage, media, prelude::*,
+};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// The scripted response event
+#[derive(Clone, Debug)]
+pub enum MockEvent {
+    /// The text chunk
+    Text(String),
+    /// The tool call
+    Tool {
+        id: String,
+        name: String,
+        args: JsonValue,
+    },
+    /// The generated image (Google `inlineData`, OpenRouter `images`)
+    Image { mime: String, bytes: Bytes },
+    /// The tokens usage
+    Usage(Usage),
+    /// The generation stats (LM Studio native API)
+    Stats(LmStats),
+    /// The in-stream error
+    Error(String),
+    /// The pause before the next event
+    Delay(Duration),
+}
+
+/// The scripted mock response
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    status: u16,
+    events: Vec<MockEvent>,
+    body: Option<(String, Bytes)>,
+    expect_body: Option<JsonValue>,
+}
+
+impl Default for MockResponse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockResponse {
+    /// Creates a new empty stream response
+    pub fn new() -> Self {
+        Self {
+            status: 200,
+            events: Vec::new(),
+            body: None,
+            expect_body: None,
+        }
+    }
+
+    /// Creates a new plain JSON response (for embeddings, tokens counting and etc.)
+    pub fn json(body: JsonValue) -> Self {
+        Self {
+            body: Some((str!("application/json"), Bytes::from(body.to_string()))),
+            ..Self::new()
+        }
+    }
+
+    /// Creates a new plain binary response (for audio, images and etc.)
+    pub fn bytes(mime: impl Into<String>, bytes: impl Into<Bytes>) -> Self {
+        Self {
+            body: Some((mime.into(), bytes.into())),
+            ..Self::new()
+        }
+    }
+
+    /// Sets the HTTP status code
+    pub fn status(mut self, code: u16) -> Self {
+        self.status = code;
+        self
+    }
+
+    /// Adds the text chunk
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.events.push(MockEvent::Text(text.into()));
+        self
+    }
+
+    /// Adds the generated image
+    pub fn image(mut self, mime: impl Into<String>, bytes: impl Into<Bytes>) -> Self {
+        self.events.push(MockEvent::Image {
+            mime: mime.into(),
+            bytes: bytes.into(),
+        });
+        self
+    }
+
+    /// Adds the tool call
+    pub fn tool(mut self, id: impl Into<String>, name: impl Into<String>, args: JsonValue) -> Self {
+        self.events.push(MockEvent::Tool {
+            id: id.into(),
+            name: name.into(),
+            args,
+        });
+        self
+    }
+
+    /// Adds the tokens usage
+    pub fn usage(mut self, usage: Usage) -> Self {
+        self.events.push(MockEvent::Usage(usage));
+        self
+    }
+
+    /// Adds the generation stats (LM Studio native API)
+    pub fn stats(mut self, stats: LmStats) -> Self {
+        self.events.push(MockEvent::Stats(stats));
+        self
+    }
+
+    /// Adds the in-stream error
+    pub fn error(mut self, message: impl Into<String>) -> Self {
+        self.events.push(MockEvent::Error(message.into()));
+        self
+    }
+
+    /// Adds the pause before the next event
+    pub fn delay(mut self, dur: Duration) -> Self {
+        self.events.push(MockEvent::Delay(dur));
+        self
+    }
+
+    /// Expects the request body to contain the JSON fields (responds with an error if it doesn't)
+    pub fn expect_body(mut self, body: JsonValue) -> Self {
+        self.expect_body.replace(body);
+        self
+    }
+}
+
+/// The request received by mock server
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    /// The HTTP method
+    pub method: String,
+    /// The request path with query
+    pub path: String,
+    /// The request headers (lowercase names)
+    pub headers: HashMap<String, String>,
+    /// The request body (`null` if it isn't a JSON)
+    pub body: JsonValue,
+    /// The raw request body (for multipart forms and etc.)
+    pub raw_body: Bytes,
+}
+
+/// The mock server state
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<MockRequest>,
+}
+
+/// The local LM API server emitting the scripted responses in the API wire format
+#[derive(Debug)]
+pub struct MockServer {
+    api_kind: ApiKind,
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl MockServer {
+    /// Starts a new mock server on localhost
+    pub async fn start(kind: ApiKind) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let handle = tokio::spawn({
+            let state = state.clone();
+            let kind = kind.clone();
+
+            async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    let state = state.clone();
+                    let kind = kind.clone();
+
+                    tokio::spawn(async move {
+                        if let Err(e) = Self::handle(socket, &kind, &state).await {
+                            log::warn!("Mock server connection error: {e}");
+                        }
+                    });
+                }
+            }
+        });
+
+        Ok(Self {
+            api_kind: kind,
+            addr,
+            state,
+            handle,
+        })
+    }
+
+    /// Returns the server URL
+    pub fn url(&self) -> String {
+        str!("http://{}", self.addr)
+    }
+
+    /// Adds the scripted response (the responses are sent in order of requests)
+    pub fn respond(&self, response: MockResponse) -> &Self {
+        self.state.lock().unwrap().responses.push_back(response);
+        self
+    }
+
+    /// Returns the received requests
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the last received request
+    pub fn last_request(&self) -> Option<MockRequest> {
+        self.state.lock().unwrap().requests.last().cloned()
+    }
+
+    /// Creates a new completions request to this server
+    pub fn completions(&self, model: impl Into<String>) -> Completions {
+        Completions::new(self.api_kind.clone(), "", model).host(self.url())
+    }
+
+    /// Creates a new embeddings request to this server
+    pub fn embeddings(&self, model: impl Into<String>) -> Embeddings {
+        Embeddings::new(self.api_kind.clone(), "", model).host(self.url())
+    }
+
+    /// Creates a new rerank request to this server
+    pub fn rerank(&self, model: impl Into<String>) -> Rerank {
+        Rerank::new(self.api_kind.clone(), "", model).host(self.url())
+    }
+
+    /// Creates a new audio transcription request to this server
+    pub fn transcription(&self, model: impl Into<String>) -> Transcription {
+        Transcription::new(self.api_kind.clone(), "", model).host(self.url())
+    }
+
+    /// Creates a new text-to-speech request to this server
+    pub fn speech(&self, model: impl Into<String>) -> Speech {
+        Speech::new(self.api_kind.clone(), "", model).host(self.url())
+    }
+
+    /// Creates a new image generation request to this server
+    pub fn image_generation(&self, model: impl Into<String>) -> ImageGeneration {
+        ImageGeneration::new(self.api_kind.clone(), "", model).host(self.url())
+    }
+
+    /// Creates a new LM Studio management client to this server
+    pub fn lmstudio(&self) -> LmStudio {
+        LmStudio::new().host(self.url())
+    }
+
+    /// Handles the connection (a request per connection)
+    async fn handle(mut socket: TcpStream, kind: &ApiKind, state: &Mutex<MockState>) -> Result<()> {
+        let request = Self::read_request(&mut socket).await?;
+
+        let response = {
+            let mut state = state.lock().unwrap();
+            let body = request.body.clone();
+            state.requests.push(request);
+
+            match state.responses.pop_front() {
+                Some(response) => match &response.expect_body {
+                    Some(expected) if !json_contains(&body, expected) => {
+                        MockResponse::new().status(400).error(str!(
+                            "The request body doesn't match: expected {expected}, got {body}"
+                        ))
+                    }
+                    _ => response,
+                },
+                None => MockResponse::new()
+                    .status(500)
+                    .error("The mock server has no scripted response"),
+            }
+        };
+
+        // write response head:
+        let content_type = match &response.body {
+            Some((mime, _)) => mime.as_str(),
+            None => "text/event-stream",
+        };
+        let head = str!(
+            "HTTP/1.1 {} MOCK\r\ncontent-type: {content_type}\r\nconnection: close\r\n\r\n",
+            response.status
+        );
+        socket.write_all(head.as_bytes()).await?;
+
+        // write plain body:
+        if let Some((_, body)) = &response.body {
+            socket.write_all(body).await?;
+            socket.shutdown().await?;
+            return Ok(());
+        }
+
+        // write the events stream:
+        for chunk in Self::encode(kind, &response.events) {
+            match chunk {
+                Ok(data) => {
+                    socket.write_all(data.as_bytes()).await?;
+                    socket.flush().await?;
+                }
+                Err(dur) => tokio::time::sleep(dur).await,
+            }
+        }
+        socket.shutdown().await?;
+
+        Ok(())
+    }
+
+    /// Reads the HTTP request
+    async fn read_request(socket: &mut TcpStream) -> Result<MockRequest> {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 8192];
+
+        // read the head:
+        let head_end = loop {
+            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos;
+            }
+            let n = socket.read(&mut chunk).await?;
+            if n == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        };
+
+        let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+        let mut lines = head.lines();
+        let mut start = lines.next().unwrap_or_default().split_whitespace();
+        let method = start.next().unwrap_or_default().to_string();
+        let path = start.next().unwrap_or_default().to_string();
+
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+            .collect();
+
+        // read the body:
+        let len = headers
+            .get("content-length")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = buffer.split_off(head_end + 4);
+        while body.len() < len {
+            let n = socket.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..n]);
+        }
+
+        Ok(MockRequest {
+            method,
+            path,
+            headers,
+            body: json::from_slice(&body).unwrap_or(JsonValue::Null),
+            raw_body: Bytes::from(body),
+        })
+    }
+
+    /// Encodes the events into SSE chunks of the API format (`Err` is a delay)
+    fn encode(kind: &ApiKind, events: &[MockEvent]) -> Vec<StdResult<String, Duration>> {
+        let sse = |value: JsonValue| Ok(str!("data: {value}\n\n"));
+        let mut output = Vec::new();
+        let has_tools = events.iter().any(|e| matches!(e, MockEvent::Tool { .. }));
+
+        if kind.is_anthropic() {
+            let event = |value: JsonValue| {
+                Ok(str!(
+                    "event: {}\ndata: {value}\n\n",
+                    value["type"].as_str().unwrap_or_default()
+                ))
+            };
+            output.push(event(json!({
+                "type": "message_start",
+                "message": { "id": "msg_mock", "type": "message", "role": "assistant", "content": [] }
+            })));
+
+            for (index, ev) in events.iter().enumerate() {
+                match ev {
+                    MockEvent::Text(text) => {
+                        output.push(event(json!({
+                            "type": "content_block_start", "index": index,
+                            "content_block": { "type": "text", "text": "" }
+                        })));
+                        output.push(event(json!({
+                            "type": "content_block_delta", "index": index,
+                            "delta": { "type": "text_delta", "text": text }
+                        })));
+                        output.push(event(
+                            json!({ "type": "content_block_stop", "index": index }),
+                        ));
+                    }
+                    MockEvent::Tool { id, name, args } => {
+                        output.push(event(json!({
+                            "type": "content_block_start", "index": index,
+                            "content_block": { "type": "tool_use", "id": id, "name": name, "input": {} }
+                        })));
+                        output.push(event(json!({
+                            "type": "content_block_delta", "index": index,
+                            "delta": { "type": "input_json_delta", "partial_json": args.to_string() }
+                        })));
+                        output.push(event(
+                            json!({ "type": "content_block_stop", "index": index }),
+                        ));
+                    }
+                    MockEvent::Usage(usage) => output.push(event(json!({
+                        "type": "message_delta",
+                        "delta": { "stop_reason": if has_tools { "tool_use" } else { "end_turn" } },
+                        "usage": {
+                            "input_tokens": usage.input_tokens,
+                            "output_tokens": usage.output_tokens,
+                            "cache_read_input_tokens": usage.cache_read_tokens,
+                            "cache_creation_input_tokens": usage.cache_write_tokens,
+                        }
+                    }))),
+                    MockEvent::Error(message) => output.push(event(json!({
+                        "type": "error",
+                        "error": { "type": "api_error", "message": message }
+                    }))),
+                    // Anthropic doesn't generate images & stats:
+                    MockEvent::Image { .. } | MockEvent::Stats(_) => {}
+                    MockEvent::Delay(dur) => output.push(Err(*dur)),
+                }
+            }
+
+            output.push(event(json!({ "type": "message_stop" })));
+        } else if kind.is_google() {
+            for ev in events {
+                match ev {
+                    MockEvent::Text(text) => output.push(sse(json!({
+                        "candidates": [{ "content": { "parts": [{ "text": text }], "role": "model" }, "index": 0 }]
+                    }))),
+                    MockEvent::Tool { id, name, args } => output.push(sse(json!({
+                        "candidates": [{
+                            "content": {
+                                "parts": [{ "functionCall": { "id": id, "name": name, "args": args } }],
+                                "role": "model"
+                            },
+                            "index": 0
+                        }]
+                    }))),
+                    MockEvent::Usage(usage) => output.push(sse(json!({
+                        "candidates": [],
+                        "usageMetadata": {
+                            "promptTokenCount": usage.input_tokens,
+                            "candidatesTokenCount": usage.output_tokens,
+                            "cachedContentTokenCount": usage.cache_read_tokens,
+                            "totalTokenCount": usage.total_tokens,
+                        }
+                    }))),
+                    MockEvent::Error(message) => output.push(sse(json!({
+                        "error": { "code": 500, "message": message, "status": "INTERNAL" }
+                    }))),
+                    MockEvent::Image { mime, bytes } => output.push(sse(json!({
+                        "candidates": [{
+                            "content": {
+                                "parts": [{ "inlineData": { "mimeType": mime, "data": media::encode(bytes) } }],
+                                "role": "model"
+                            },
+                            "index": 0
+                        }]
+                    }))),
+                    // Google doesn't report stats:
+                    MockEvent::Stats(_) => {}
+                    MockEvent::Delay(dur) => output.push(Err(*dur)),
+                }
+            }
+        } else if kind.is_responses() {
+            let mut sequence = 0;
+            let mut event = |mut value: JsonValue| {
+                value["sequence_number"] = json!(sequence);
+                sequence += 1;
+                Ok(str!(
+                    "event: {}\ndata: {value}\n\n",
+                    value["type"].as_str().unwrap_or_default()
+                ))
+            };
+            let mut response =
+                json!({ "id": "resp_mock", "object": "response", "status": "in_progress" });
+            output.push(event(
+                json!({ "type": "response.created", "response": response }),
+            ));
+
+            for (index, ev) in events.iter().enumerate() {
+                match ev {
+                    MockEvent::Text(text) => output.push(event(json!({
+                        "type": "response.output_text.delta", "item_id": "msg_mock",
+                        "output_index": index, "content_index": 0, "delta": text
+                    }))),
+                    MockEvent::Tool { id, name, args } => {
+                        let mut item = json!({
+                            "type": "function_call", "id": str!("fc_{index}"),
+                            "call_id": id, "name": name, "arguments": ""
+                        });
+                        output.push(event(json!({
+                            "type": "response.output_item.added", "output_index": index, "item": item
+                        })));
+                        output.push(event(json!({
+                            "type": "response.function_call_arguments.delta", "item_id": item["id"],
+                            "output_index": index, "delta": args.to_string()
+                        })));
+                        item["arguments"] = json!(args.to_string());
+                        output.push(event(json!({
+                            "type": "response.output_item.done", "output_index": index, "item": item
+                        })));
+                    }
+                    MockEvent::Usage(usage) => {
+                        response["usage"] = json!({
+                            "input_tokens": usage.input_tokens,
+                            "output_tokens": usage.output_tokens,
+                            "total_tokens": usage.total_tokens,
+                            "input_tokens_details": { "cached_tokens": usage.cache_read_tokens }
+                        });
+                    }
+                    MockEvent::Error(message) => output.push(event(json!({
+                        "type": "error", "code": "server_error", "message": message, "param": null
+                    }))),
+                    // the Responses API generates images by the tool only:
+                    MockEvent::Image { .. } | MockEvent::Stats(_) => {}
+                    MockEvent::Delay(dur) => output.push(Err(*dur)),
+                }
+            }
+
+            response["status"] = json!("completed");
+            output.push(event(
+                json!({ "type": "response.completed", "response": response }),
+            ));
+        } else {
+            let mut tool_index = 0;
+            let mut usage_chunk = None;
+            let mut stats_chunk = None;
+
+            for ev in events {
+                match ev {
+                    MockEvent::Text(text) => output.push(sse(json!({
+                        "choices": [{ "index": 0, "delta": { "content": text }, "finish_reason": null }]
+                    }))),
+                    MockEvent::Tool { id, name, args } => {
+                        output.push(sse(json!({
+                            "choices": [{
+                                "index": 0,
+                                "delta": {
+                                    "tool_calls": [{
+                                        "index": tool_index, "id": id, "type": "function",
+                                        "function": { "name": name, "arguments": args.to_string() }
+                                    }]
+                                },
+                                "finish_reason": null
+                            }]
+                        })));
+                        tool_index += 1;
+                    }
+                    // the usage is sent after the finish reason:
+                    MockEvent::Usage(usage) => {
+                        usage_chunk.replace(sse(json!({
+                            "choices": [],
+                            "usage": {
+                                "prompt_tokens": usage.input_tokens,
+                                "completion_tokens": usage.output_tokens,
+                                "total_tokens": usage.total_tokens,
+                                "prompt_tokens_details": { "cached_tokens": usage.cache_read_tokens }
+                            }
+                        })));
+                    }
+                    // the stats are sent with the last chunk:
+                    MockEvent::Stats(stats) => {
+                        stats_chunk.replace(sse(json!({ "choices": [], "stats": stats })));
+                    }
+                    MockEvent::Error(message) => output.push(sse(json!({
+                        "error": { "code": 500, "message": message }
+                    }))),
+                    MockEvent::Image { mime, bytes } => output.push(sse(json!({
+                        "choices": [{
+                            "index": 0,
+                            "delta": { "images": [{
+                                "type": "image_url",
+                                "image_url": { "url": media::data_url(mime, bytes) }
+                            }] },
+                            "finish_reason": null
+                        }]
+                    }))),
+                    MockEvent::Delay(dur) => output.push(Err(*dur)),
+                }
+            }
+
+            output.push(sse(json!({
+                "choices": [{
+                    "index": 0,
+                    "delta": {},
+                    "finish_reason": if has_tools { "tool_calls" } else { "stop" }
+                }]
+            })));
+            output.extend(usage_chunk);
+            output.extend(stats_chunk);
+            output.push(Ok(str!("data: [DONE]\n\n")));
+        }
+
+        output
+    }
+}
+
+/// Returns true if the JSON value contains all the expected fields (the arrays are matched by items)
+fn json_contains(value: &JsonValue, expected: &JsonValue) -> bool {
+    match (value, expected) {
+        (JsonValue::Object(value), JsonValue::Object(expected)) => expected
+            .iter()
+            .all(|(k, v)| value.get(k).is_some_and(|value| json_contains(value, v))),
+        (JsonValue::Array(value), JsonValue::Array(expected)) => {
+            value.len() == expected.len()
+                && value.iter().zip(expected).all(|(v, e)| json_contains(v, e))
+        }
+        _ => value == expected,
+    }
+}
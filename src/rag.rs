@@ -0,0 +1,243 @@
+use crate::{
+    AiChunk, BpeTokenizer, Completions, Tokenizer, Usage,
+    prelude::*,
+    vector::{Filter, SearchResult, VectorIndex, VectorStore},
+};
+use futures::future::BoxFuture;
+use regex::Regex;
+use std::sync::{Arc, LazyLock};
+
+/// The citation marker regex (`[1]`, `[2, 3]`)
+static CITATION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(\d+(?:\s*,\s*\d+)*)\]").unwrap());
+
+/// The default answer instructions
+const INSTRUCTIONS: &str = "Answer the question using only the sources below. \
+    Cite the used sources with their markers, like [1] or [1, 2]. \
+    If the sources don't contain the answer, say so.";
+
+/// The passages retriever (implement it to plug a search engine or database)
+pub trait Retriever: Send + Sync {
+    /// Returns the top-k passages relevant to the query (matching the metadata filter)
+    fn retrieve<'a>(
+        &'a self,
+        query: &'a str,
+        k: usize,
+        filter: Option<&'a Filter>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>>;
+}
+
+impl<I: VectorIndex> Retriever for VectorStore<I> {
+    fn retrieve<'a>(
+        &'a self,
+        query: &'a str,
+        k: usize,
+        filter: Option<&'a Filter>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(self.query(query, k, filter))
+    }
+}
+
+/// The cited source passage
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Source {
+    /// The citation marker number
+    pub marker: usize,
+    /// The source passage
+    #[serde(flatten)]
+    pub passage: SearchResult,
+}
+
+/// The retrieved context prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagContext {
+    /// The prompt with sources & question
+    pub prompt: String,
+    /// The sources included into prompt
+    pub sources: Vec<Source>,
+}
+
+impl RagContext {
+    /// Returns the sources cited in the answer (in order of the first citation)
+    pub fn citations(&self, answer: &str) -> Vec<Source> {
+        let mut markers = Vec::new();
+        for caps in CITATION_RE.captures_iter(answer) {
+            for num in caps[1]
+                .split(',')
+                .filter_map(|n| n.trim().parse::<usize>().ok())
+            {
+                if !markers.contains(&num) {
+                    markers.push(num);
+                }
+            }
+        }
+
+        markers
+            .into_iter()
+            .filter_map(|num| self.sources.iter().find(|s| s.marker == num).cloned())
+            .collect()
+    }
+}
+
+/// The RAG answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagAnswer {
+    /// The answer text
+    pub text: String,
+    /// The sources cited in the answer
+    pub citations: Vec<Source>,
+    /// The retrieved context
+    pub context: RagContext,
+    /// The completion tokens usage
+    pub usage: Option<Usage>,
+}
+
+/// The retrieval-augmented completions helper
+#[derive(Clone)]
+pub struct Rag {
+    retriever: Arc<dyn Retriever>,
+    top_k: usize,
+    token_budget: usize,
+    filter: Option<Filter>,
+    instructions: String,
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+}
+
+impl std::fmt::Debug for Rag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rag")
+            .field("top_k", &self.top_k)
+            .field("token_budget", &self.token_budget)
+            .field("filter", &self.filter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Rag {
+    /// Creates a new RAG helper
+    pub fn new(retriever: Arc<dyn Retriever>) -> Self {
+        Self {
+            retriever,
+            top_k: 5,
+            token_budget: 2000,
+            filter: None,
+            instructions: INSTRUCTIONS.to_string(),
+            tokenizer: None,
+        }
+    }
+
+    /// Sets the retrieved passages count (5 by default)
+    pub fn top_k(mut self, k: usize) -> Self {
+        self.top_k = k;
+        self
+    }
+
+    /// Sets the max sources tokens in prompt (2000 by default)
+    pub fn token_budget(mut self, tokens: usize) -> Self {
+        self.token_budget = tokens;
+        self
+    }
+
+    /// Sets the passages metadata filter
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter.replace(filter);
+        self
+    }
+
+    /// Sets the custom answer instructions
+    pub fn instructions(mut self, text: impl Into<String>) -> Self {
+        self.instructions = text.into();
+        self
+    }
+
+    /// Sets the sources tokenizer (the completions model tokenizer is used by default)
+    pub fn tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer.replace(tokenizer);
+        self
+    }
+
+    /// Retrieves the passages and formats the prompt (the passages over the token budget are skipped,
+    /// the tokens are counted by custom tokenizer or the `cl100k` approximation)
+    pub async fn context(&self, query: &str) -> Result<RagContext> {
+        match &self.tokenizer {
+            Some(tokenizer) => self.context_with(query, &**tokenizer).await,
+            None => self.context_with(query, &BpeTokenizer::default()).await,
+        }
+    }
+
+    /// Retrieves the passages and formats the prompt (the tokens are counted by the tokenizer)
+    async fn context_with(&self, query: &str, tokenizer: &dyn Tokenizer) -> Result<RagContext> {
+        let passages = self
+            .retriever
+            .retrieve(query, self.top_k, self.filter.as_ref())
+            .await?;
+
+        let mut sources = Vec::new();
+        let mut lines = Vec::new();
+        let mut tokens = 0;
+
+        for passage in passages {
+            let line = str!("[{}] {}", sources.len() + 1, passage.text.trim());
+            let count = tokenizer.count_tokens(&line);
+            if tokens + count > self.token_budget {
+                continue;
+            }
+            tokens += count;
+
+            lines.push(line);
+            sources.push(Source {
+                marker: sources.len() + 1,
+                passage,
+            });
+        }
+
+        Ok(RagContext {
+            prompt: str!(
+                "{}\n\nSources:\n{}\n\nQuestion: {query}",
+                self.instructions,
+                lines.join("\n\n")
+            ),
+            sources,
+        })
+    }
+
+    /// Adds the retrieved context prompt as a user message
+    pub async fn prepare(
+        &self,
+        completions: Completions,
+        query: &str,
+    ) -> Result<(Completions, RagContext)> {
+        let tokenizer = self
+            .tokenizer
+            .clone()
+            .unwrap_or_else(|| completions.get_tokenizer());
+        let context = self.context_with(query, &*tokenizer).await?;
+        let completions = completions.user_message(vec![context.prompt.clone().into()]);
+
+        Ok((completions, context))
+    }
+
+    /// Sends the retrieval-augmented request and reads the answer with citations
+    pub async fn send(&self, completions: Completions, query: &str) -> Result<RagAnswer> {
+        let (completions, context) = self.prepare(completions, query).await?;
+        let mut response = completions.send().await?;
+
+        // read response stream:
+        let mut text = String::new();
+        let mut usage = None;
+        while let Some(chunk) = response.next().await {
+            match chunk? {
+                AiChunk::Text { text: part } => text.push_str(&part),
+                AiChunk::Usage { usage: u } => usage = Some(u),
+                _ => {}
+            }
+        }
+
+        Ok(RagAnswer {
+            citations: context.citations(&text),
+            text,
+            context,
+            usage,
+        })
+    }
+}
@@ -0,0 +1,18 @@
+use crate::prelude::*;
+
+/// The text chunk (a slice of the source text)
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct TextChunk {
+    /// The chunk text
+    pub text: String,
+    /// The start byte offset in source text
+    pub start: usize,
+    /// The end byte offset in source text (exclusive)
+    pub end: usize,
+    /// The tokens count
+    pub tokens: usize,
+    /// The Markdown headings path (like `Intro > Install`)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+}
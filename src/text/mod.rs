@@ -0,0 +1,5 @@
+pub mod chunk;
+pub use chunk::TextChunk;
+
+pub mod splitter;
+pub use splitter::{SplitMode, TextSplitter};
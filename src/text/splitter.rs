@@ -0,0 +1,372 @@
+use super::TextChunk;
+use crate::{BpeTokenizer, Tokenizer, prelude::*};
+use std::{ops::Range, sync::Arc};
+
+/// The recursive splitter separators (from the largest text units)
+const TEXT_SEPARATORS: &[&str] = &["\n\n", "\n", ". ", "? ", "! ", "; ", ", ", " ", ""];
+/// The code splitter separators
+const CODE_SEPARATORS: &[&str] = &["\n\n", "\n", "; ", " ", ""];
+/// The sentence parts separators
+const SENTENCE_SEPARATORS: &[&str] = &["; ", ", ", " ", ""];
+
+/// The text splitting mode
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitMode {
+    /// By paragraphs, lines, sentences, words and chars
+    #[default]
+    Recursive,
+    /// By sentences
+    Sentence,
+    /// By Markdown sections (the chunks don't cross headings)
+    Markdown,
+    /// By top-level code blocks (blank lines outside braces)
+    Code,
+}
+
+/// The token-bounded text splitter (for embedding pipelines)
+#[derive(Debug, Clone)]
+pub struct TextSplitter {
+    mode: SplitMode,
+    max_tokens: usize,
+    overlap: usize,
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl TextSplitter {
+    /// Creates a new text splitter
+    pub fn new(mode: SplitMode, max_tokens: usize) -> Self {
+        Self {
+            mode,
+            max_tokens: max_tokens.max(1),
+            overlap: 0,
+            tokenizer: Arc::new(BpeTokenizer::default()),
+        }
+    }
+
+    /// Creates a new recursive character splitter
+    pub fn recursive(max_tokens: usize) -> Self {
+        Self::new(SplitMode::Recursive, max_tokens)
+    }
+
+    /// Creates a new sentence splitter
+    pub fn sentence(max_tokens: usize) -> Self {
+        Self::new(SplitMode::Sentence, max_tokens)
+    }
+
+    /// Creates a new Markdown heading-aware splitter
+    pub fn markdown(max_tokens: usize) -> Self {
+        Self::new(SplitMode::Markdown, max_tokens)
+    }
+
+    /// Creates a new code-aware splitter
+    pub fn code(max_tokens: usize) -> Self {
+        Self::new(SplitMode::Code, max_tokens)
+    }
+
+    /// Sets the overlap tokens between neighbour chunks
+    pub fn overlap(mut self, tokens: usize) -> Self {
+        self.overlap = tokens;
+        self
+    }
+
+    /// Sets the custom tokenizer (`cl100k` by default)
+    pub fn tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Splits the text into chunks
+    pub fn split(&self, text: &str) -> Vec<TextChunk> {
+        let all = 0..text.len();
+
+        match self.mode {
+            SplitMode::Recursive => {
+                let mut pieces = Vec::new();
+                self.split_range(text, all, TEXT_SEPARATORS, &mut pieces);
+                self.merge(text, &pieces, None)
+            }
+            SplitMode::Sentence => {
+                let mut pieces = Vec::new();
+                for range in sentences(text) {
+                    self.split_range(text, range, SENTENCE_SEPARATORS, &mut pieces);
+                }
+                self.merge(text, &pieces, None)
+            }
+            SplitMode::Markdown => markdown_sections(text)
+                .into_iter()
+                .flat_map(|(range, heading)| {
+                    let mut pieces = Vec::new();
+                    self.split_range(text, range, TEXT_SEPARATORS, &mut pieces);
+                    self.merge(text, &pieces, heading)
+                })
+                .collect(),
+            SplitMode::Code => {
+                let mut pieces = Vec::new();
+                for range in code_blocks(text) {
+                    self.split_range(text, range, CODE_SEPARATORS, &mut pieces);
+                }
+                self.merge(text, &pieces, None)
+            }
+        }
+    }
+
+    /// Splits the text range into pieces fitting the token limit (with tokens count)
+    fn split_range(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        separators: &[&str],
+        output: &mut Vec<(Range<usize>, usize)>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let tokens = self.tokenizer.count_tokens(&text[range.clone()]);
+        if tokens <= self.max_tokens {
+            output.push((range, tokens));
+            return;
+        }
+
+        match separators.split_first() {
+            // split by separator (it stays at the part end):
+            Some((sep, rest)) if !sep.is_empty() => {
+                let slice = &text[range.clone()];
+                let mut start = 0;
+                for (idx, _) in slice.match_indices(sep) {
+                    let end = idx + sep.len();
+                    self.split_range(text, range.start + start..range.start + end, rest, output);
+                    start = end;
+                }
+                self.split_range(text, range.start + start..range.end, rest, output);
+            }
+            // split by chars (the window is halved while it doesn't fit):
+            _ => {
+                let bounds: Vec<usize> = text[range.clone()]
+                    .char_indices()
+                    .map(|(idx, _)| range.start + idx)
+                    .chain([range.end])
+                    .collect();
+                let mut size = self.max_tokens;
+                let mut pos = 0;
+
+                while pos + 1 < bounds.len() {
+                    let end = (pos + size).min(bounds.len() - 1);
+                    let tokens = self.tokenizer.count_tokens(&text[bounds[pos]..bounds[end]]);
+                    if tokens > self.max_tokens && end - pos > 1 {
+                        size = (end - pos) / 2;
+                        continue;
+                    }
+                    output.push((bounds[pos]..bounds[end], tokens));
+                    pos = end;
+                }
+            }
+        }
+    }
+
+    /// Merges the neighbour pieces into chunks (with overlap)
+    fn merge(
+        &self,
+        text: &str,
+        pieces: &[(Range<usize>, usize)],
+        heading: Option<String>,
+    ) -> Vec<TextChunk> {
+        let mut chunks = Vec::new();
+        let mut i = 0;
+
+        while i < pieces.len() {
+            // take the pieces while they fit:
+            let mut j = i;
+            let mut tokens = 0;
+            while j < pieces.len() && (j == i || tokens + pieces[j].1 <= self.max_tokens) {
+                tokens += pieces[j].1;
+                j += 1;
+            }
+
+            if let Some(chunk) = self.chunk(text, pieces[i].0.start..pieces[j - 1].0.end, &heading)
+            {
+                chunks.push(chunk);
+            }
+            if j >= pieces.len() {
+                break;
+            }
+
+            // step back for overlap (leaving space for the next piece):
+            let mut k = j;
+            let mut overlap = 0;
+            while k > i + 1
+                && overlap + pieces[k - 1].1 <= self.overlap
+                && overlap + pieces[k - 1].1 + pieces[j].1 <= self.max_tokens
+            {
+                overlap += pieces[k - 1].1;
+                k -= 1;
+            }
+            i = k;
+        }
+
+        chunks
+    }
+
+    /// Creates a chunk from the text range (without the edge whitespaces)
+    fn chunk(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        heading: &Option<String>,
+    ) -> Option<TextChunk> {
+        let slice = &text[range.clone()];
+        let trimmed = slice.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let start = range.start + (slice.len() - slice.trim_start().len());
+
+        Some(TextChunk {
+            text: trimmed.to_string(),
+            start,
+            end: start + trimmed.len(),
+            tokens: self.tokenizer.count_tokens(trimmed),
+            heading: heading.clone(),
+        })
+    }
+}
+
+/// Returns the sentence ranges (the sentence ends with `.`, `!`, `?` or a paragraph break)
+fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((idx, ch)) = chars.next() {
+        let is_end = match ch {
+            '.' | '!' | '?' | '…' => {
+                // include the closing quotes & brackets:
+                while let Some(&(_, next)) = chars.peek() {
+                    if matches!(next, '"' | '\'' | ')' | ']' | '»' | '”' | '.' | '!' | '?') {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                chars.peek().is_none_or(|(_, next)| next.is_whitespace())
+            }
+            '\n' => chars.peek().is_some_and(|(_, next)| *next == '\n'),
+            _ => false,
+        };
+
+        if is_end {
+            // include the trailing whitespaces:
+            let mut end = chars.peek().map_or(text.len(), |(i, _)| *i);
+            while let Some(&(i, next)) = chars.peek() {
+                if !next.is_whitespace() {
+                    break;
+                }
+                chars.next();
+                end = i + next.len_utf8();
+            }
+            if end <= idx {
+                end = idx + ch.len_utf8();
+            }
+
+            ranges.push(start..end);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        ranges.push(start..text.len());
+    }
+
+    ranges
+}
+
+/// Returns the line ranges (with line breaks)
+fn lines(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = 0;
+    text.split_inclusive('\n').map(move |line| {
+        let range = start..start + line.len();
+        start = range.end;
+        range
+    })
+}
+
+/// Returns the Markdown sections (with headings path)
+fn markdown_sections(text: &str) -> Vec<(Range<usize>, Option<String>)> {
+    let mut sections = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut start = 0;
+    let mut in_fence = false;
+
+    let path = |headings: &[(usize, String)]| {
+        (!headings.is_empty()).then(|| {
+            headings
+                .iter()
+                .map(|(_, title)| title.as_str())
+                .collect::<Vec<_>>()
+                .join(" > ")
+        })
+    };
+
+    for range in lines(text) {
+        let line = text[range.clone()].trim_end();
+
+        // skip the code fences:
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            if start < range.start {
+                sections.push((start..range.start, path(&headings)));
+            }
+            start = range.start;
+
+            while headings.last().is_some_and(|(lvl, _)| *lvl >= level) {
+                headings.pop();
+            }
+            headings.push((level, line[level..].trim().to_string()));
+        }
+    }
+    if start < text.len() {
+        sections.push((start..text.len(), path(&headings)));
+    }
+
+    sections
+}
+
+/// Returns the top-level code blocks (split by blank lines and closed top-level braces)
+fn code_blocks(text: &str) -> Vec<Range<usize>> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut depth: i64 = 0;
+
+    for range in lines(text) {
+        let line = &text[range.clone()];
+        let was_nested = depth > 0;
+
+        for ch in line.chars() {
+            match ch {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth -= 1,
+                _ => {}
+            }
+        }
+        depth = depth.max(0);
+
+        let is_blank = line.trim().is_empty();
+        let closed_block = was_nested && depth == 0;
+        if depth == 0 && (is_blank || closed_block) && start < range.end {
+            blocks.push(start..range.end);
+            start = range.end;
+        }
+    }
+    if start < text.len() {
+        blocks.push(start..text.len());
+    }
+
+    blocks
+}
@@ -1,12 +1,14 @@
-// use crate::prelude::*;
+pub mod tokenizer;
+#[cfg(feature = "tokenizers")]
+pub use tokenizer::HfTokenizer;
+pub use tokenizer::{BpeTokenizer, Tokenizer, model_family, tokenizer_for};
 
-/// Tokenizes text
+/// Tokenizes text (`cl100k` encoding)
 pub fn tokenize(text: &str) -> Vec<u32> {
-    let tokenizer = tiktoken_rs::cl100k_base().expect("Failed to create tokenizer");
-    tokenizer.encode_with_special_tokens(text)
+    BpeTokenizer::default().tokenize(text)
 }
 
-/// Returns tokens count in string
+/// Returns tokens count in string (`cl100k` encoding)
 pub fn count_tokens(text: &str) -> usize {
     tokenize(text).len()
 }
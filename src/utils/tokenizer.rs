@@ -0,0 +1,217 @@
+use crate::{ApiKind, prelude::*};
+use std::sync::Arc;
+#[cfg(feature = "tokenizers")]
+use std::{
+    path::PathBuf,
+    sync::{LazyLock, RwLock},
+};
+use tiktoken_rs::CoreBPE;
+
+/// The text tokenizer
+pub trait Tokenizer: std::fmt::Debug + Send + Sync {
+    /// Tokenizes text
+    fn tokenize(&self, text: &str) -> Vec<u32>;
+
+    /// Returns tokens count in string
+    fn count_tokens(&self, text: &str) -> usize {
+        self.tokenize(text).len()
+    }
+}
+
+/// The OpenAI BPE tokenizer (cached `tiktoken` encodings)
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BpeTokenizer {
+    /// GPT-4, GPT-3.5 (and the approximation for other models)
+    #[default]
+    Cl100k,
+    /// GPT-4o, GPT-4.1, GPT-5, o1, o3, o4
+    O200k,
+    /// GPT-OSS
+    O200kHarmony,
+}
+
+impl BpeTokenizer {
+    /// Returns the OpenAI model tokenizer
+    pub fn for_model(model: &str) -> Option<Self> {
+        use tiktoken_rs::tokenizer::{Tokenizer as Encoding, get_tokenizer};
+
+        // cut off the provider prefix (example: "openai/gpt-4o"):
+        let model = model.rsplit('/').next().unwrap_or(model);
+
+        match get_tokenizer(model)? {
+            Encoding::O200kBase => Some(Self::O200k),
+            Encoding::O200kHarmony => Some(Self::O200kHarmony),
+            Encoding::Cl100kBase => Some(Self::Cl100k),
+            _ => None,
+        }
+    }
+
+    /// Returns the cached BPE encoding
+    fn bpe(&self) -> &'static CoreBPE {
+        match self {
+            Self::Cl100k => tiktoken_rs::cl100k_base_singleton(),
+            Self::O200k => tiktoken_rs::o200k_base_singleton(),
+            Self::O200kHarmony => tiktoken_rs::o200k_harmony_singleton(),
+        }
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<u32> {
+        self.bpe().encode_with_special_tokens(text)
+    }
+}
+
+/// The open-weights model families (the first matched name part)
+const MODEL_FAMILIES: &[(&str, &str)] = &[
+    ("llama", "llama"),
+    ("qwen", "qwen"),
+    ("qwq", "qwen"),
+    ("mistral", "mistral"),
+    ("mixtral", "mistral"),
+    ("ministral", "mistral"),
+    ("gemma", "gemma"),
+    ("phi", "phi"),
+    ("deepseek", "deepseek"),
+    ("glm", "glm"),
+];
+
+/// Returns the open-weights model family (`llama`, `qwen`, `mistral`, `gemma`, `phi`, `deepseek`, `glm`)
+pub fn model_family(model: &str) -> Option<&'static str> {
+    let model = model.to_lowercase();
+    let name = model.rsplit('/').next().unwrap_or(&model);
+
+    MODEL_FAMILIES
+        .iter()
+        .find(|(part, _)| name.starts_with(part) || name.contains(&str!("-{part}")))
+        .map(|(_, family)| *family)
+}
+
+/// The registered & found HuggingFace tokenizers (by model family or model ID)
+#[cfg(feature = "tokenizers")]
+static HF_TOKENIZERS: LazyLock<RwLock<HashMap<String, Option<HfTokenizer>>>> =
+    LazyLock::new(Default::default);
+
+/// The HuggingFace tokenizer (from `tokenizer.json` file)
+#[cfg(feature = "tokenizers")]
+#[derive(Clone)]
+pub struct HfTokenizer {
+    inner: Arc<tokenizers::Tokenizer>,
+}
+
+#[cfg(feature = "tokenizers")]
+impl HfTokenizer {
+    /// Reads the tokenizer from `tokenizer.json` file
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(tokenizers::Tokenizer::from_file(path)?),
+        })
+    }
+
+    /// Reads the tokenizer from `tokenizer.json` bytes
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(tokenizers::Tokenizer::from_bytes(bytes)?),
+        })
+    }
+
+    /// Registers the tokenizer for the model family or model ID (is chosen by `tokenizer_for`)
+    pub fn register(self, family: impl Into<String>) {
+        if let Ok(mut map) = HF_TOKENIZERS.write() {
+            // the cached misses may be covered by the new tokenizer:
+            map.retain(|_, found| found.is_some());
+            map.insert(family.into().to_lowercase(), Some(self));
+        }
+    }
+
+    /// Reads the tokenizer of the downloaded HuggingFace hub model (`$HF_HOME/hub`, `~/.cache/huggingface/hub`)
+    pub fn from_hub_cache(model_id: &str) -> Option<Self> {
+        let hub = std::env::var_os("HF_HUB_CACHE")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HF_HOME").map(|home| PathBuf::from(home).join("hub")))
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".cache/huggingface/hub"))
+            })?;
+        let snapshots = hub
+            .join(str!("models--{}", model_id.replace('/', "--")))
+            .join("snapshots");
+
+        std::fs::read_dir(snapshots)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("tokenizer.json"))
+            .find(|path| path.is_file())
+            .and_then(|path| Self::from_file(path).ok())
+    }
+
+    /// Returns the tokenizer for the model (the registered model ID or family, then the hub cache)
+    pub fn for_model(model: &str) -> Option<Self> {
+        let id = model.to_lowercase();
+        if let Ok(map) = HF_TOKENIZERS.read() {
+            if let Some(Some(found)) = map.get(&id) {
+                return Some(found.clone());
+            }
+            if let Some(found) =
+                model_family(model).and_then(|family| map.get(family).cloned().flatten())
+            {
+                return Some(found);
+            }
+            // the cached miss:
+            if map.contains_key(&id) {
+                return None;
+            }
+        }
+
+        // remember the search result (the missing tokenizers too):
+        let found = Self::from_hub_cache(model);
+        if let Ok(mut map) = HF_TOKENIZERS.write() {
+            map.insert(id, found.clone());
+        }
+        found
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl std::fmt::Debug for HfTokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HfTokenizer")
+            .field("vocab_size", &self.inner.get_vocab_size(true))
+            .finish()
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl Tokenizer for HfTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<u32> {
+        match self.inner.encode(text, false) {
+            Ok(enc) => enc.get_ids().to_vec(),
+            // the BPE approximation is better than zero tokens (the context isn't trimmed):
+            Err(e) => {
+                log::warn!("Failed to tokenize text, the cl100k approximation is used: {e}");
+                BpeTokenizer::default().tokenize(text)
+            }
+        }
+    }
+}
+
+/// Returns the tokenizer for the model:
+/// * the OpenAI models use their `tiktoken` encodings;
+/// * the open-weights models (Llama, Qwen, Mistral and etc.) use the registered or downloaded HuggingFace
+///   tokenizers with the `tokenizers` feature (see `HfTokenizer::register` and `HfTokenizer::from_hub_cache`);
+/// * the others (Claude, Gemini and unknown models) use the `cl100k` approximation.
+pub fn tokenizer_for(kind: &ApiKind, model: &str) -> Arc<dyn Tokenizer> {
+    if kind.is_openai()
+        && let Some(tokenizer) = BpeTokenizer::for_model(model)
+    {
+        return Arc::new(tokenizer);
+    }
+
+    #[cfg(feature = "tokenizers")]
+    if let Some(tokenizer) = HfTokenizer::for_model(model) {
+        return Arc::new(tokenizer);
+    }
+
+    log::debug!("No tokenizer for the '{model}' model, the cl100k approximation is used");
+    Arc::new(BpeTokenizer::default())
+}
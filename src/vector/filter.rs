@@ -0,0 +1,133 @@
+use crate::prelude::*;
+use std::cmp::Ordering;
+
+/// The metadata filter
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    /// The field equals to value
+    Eq(String, JsonValue),
+    /// The field doesn't equal to value
+    Ne(String, JsonValue),
+    /// The field equals to one of values
+    In(String, Vec<JsonValue>),
+    /// The field is greater than value (numbers & strings)
+    Gt(String, JsonValue),
+    /// The field is greater than or equal to value
+    Gte(String, JsonValue),
+    /// The field is less than value
+    Lt(String, JsonValue),
+    /// The field is less than or equal to value
+    Lte(String, JsonValue),
+    /// The field exists
+    Exists(String),
+    /// All the filters match
+    And(Vec<Filter>),
+    /// Any of the filters match
+    Or(Vec<Filter>),
+    /// The filter doesn't match
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Creates the `field == value` filter
+    pub fn eq(key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        Self::Eq(key.into(), value.into())
+    }
+
+    /// Creates the `field != value` filter
+    pub fn ne(key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        Self::Ne(key.into(), value.into())
+    }
+
+    /// Creates the `field in values` filter
+    pub fn any_of(key: impl Into<String>, values: Vec<JsonValue>) -> Self {
+        Self::In(key.into(), values)
+    }
+
+    /// Creates the `field > value` filter
+    pub fn gt(key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        Self::Gt(key.into(), value.into())
+    }
+
+    /// Creates the `field >= value` filter
+    pub fn gte(key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        Self::Gte(key.into(), value.into())
+    }
+
+    /// Creates the `field < value` filter
+    pub fn lt(key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        Self::Lt(key.into(), value.into())
+    }
+
+    /// Creates the `field <= value` filter
+    pub fn lte(key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        Self::Lte(key.into(), value.into())
+    }
+
+    /// Creates the field existence filter
+    pub fn exists(key: impl Into<String>) -> Self {
+        Self::Exists(key.into())
+    }
+
+    /// Combines the filters with `and`
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Self::And(mut list) => {
+                list.push(other);
+                Self::And(list)
+            }
+            this => Self::And(vec![this, other]),
+        }
+    }
+
+    /// Combines the filters with `or`
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Self::Or(mut list) => {
+                list.push(other);
+                Self::Or(list)
+            }
+            this => Self::Or(vec![this, other]),
+        }
+    }
+
+    /// Returns true if the metadata matches the filter
+    pub fn matches(&self, metadata: &HashMap<String, JsonValue>) -> bool {
+        let cmp = |key: &String, value: &JsonValue| {
+            metadata.get(key).and_then(|field| compare(field, value))
+        };
+
+        match self {
+            Self::Eq(key, value) => metadata.get(key) == Some(value),
+            Self::Ne(key, value) => metadata.get(key) != Some(value),
+            Self::In(key, values) => metadata.get(key).is_some_and(|v| values.contains(v)),
+            Self::Gt(key, value) => cmp(key, value) == Some(Ordering::Greater),
+            Self::Gte(key, value) => cmp(key, value).is_some_and(Ordering::is_ge),
+            Self::Lt(key, value) => cmp(key, value) == Some(Ordering::Less),
+            Self::Lte(key, value) => cmp(key, value).is_some_and(Ordering::is_le),
+            Self::Exists(key) => metadata.contains_key(key),
+            Self::And(list) => list.iter().all(|f| f.matches(metadata)),
+            Self::Or(list) => list.iter().any(|f| f.matches(metadata)),
+            Self::Not(filter) => !filter.matches(metadata),
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Self;
+
+    /// Negates the filter
+    fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+}
+
+/// Compares the JSON numbers or strings
+fn compare(a: &JsonValue, b: &JsonValue) -> Option<Ordering> {
+    match (a, b) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (JsonValue::String(a), JsonValue::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
@@ -0,0 +1,122 @@
+use super::{
+    Filter, Metric, SearchResult, VectorIndex, VectorRecord, check_dims, check_query, top_k,
+};
+use crate::prelude::*;
+use std::{fs, path::Path};
+
+/// The brute-force vector index (exact search)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlatIndex {
+    metric: Metric,
+    dims: Option<usize>,
+    records: Vec<VectorRecord>,
+    #[serde(skip)]
+    ids: HashMap<String, usize>,
+}
+
+impl FlatIndex {
+    /// Creates a new empty index
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the vector dimensions
+    pub fn dims(&self) -> Option<usize> {
+        self.dims
+    }
+
+    /// Returns the records
+    pub fn records(&self) -> &[VectorRecord] {
+        &self.records
+    }
+
+    /// Serializes the index into JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(json::to_string(self)?)
+    }
+
+    /// Parses the index from JSON string (the records must have the index dimensions)
+    pub fn from_json(s: &str) -> Result<Self> {
+        let mut this: Self = json::from_str(s)?;
+        for record in &this.records {
+            check_dims(&mut this.dims, &record.vector)?;
+        }
+        this.ids = this
+            .records
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| (r.id.clone(), idx))
+            .collect();
+        Ok(this)
+    }
+
+    /// Saves the index into file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Loads the index from file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl VectorIndex for FlatIndex {
+    fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    fn add(&mut self, record: VectorRecord) -> Result<()> {
+        check_dims(&mut self.dims, &record.vector)?;
+
+        match self.ids.get(&record.id) {
+            Some(&idx) => self.records[idx] = record,
+            None => {
+                self.ids.insert(record.id.clone(), self.records.len());
+                self.records.push(record);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: &str) -> Option<VectorRecord> {
+        let idx = self.ids.remove(id)?;
+        let record = self.records.swap_remove(idx);
+
+        // fix the moved record position:
+        if let Some(moved) = self.records.get(idx) {
+            self.ids.insert(moved.id.clone(), idx);
+        }
+        Some(record)
+    }
+
+    fn get(&self, id: &str) -> Option<&VectorRecord> {
+        self.ids.get(id).map(|&idx| &self.records[idx])
+    }
+
+    fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        check_query(self.dims, query)?;
+
+        let results = self
+            .records
+            .iter()
+            .filter(|r| filter.is_none_or(|f| f.matches(&r.metadata)))
+            .map(|r| SearchResult::new(r, self.metric.score(query, &r.vector)))
+            .collect();
+
+        Ok(top_k(results, k))
+    }
+}
@@ -0,0 +1,363 @@
+use super::{
+    Filter, Metric, SearchResult, VectorIndex, VectorRecord, check_dims, check_query, top_k,
+};
+use crate::prelude::*;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fs,
+    path::Path,
+};
+
+/// The graph node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    record: VectorRecord,
+    deleted: bool,
+    layers: Vec<Vec<usize>>,
+}
+
+/// The search candidate (ordered by distance)
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    dist: f32,
+    idx: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then(self.idx.cmp(&other.idx))
+    }
+}
+
+/// The HNSW graph vector index (approximate search)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndex {
+    metric: Metric,
+    dims: Option<usize>,
+    m: usize,
+    ef_construction: usize,
+    ef_search: usize,
+    nodes: Vec<Node>,
+    entry: Option<usize>,
+    deleted: usize,
+    seed: u64,
+    #[serde(skip)]
+    ids: HashMap<String, usize>,
+}
+
+impl Default for HnswIndex {
+    fn default() -> Self {
+        Self::new(Metric::default())
+    }
+}
+
+impl HnswIndex {
+    /// Creates a new empty index
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            dims: None,
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+            nodes: Vec::new(),
+            entry: None,
+            deleted: 0,
+            seed: 0x2545_f491_4f6c_dd1d,
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Sets the max neighbors per node (16 by default)
+    pub fn m(mut self, m: usize) -> Self {
+        self.m = m.max(2);
+        self
+    }
+
+    /// Sets the candidates count on insert (200 by default)
+    pub fn ef_construction(mut self, ef: usize) -> Self {
+        self.ef_construction = ef.max(1);
+        self
+    }
+
+    /// Sets the candidates count on search (64 by default)
+    pub fn ef_search(mut self, ef: usize) -> Self {
+        self.ef_search = ef.max(1);
+        self
+    }
+
+    /// Returns the vector dimensions
+    pub fn dims(&self) -> Option<usize> {
+        self.dims
+    }
+
+    /// Rebuilds the graph without the removed records
+    pub fn compact(&mut self) -> Result<()> {
+        let records: Vec<VectorRecord> = self
+            .nodes
+            .drain(..)
+            .filter(|node| !node.deleted)
+            .map(|node| node.record)
+            .collect();
+
+        self.entry = None;
+        self.deleted = 0;
+        self.ids.clear();
+
+        for record in records {
+            self.add(record)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the index into JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(json::to_string(self)?)
+    }
+
+    /// Parses the index from JSON string (the records must have the index dimensions)
+    pub fn from_json(s: &str) -> Result<Self> {
+        let mut this: Self = json::from_str(s)?;
+        for node in &this.nodes {
+            check_dims(&mut this.dims, &node.record.vector)?;
+        }
+        this.ids = this
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| !node.deleted)
+            .map(|(idx, node)| (node.record.id.clone(), idx))
+            .collect();
+        Ok(this)
+    }
+
+    /// Saves the index into file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Loads the index from file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the distance between vectors (lower is closer)
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        -self.metric.score(a, b)
+    }
+
+    /// Returns the max neighbors count on layer
+    fn max_neighbors(&self, layer: usize) -> usize {
+        if layer == 0 { self.m * 2 } else { self.m }
+    }
+
+    /// Returns the random node level (xorshift generator)
+    fn random_level(&mut self) -> usize {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        let uniform = ((self.seed >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let mult = 1.0 / (self.m as f64).ln();
+        (-uniform.ln() * mult).floor() as usize
+    }
+
+    /// Returns the closest `ef` nodes on layer (sorted by distance)
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+
+        for &idx in entries {
+            let dist = self.distance(query, &self.nodes[idx].record.vector);
+            candidates.push(Reverse(Candidate { dist, idx }));
+            results.push(Candidate { dist, idx });
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            if results
+                .peek()
+                .is_some_and(|far: &Candidate| current.dist > far.dist && results.len() >= ef)
+            {
+                break;
+            }
+
+            let Some(neighbors) = self.nodes[current.idx].layers.get(layer) else {
+                continue;
+            };
+            for &idx in neighbors {
+                if !visited.insert(idx) {
+                    continue;
+                }
+
+                let dist = self.distance(query, &self.nodes[idx].record.vector);
+                if results.len() < ef || results.peek().is_some_and(|far| dist < far.dist) {
+                    candidates.push(Reverse(Candidate { dist, idx }));
+                    results.push(Candidate { dist, idx });
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    /// Returns the entry point on layer (greedy descent from the top layer)
+    fn descend(&self, query: &[f32], to_layer: usize) -> Option<usize> {
+        let mut entry = self.entry?;
+        let top = self.nodes[entry].layers.len() - 1;
+
+        for layer in (to_layer + 1..=top).rev() {
+            if let Some(closest) = self.search_layer(query, &[entry], 1, layer).first() {
+                entry = closest.idx;
+            }
+        }
+        Some(entry)
+    }
+
+    /// Inserts the node into graph
+    fn insert(&mut self, record: VectorRecord) -> usize {
+        let level = self.random_level();
+        let idx = self.nodes.len();
+        self.nodes.push(Node {
+            record,
+            deleted: false,
+            layers: vec![Vec::new(); level + 1],
+        });
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(idx);
+            return idx;
+        };
+        let top = self.nodes[entry].layers.len() - 1;
+        let query = self.nodes[idx].record.vector.clone();
+
+        let mut entries = vec![self.descend(&query, level).unwrap_or(entry)];
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entries, self.ef_construction, layer);
+            let neighbors: Vec<usize> = found.iter().take(self.m).map(|c| c.idx).collect();
+
+            // connect the neighbors both ways (pruning the overflowed lists):
+            for &n in &neighbors {
+                self.nodes[n].layers[layer].push(idx);
+
+                if self.nodes[n].layers[layer].len() > self.max_neighbors(layer) {
+                    let base = self.nodes[n].record.vector.clone();
+                    let mut list: Vec<Candidate> = self.nodes[n].layers[layer]
+                        .iter()
+                        .map(|&i| Candidate {
+                            dist: self.distance(&base, &self.nodes[i].record.vector),
+                            idx: i,
+                        })
+                        .collect();
+                    list.sort();
+                    list.truncate(self.max_neighbors(layer));
+                    self.nodes[n].layers[layer] = list.into_iter().map(|c| c.idx).collect();
+                }
+            }
+            self.nodes[idx].layers[layer] = neighbors;
+            entries = found.into_iter().map(|c| c.idx).collect();
+        }
+
+        if level > top {
+            self.entry = Some(idx);
+        }
+        idx
+    }
+}
+
+impl VectorIndex for HnswIndex {
+    fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    fn add(&mut self, record: VectorRecord) -> Result<()> {
+        check_dims(&mut self.dims, &record.vector)?;
+
+        // the replaced record stays in graph as removed:
+        self.remove(&record.id);
+
+        let id = record.id.clone();
+        let idx = self.insert(record);
+        self.ids.insert(id, idx);
+        Ok(())
+    }
+
+    fn remove(&mut self, id: &str) -> Option<VectorRecord> {
+        let idx = self.ids.remove(id)?;
+        self.nodes[idx].deleted = true;
+        self.deleted += 1;
+        Some(self.nodes[idx].record.clone())
+    }
+
+    fn get(&self, id: &str) -> Option<&VectorRecord> {
+        self.ids.get(id).map(|&idx| &self.nodes[idx].record)
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len() - self.deleted
+    }
+
+    fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        check_query(self.dims, query)?;
+
+        let matches =
+            |node: &Node| !node.deleted && filter.is_none_or(|f| f.matches(&node.record.metadata));
+
+        let Some(entry) = self.descend(query, 0) else {
+            return Ok(Vec::new());
+        };
+        let results: Vec<SearchResult> = self
+            .search_layer(query, &[entry], self.ef_search.max(k), 0)
+            .into_iter()
+            .filter(|c| matches(&self.nodes[c.idx]))
+            .map(|c| SearchResult::new(&self.nodes[c.idx].record, -c.dist))
+            .collect();
+
+        // the strict filters may leave too few results, so fall back to the exact search:
+        if results.len() >= k.min(self.len()) || (filter.is_none() && self.deleted == 0) {
+            return Ok(top_k(results, k));
+        }
+
+        let results = self
+            .nodes
+            .iter()
+            .filter(|node| matches(node))
+            .map(|node| {
+                SearchResult::new(&node.record, self.metric.score(query, &node.record.vector))
+            })
+            .collect();
+        Ok(top_k(results, k))
+    }
+}
@@ -0,0 +1,60 @@
+use super::{Filter, Metric, SearchResult, VectorRecord};
+use crate::prelude::*;
+
+/// The vector index
+pub trait VectorIndex: std::fmt::Debug + Send + Sync {
+    /// Returns the similarity metric
+    fn metric(&self) -> Metric;
+
+    /// Adds the record (replaces the record with the same ID)
+    fn add(&mut self, record: VectorRecord) -> Result<()>;
+
+    /// Removes the record by ID
+    fn remove(&mut self, id: &str) -> Option<VectorRecord>;
+
+    /// Returns the record by ID
+    fn get(&self, id: &str) -> Option<&VectorRecord>;
+
+    /// Returns the records count
+    fn len(&self) -> usize;
+
+    /// Returns true if the index is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the top-k closest records (matching the metadata filter), the query must have the index dimensions
+    fn search(&self, query: &[f32], k: usize, filter: Option<&Filter>)
+    -> Result<Vec<SearchResult>>;
+}
+
+/// Checks the vector dimensions (the first vector sets the index dimensions)
+pub(crate) fn check_dims(dims: &mut Option<usize>, vector: &[f32]) -> Result<()> {
+    match *dims {
+        Some(expected) if expected != vector.len() => {
+            Err(Error::InvalidVectorDims(vector.len(), expected).into())
+        }
+        Some(_) => Ok(()),
+        None => {
+            dims.replace(vector.len());
+            Ok(())
+        }
+    }
+}
+
+/// Checks the query vector dimensions (any query matches the empty index)
+pub(crate) fn check_query(dims: Option<usize>, query: &[f32]) -> Result<()> {
+    match dims {
+        Some(expected) if expected != query.len() => {
+            Err(Error::InvalidVectorDims(query.len(), expected).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sorts the results by score and keeps the top-k
+pub(crate) fn top_k(mut results: Vec<SearchResult>, k: usize) -> Vec<SearchResult> {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(k);
+    results
+}
@@ -0,0 +1,82 @@
+use crate::prelude::*;
+
+/// The lanes count of the unrolled loops (lets the compiler vectorize them)
+const LANES: usize = 8;
+
+/// The vector similarity metric
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    /// The cosine similarity
+    #[default]
+    Cosine,
+    /// The dot product
+    Dot,
+    /// The euclidean distance (the score is negative distance)
+    L2,
+}
+
+impl Metric {
+    /// Returns the similarity score (higher is closer, the vectors must have the same dimensions)
+    pub fn score(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::Cosine => {
+                let norm = (dot(a, a) * dot(b, b)).sqrt();
+                if norm > 0.0 { dot(a, b) / norm } else { 0.0 }
+            }
+            Self::Dot => dot(a, b),
+            Self::L2 => -l2_squared(a, b).sqrt(),
+        }
+    }
+}
+
+/// Returns the dot product of vectors (the indexes check the dimensions before)
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len(), "the vectors dimensions differ");
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let mut acc = [0.0f32; LANES];
+
+    let mut chunks_a = a.chunks_exact(LANES);
+    let mut chunks_b = b.chunks_exact(LANES);
+    for (x, y) in chunks_a.by_ref().zip(chunks_b.by_ref()) {
+        for i in 0..LANES {
+            acc[i] += x[i] * y[i];
+        }
+    }
+
+    let tail: f32 = chunks_a
+        .remainder()
+        .iter()
+        .zip(chunks_b.remainder())
+        .map(|(x, y)| x * y)
+        .sum();
+
+    acc.iter().sum::<f32>() + tail
+}
+
+/// Returns the squared euclidean distance of vectors (the indexes check the dimensions before)
+pub fn l2_squared(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len(), "the vectors dimensions differ");
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let mut acc = [0.0f32; LANES];
+
+    let mut chunks_a = a.chunks_exact(LANES);
+    let mut chunks_b = b.chunks_exact(LANES);
+    for (x, y) in chunks_a.by_ref().zip(chunks_b.by_ref()) {
+        for i in 0..LANES {
+            let d = x[i] - y[i];
+            acc[i] += d * d;
+        }
+    }
+
+    let tail: f32 = chunks_a
+        .remainder()
+        .iter()
+        .zip(chunks_b.remainder())
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum();
+
+    acc.iter().sum::<f32>() + tail
+}
@@ -0,0 +1,23 @@
+pub mod metric;
+pub use metric::{Metric, dot, l2_squared};
+
+pub mod filter;
+pub use filter::Filter;
+
+pub mod record;
+pub use record::{SearchResult, VectorRecord};
+
+pub mod index;
+pub use index::VectorIndex;
+pub(crate) use index::{check_dims, check_query, top_k};
+
+pub mod flat;
+pub use flat::FlatIndex;
+
+#[cfg(feature = "hnsw")]
+pub mod hnsw;
+#[cfg(feature = "hnsw")]
+pub use hnsw::HnswIndex;
+
+pub mod store;
+pub use store::VectorStore;
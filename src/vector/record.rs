@@ -0,0 +1,65 @@
+use crate::prelude::*;
+
+/// The vector index record
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VectorRecord {
+    /// The record ID
+    pub id: String,
+    /// The source text
+    pub text: String,
+    /// The custom metadata
+    #[serde(default)]
+    pub metadata: HashMap<String, JsonValue>,
+    /// The embedding vector
+    #[serde(default)]
+    pub vector: Vec<f32>,
+}
+
+impl VectorRecord {
+    /// Creates a new record (the vector is filled by `VectorStore`)
+    pub fn new(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            text: text.into(),
+            metadata: HashMap::new(),
+            vector: Vec::new(),
+        }
+    }
+
+    /// Adds the custom metadata
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the embedding vector
+    pub fn vector(mut self, vector: Vec<f32>) -> Self {
+        self.vector = vector;
+        self
+    }
+}
+
+/// The vector search result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchResult {
+    /// The record ID
+    pub id: String,
+    /// The source text
+    pub text: String,
+    /// The custom metadata
+    pub metadata: HashMap<String, JsonValue>,
+    /// The similarity score (higher is closer)
+    pub score: f32,
+}
+
+impl SearchResult {
+    /// Creates a new result from the record
+    pub(crate) fn new(record: &VectorRecord, score: f32) -> Self {
+        Self {
+            id: record.id.clone(),
+            text: record.text.clone(),
+            metadata: record.metadata.clone(),
+            score,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use super::{Filter, FlatIndex, SearchResult, VectorIndex, VectorRecord};
+use crate::{Embeddings, TaskType, Usage, prelude::*};
+
+/// The vector index with embeddings model (adds texts & queries by text)
+#[derive(Debug)]
+pub struct VectorStore<I: VectorIndex = FlatIndex> {
+    embeddings: Embeddings,
+    index: I,
+}
+
+impl<I: VectorIndex> VectorStore<I> {
+    /// Creates a new vector store
+    pub fn new(embeddings: Embeddings, index: I) -> Self {
+        Self { embeddings, index }
+    }
+
+    /// Returns the embeddings request
+    pub fn embeddings(&self) -> &Embeddings {
+        &self.embeddings
+    }
+
+    /// Returns the vector index
+    pub fn index(&self) -> &I {
+        &self.index
+    }
+
+    /// Returns the mutable vector index
+    pub fn index_mut(&mut self) -> &mut I {
+        &mut self.index
+    }
+
+    /// Returns the vector index
+    pub fn into_index(self) -> I {
+        self.index
+    }
+
+    /// Embeds the records texts (in batches) and adds them into index
+    pub async fn add_texts(&mut self, records: Vec<VectorRecord>) -> Result<Usage> {
+        if records.is_empty() {
+            return Ok(Usage::default());
+        }
+
+        let mut request = self.embeddings.clone();
+        request.input = records.iter().map(|r| r.text.clone()).collect();
+        request.task_type.get_or_insert(TaskType::RetrievalDocument);
+
+        let mut response = request.send_batched().await?;
+        let mut records: Vec<Option<VectorRecord>> = records.into_iter().map(Some).collect();
+
+        for emb in response.data.drain(..) {
+            if let Some(record) = records.get_mut(emb.index).and_then(Option::take) {
+                self.index.add(record.vector(emb.embedding))?;
+            }
+        }
+
+        Ok(response.usage)
+    }
+
+    /// Embeds the query text
+    pub async fn embed_query(&self, text: impl Into<String>) -> Result<Vec<f32>> {
+        let mut request = self.embeddings.clone();
+        request.input = vec![text.into()];
+        request.task_type.get_or_insert(TaskType::RetrievalQuery);
+
+        let response = request.send().await?;
+        response
+            .data
+            .into_iter()
+            .next()
+            .map(|emb| emb.embedding)
+            .ok_or_else(|| Error::MissingEmbeddings(str!("0")).into())
+    }
+
+    /// Returns the top-k closest records to the query text (matching the metadata filter)
+    pub async fn query(
+        &self,
+        text: impl Into<String>,
+        k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        let vector = self.embed_query(text).await?;
+        self.index.search(&vector, k, filter)
+    }
+}
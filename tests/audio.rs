@@ -0,0 +1,87 @@
+mod common;
+
+use anylm::{
+    ApiKind, AudioFormat, MockResponse, MockServer, TimestampGranularity, TranscriptionFormat,
+};
+use common::Result;
+use serde_json::json;
+
+#[tokio::test]
+async fn mock_audio() -> Result<()> {
+    // transcription with timestamps (multipart upload):
+    let server = MockServer::start(ApiKind::LmStudio).await?;
+    server.respond(MockResponse::json(json!({
+        "text": "Hello world",
+        "language": "english",
+        "duration": 1.5,
+        "segments": [{ "id": 0, "start": 0.0, "end": 1.5, "text": "Hello world" }],
+        "words": [
+            { "word": "Hello", "start": 0.0, "end": 0.6 },
+            { "word": "world", "start": 0.7, "end": 1.5 }
+        ]
+    })));
+
+    let data = server
+        .transcription("whisper-1")
+        .audio("hello.wav", b"RIFF-mock-audio".to_vec())
+        .language("en")
+        .timestamps(TimestampGranularity::Word)
+        .timestamps(TimestampGranularity::Segment)
+        .send()
+        .await?;
+
+    assert_eq!(data.text, "Hello world");
+    assert_eq!(data.duration, Some(1.5));
+    assert_eq!(data.segments.len(), 1);
+    assert_eq!(data.words[1].word, "world");
+
+    let request = server.last_request().unwrap();
+    assert_eq!(request.path, "/v1/audio/transcriptions");
+    assert!(request.headers["content-type"].starts_with("multipart/form-data"));
+    let form = String::from_utf8_lossy(&request.raw_body);
+    assert!(form.contains("name=\"response_format\"\r\n\r\nverbose_json"));
+    assert!(form.contains("name=\"timestamp_granularities[]\"\r\n\r\nword"));
+    assert!(form.contains("filename=\"hello.wav\""));
+    assert!(form.contains("RIFF-mock-audio"));
+
+    // plain text format:
+    server.respond(MockResponse::bytes("text/plain", "Hello world\n"));
+    let data = server
+        .transcription("whisper-1")
+        .audio("hello.mp3", b"ID3".to_vec())
+        .response_format(TranscriptionFormat::Text)
+        .send()
+        .await?;
+    assert_eq!(data.text, "Hello world\n");
+
+    // speech stream:
+    server.respond(MockResponse::bytes(
+        "audio/mpeg",
+        b"ID3-mock-audio".to_vec(),
+    ));
+    let audio = server
+        .speech("tts-1")
+        .input("Hello world")
+        .voice("nova")
+        .response_format(AudioFormat::Opus)
+        .send()
+        .await?
+        .bytes()
+        .await?;
+
+    assert_eq!(&audio[..], b"ID3-mock-audio");
+    let request = server.last_request().unwrap();
+    assert_eq!(request.path, "/v1/audio/speech");
+    assert_eq!(request.body["voice"], "nova");
+    assert_eq!(request.body["response_format"], "opus");
+
+    // speech error:
+    server.respond(
+        MockResponse::json(
+            json!({ "error": { "message": "Unknown voice", "type": "invalid_request_error" } }),
+        )
+        .status(400),
+    );
+    assert!(server.speech("tts-1").input("Hi").send().await.is_err());
+    Ok(())
+}
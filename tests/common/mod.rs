@@ -0,0 +1,55 @@
+//! The shared test helpers
+#![allow(dead_code)]
+
+use anylm::{AiChunk, Completions, Usage};
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+/// Reads all the response chunks
+pub async fn read_chunks(request: Completions) -> Result<Vec<AiChunk>> {
+    let mut response = request.send().await?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = response.next().await {
+        chunks.push(chunk?);
+    }
+    Ok(chunks)
+}
+
+/// Joins the text chunks
+pub fn text(chunks: &[AiChunk]) -> String {
+    chunks
+        .iter()
+        .filter_map(|c| match c {
+            AiChunk::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the tool calls (id, name, arguments)
+pub fn tools(chunks: &[AiChunk]) -> Vec<(String, String, serde_json::Value)> {
+    chunks
+        .iter()
+        .filter_map(|c| match c {
+            AiChunk::Tool { id, name, json_str } => Some((
+                id.clone(),
+                name.clone(),
+                serde_json::from_str(json_str).unwrap(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the tokens usage
+pub fn usage(chunks: &[AiChunk]) -> Option<Usage> {
+    chunks.iter().find_map(|c| match c {
+        AiChunk::Usage { usage } => Some(usage.clone()),
+        _ => None,
+    })
+}
+
+/// Encodes bytes into base64
+pub fn base64(bytes: &[u8]) -> String {
+    anylm::media::encode(bytes)
+}
@@ -0,0 +1,796 @@
+mod common;
+
+use anylm::{
+    ApiKind, Content, Message, MockResponse, MockServer, Schema, SchemaMode, Tool, ToolCall,
+    ToolChoice, Usage,
+};
+use common::{Result, read_chunks, text, tools, usage};
+use serde_json::json;
+use std::time::Duration;
+
+/// The scripted response with text, tool call and usage
+fn script() -> MockResponse {
+    MockResponse::new()
+        .text("Hello, ")
+        .text("world!")
+        .tool("call_1", "weather", json!({ "city": "Paris" }))
+        .usage(Usage {
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            ..Default::default()
+        })
+}
+
+#[tokio::test]
+async fn mock_wire_formats() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(script());
+
+        let chunks = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()]),
+        )
+        .await?;
+
+        assert_eq!(text(&chunks), "Hello, world!", "{kind:?}");
+        assert_eq!(
+            tools(&chunks),
+            vec![(
+                "call_1".into(),
+                "weather".into(),
+                json!({ "city": "Paris" })
+            )],
+            "{kind:?}"
+        );
+        assert_eq!(usage(&chunks).map(|u| u.output_tokens), Some(5), "{kind:?}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_request_body() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server
+        .respond(
+            MockResponse::new()
+                .text("Hi")
+                .expect_body(json!({ "model": "mock-model", "temperature": 0.5 })),
+        )
+        .respond(
+            MockResponse::new()
+                .text("Hi")
+                .expect_body(json!({ "model": "other" })),
+        );
+
+    let request = server
+        .completions("mock-model")
+        .temperature(0.5)
+        .user_message(vec!["Hi!".into()]);
+
+    assert_eq!(text(&read_chunks(request.clone()).await?), "Hi");
+    assert!(read_chunks(request).await.is_err());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].path, "/v1/chat/completions");
+    assert_eq!(requests[0].body["messages"][0]["content"][0]["text"], "Hi!");
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_tool_calls_history() -> Result<()> {
+    for kind in [ApiKind::OpenAI, ApiKind::Anthropic, ApiKind::Gemini] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Done"));
+
+        let request = server
+            .completions("mock-model")
+            .user_message(vec!["Weather in Paris and Rome?".into()])
+            .tool_calls_message(
+                vec![],
+                vec![
+                    ToolCall::new("call_1", "weather", r#"{"city":"Paris"}"#),
+                    ToolCall::new("call_2", "weather", r#"{"city":"Rome"}"#),
+                ],
+            )
+            .tool_message("call_1", vec!["Sunny".into()])
+            .tool_message("call_2", vec!["Rainy".into()]);
+
+        assert_eq!(text(&read_chunks(request).await?), "Done");
+
+        let body = server.last_request().unwrap().body;
+        match kind {
+            ApiKind::OpenAI => {
+                let msgs = &body["messages"];
+                assert_eq!(msgs[1]["content"], json!(null));
+                assert_eq!(msgs[1]["tool_calls"][1]["id"], "call_2");
+                assert_eq!(
+                    msgs[1]["tool_calls"][0]["function"]["arguments"],
+                    r#"{"city":"Paris"}"#
+                );
+                assert_eq!(msgs[3]["tool_call_id"], "call_2");
+            }
+            ApiKind::Anthropic => {
+                let msgs = &body["messages"];
+                assert_eq!(msgs.as_array().unwrap().len(), 3);
+                assert_eq!(msgs[1]["content"][1]["type"], "tool_use");
+                assert_eq!(msgs[1]["content"][1]["input"], json!({ "city": "Rome" }));
+                assert_eq!(msgs[2]["content"][0]["tool_use_id"], "call_1");
+                assert_eq!(msgs[2]["content"][1]["tool_use_id"], "call_2");
+            }
+            _ => {
+                let contents = &body["contents"];
+                assert_eq!(contents.as_array().unwrap().len(), 3);
+                assert_eq!(contents[1]["role"], "model");
+                assert_eq!(
+                    contents[1]["parts"][0]["functionCall"]["args"],
+                    json!({ "city": "Paris" })
+                );
+                assert_eq!(
+                    contents[2]["parts"][1]["functionResponse"]["name"],
+                    "weather"
+                );
+                assert_eq!(
+                    contents[2]["parts"][1]["functionResponse"]["response"]["result"],
+                    "Rainy"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_errors() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Hi").error("Overloaded"));
+
+        let err = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()]),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("Overloaded"), "{kind:?}: {err}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_http_errors() -> Result<()> {
+    for (kind, body) in [
+        (
+            ApiKind::OpenAI,
+            json!({
+                "error": { "message": "Incorrect API key provided", "type": "invalid_request_error" }
+            }),
+        ),
+        (
+            ApiKind::Anthropic,
+            json!({
+                "type": "error",
+                "error": { "type": "authentication_error", "message": "Incorrect API key provided" }
+            }),
+        ),
+        (
+            ApiKind::Gemini,
+            json!({
+                "error": { "code": 401, "message": "Incorrect API key provided", "status": "UNAUTHENTICATED" }
+            }),
+        ),
+    ] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::json(body).status(401));
+
+        let err = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()]),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("Incorrect API key"), "{kind:?}: {err}");
+    }
+
+    // the non-JSON error bodies keep the status:
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::bytes("text/html", "<h1>Bad Gateway</h1>").status(502));
+
+    let err = read_chunks(
+        server
+            .completions("mock-model")
+            .user_message(vec!["Hi!".into()]),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("502") && err.contains("Bad Gateway"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_timeout() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(
+        MockResponse::new()
+            .delay(Duration::from_secs(5))
+            .text("Late"),
+    );
+
+    let started = std::time::Instant::now();
+    let result = read_chunks(
+        server
+            .completions("mock-model")
+            .timeout_ms(200)
+            .user_message(vec!["Hi!".into()]),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(started.elapsed() < Duration::from_secs(3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_responses() -> Result<()> {
+    let server = MockServer::start(ApiKind::Responses).await?;
+    server.respond(script()).respond(
+        MockResponse::new()
+            .text("Sunny")
+            .expect_body(json!({ "previous_response_id": "resp_mock" })),
+    );
+
+    // the first turn with tool:
+    let request = server
+        .completions("mock-model")
+        .max_tokens(100)
+        .system_message(vec!["Be brief".into()])
+        .user_message(vec!["Weather?".into()])
+        .tool(Tool::new("weather", "Gets the weather").property(
+            "city",
+            Schema::string("The city"),
+            true,
+        ))
+        .tool_choice(ToolChoice::tool("weather"));
+
+    let mut response = request.send().await?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = response.next().await {
+        chunks.push(chunk?);
+    }
+    assert_eq!(text(&chunks), "Hello, world!");
+    assert_eq!(tools(&chunks)[0].0, "call_1");
+    assert_eq!(usage(&chunks).map(|u| u.total_tokens), Some(15));
+    assert_eq!(response.response_id().as_deref(), Some("resp_mock"));
+
+    let body = server.last_request().unwrap().body;
+    assert_eq!(server.last_request().unwrap().path, "/v1/responses");
+    assert_eq!(body["max_output_tokens"], 100);
+    assert_eq!(body["input"][0]["role"], "system");
+    assert_eq!(body["input"][1]["content"][0]["type"], "input_text");
+    assert_eq!(body["tools"][0]["name"], "weather");
+    assert_eq!(
+        body["tool_choice"],
+        json!({ "type": "function", "name": "weather" })
+    );
+    assert!(body.get("messages").is_none());
+
+    // the next turn continues the server-side conversation:
+    let request = server
+        .completions("mock-model")
+        .previous_response_id(response.response_id().unwrap())
+        .tool_message("call_1", vec!["+25C".into()]);
+    assert_eq!(text(&read_chunks(request).await?), "Sunny");
+
+    let body = server.last_request().unwrap().body;
+    assert_eq!(
+        body["input"][0],
+        json!({ "type": "function_call_output", "call_id": "call_1", "output": "+25C" })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_media_content() -> Result<()> {
+    let dir = std::env::temp_dir().join("anylm-mock-media");
+    std::fs::create_dir_all(&dir)?;
+
+    // a two pages PDF & a short WAV (16 kHz, 16 bit, mono = 32000 bytes/sec):
+    let pdf_path = dir.join("report.pdf");
+    std::fs::write(
+        &pdf_path,
+        b"%PDF-1.4\n1 0 obj << /Type /Pages /Kids [2 0 R 3 0 R] >>\n2 0 obj << /Type /Page >>\n3 0 obj << /Type/Page >>\n%%EOF",
+    )?;
+    let mut wav =
+        b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x01\0\x80\x3e\0\0\0\x7d\0\0\x02\0\x10\0data"
+            .to_vec();
+    wav.resize(32_044, 0);
+    let wav_path = dir.join("question.wav");
+    std::fs::write(&wav_path, &wav)?;
+
+    let document = Content::document_file(&pdf_path)?;
+    let audio = Content::audio_file(&wav_path)?;
+    let Content::Audio { audio: data, .. } = &audio else {
+        unreachable!()
+    };
+    assert_eq!(data.format, "wav");
+    assert!((data.duration_secs() - 1.0).abs() < 0.01);
+
+    // the invalid (non-ASCII) data doesn't break the estimate:
+    let broken: Content = serde_json::from_value(json!({
+        "type": "input_audio",
+        "input_audio": { "data": "aПривет, мир! ".repeat(8), "format": "wav" }
+    }))?;
+    let Content::Audio { audio: broken } = &broken else {
+        unreachable!()
+    };
+    assert!(broken.duration_secs() >= 0.0);
+
+    // token estimates:
+    let msg = Message::user(vec![document.clone()]);
+    assert_eq!(msg.tokens_count, 3000);
+    let msg = Message::user(vec![audio.clone()]);
+    assert_eq!(msg.tokens_count, 32);
+
+    // OpenAI format (`file` & `input_audio` parts):
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(
+        MockResponse::new()
+            .text("ok")
+            .expect_body(json!({ "messages": [{ "content": [
+                { "type": "file", "file": { "filename": "report.pdf" } },
+                { "type": "input_audio", "input_audio": { "format": "wav" } }
+            ] }] })),
+    );
+    let chunks = read_chunks(
+        server
+            .completions("gpt-4o-audio-preview")
+            .user_message(vec![document.clone(), audio.clone()]),
+    )
+    .await?;
+    assert_eq!(text(&chunks), "ok");
+
+    // Anthropic format (`document` block with base64 source):
+    let server = MockServer::start(ApiKind::Anthropic).await?;
+    server.respond(MockResponse::new().text("ok").expect_body(
+        json!({ "messages": [{ "content": [{
+                "type": "document",
+                "title": "report.pdf",
+                "source": { "type": "base64", "media_type": "application/pdf" }
+            }] }] }),
+    ));
+    let chunks = read_chunks(
+        server
+            .completions("claude-sonnet-4-5")
+            .user_message(vec![document.clone()]),
+    )
+    .await?;
+    assert_eq!(text(&chunks), "ok");
+
+    // Anthropic doesn't support audio input:
+    assert!(
+        server
+            .completions("claude-sonnet-4-5")
+            .user_message(vec![audio.clone()])
+            .send()
+            .await
+            .is_err()
+    );
+
+    // Gemini format (`inlineData` parts):
+    let server = MockServer::start(ApiKind::Gemini).await?;
+    server.respond(
+        MockResponse::new()
+            .text("ok")
+            .expect_body(json!({ "contents": [{ "parts": [
+                { "inlineData": { "mimeType": "application/pdf" } },
+                { "inlineData": { "mimeType": "audio/wav" } }
+            ] }] })),
+    );
+    let chunks = read_chunks(
+        server
+            .completions("gemini-2.5-flash")
+            .user_message(vec![document, audio]),
+    )
+    .await?;
+    assert_eq!(text(&chunks), "ok");
+    Ok(())
+}
+
+/// Returns the declared tool names of the request body
+fn tool_names(kind: &ApiKind, body: &serde_json::Value) -> Vec<String> {
+    let tools = match kind {
+        ApiKind::Gemini => &body["tools"][0]["function_declarations"],
+        _ => &body["tools"],
+    };
+    tools
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| match kind {
+            ApiKind::OpenAI => &t["function"]["name"],
+            _ => &t["name"],
+        })
+        .map(|n| n.as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn mock_schema_tool() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        // the object schema is used as the tool input as is:
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().tool(
+            "call_1",
+            "json_response",
+            json!({ "city": "Paris" }),
+        ));
+
+        let chunks = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Where?".into()])
+                .tool(Tool::new("weather", "Returns the weather"))
+                .schema(Schema::object("").required_property("city", Schema::string("")))
+                .schema_mode(SchemaMode::Tool),
+        )
+        .await?;
+
+        assert!(tools(&chunks).is_empty(), "{kind:?}");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&text(&chunks))?,
+            json!({ "city": "Paris" }),
+            "{kind:?}"
+        );
+
+        let body = server.last_request().unwrap().body;
+        assert_eq!(
+            tool_names(&kind, &body),
+            vec!["weather", "json_response"],
+            "{kind:?}"
+        );
+        assert!(body.get("response_format").is_none(), "{kind:?}");
+        assert!(body.get("output_config").is_none(), "{kind:?}");
+        assert!(body.get("text").is_none(), "{kind:?}");
+        match kind {
+            ApiKind::OpenAI => {
+                assert_eq!(body["tool_choice"]["function"]["name"], "json_response");
+            }
+            ApiKind::Anthropic => {
+                assert_eq!(
+                    body["tool_choice"],
+                    json!({ "type": "tool", "name": "json_response" })
+                );
+                assert_eq!(
+                    body["tools"][1]["input_schema"]["properties"]["city"]["type"],
+                    "string"
+                );
+            }
+            ApiKind::Gemini => {
+                assert_eq!(
+                    body["toolConfig"]["functionCallingConfig"],
+                    json!({ "mode": "ANY", "allowedFunctionNames": ["json_response"] })
+                );
+                assert!(body["generationConfig"].get("response_schema").is_none());
+            }
+            _ => {
+                assert_eq!(
+                    body["tool_choice"],
+                    json!({ "type": "function", "name": "json_response" })
+                );
+            }
+        }
+
+        // the other schemas are wrapped into the `value` property:
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().tool(
+            "call_1",
+            "json_response",
+            json!({ "value": ["Paris", "Rome"] }),
+        ));
+
+        let chunks = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Where?".into()])
+                .schema(Schema::array("").items(Schema::string("")))
+                .schema_mode(SchemaMode::Tool),
+        )
+        .await?;
+
+        assert!(tools(&chunks).is_empty(), "{kind:?}");
+        assert_eq!(text(&chunks), r#"["Paris","Rome"]"#, "{kind:?}");
+
+        let body = server.last_request().unwrap().body;
+        let params = match kind {
+            ApiKind::OpenAI => &body["tools"][0]["function"]["parameters"],
+            ApiKind::Anthropic => &body["tools"][0]["input_schema"],
+            ApiKind::Gemini => &body["tools"][0]["function_declarations"][0]["parameters"],
+            _ => &body["tools"][0]["parameters"],
+        };
+        assert_eq!(params["type"], "object", "{kind:?}");
+        assert_eq!(params["required"], json!(["value"]), "{kind:?}");
+        assert_eq!(params["properties"]["value"]["type"], "array", "{kind:?}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_tool_choice() -> Result<()> {
+    for kind in [ApiKind::Anthropic, ApiKind::Gemini, ApiKind::Responses] {
+        for choice in [ToolChoice::Auto, ToolChoice::None, ToolChoice::Required] {
+            let server = MockServer::start(kind.clone()).await?;
+            server.respond(MockResponse::new().text("Hi"));
+
+            read_chunks(
+                server
+                    .completions("mock-model")
+                    .user_message(vec!["Hi!".into()])
+                    .tool(Tool::new("weather", "Returns the weather"))
+                    .tool_choice(choice.clone()),
+            )
+            .await?;
+
+            let body = server.last_request().unwrap().body;
+            let (actual, expected) = match (&kind, &choice) {
+                (ApiKind::Anthropic, ToolChoice::Auto) => {
+                    (&body["tool_choice"], json!({ "type": "auto" }))
+                }
+                (ApiKind::Anthropic, ToolChoice::None) => {
+                    (&body["tool_choice"], json!({ "type": "none" }))
+                }
+                (ApiKind::Anthropic, _) => (&body["tool_choice"], json!({ "type": "any" })),
+                (ApiKind::Gemini, ToolChoice::Auto) => (
+                    &body["toolConfig"],
+                    json!({ "functionCallingConfig": { "mode": "AUTO" } }),
+                ),
+                (ApiKind::Gemini, ToolChoice::None) => (
+                    &body["toolConfig"],
+                    json!({ "functionCallingConfig": { "mode": "NONE" } }),
+                ),
+                (ApiKind::Gemini, _) => (
+                    &body["toolConfig"],
+                    json!({ "functionCallingConfig": { "mode": "ANY" } }),
+                ),
+                (_, ToolChoice::Auto) => (&body["tool_choice"], json!("auto")),
+                (_, ToolChoice::None) => (&body["tool_choice"], json!("none")),
+                _ => (&body["tool_choice"], json!("required")),
+            };
+            assert_eq!(actual, &expected, "{kind:?} {choice:?}");
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_logprobs_params() -> Result<()> {
+    for kind in [ApiKind::Anthropic, ApiKind::Responses] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Hi"));
+
+        let chunks = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()])
+                .logprobs(3),
+        )
+        .await?;
+        assert_eq!(text(&chunks), "Hi", "{kind:?}");
+
+        let body = server.last_request().unwrap().body;
+        if kind.is_anthropic() {
+            // the unsupported option is skipped:
+            assert_eq!(body.get("logprobs"), None);
+            assert_eq!(body.get("top_logprobs"), None);
+            assert_eq!(body.get("include"), None);
+        } else {
+            assert_eq!(body["include"], json!(["message.output_text.logprobs"]));
+            assert_eq!(body["top_logprobs"], 3);
+            assert_eq!(body.get("logprobs"), None);
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_sampling_params() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Hi"));
+
+        read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()])
+                .temperature(0.5)
+                .max_tokens(100)
+                .top_p(0.75)
+                .top_k(40)
+                .stop("END")
+                .seed(7)
+                .presence_penalty(0.25)
+                .frequency_penalty(0.125)
+                .min_p(0.0625),
+        )
+        .await?;
+
+        let mut body = server.last_request().unwrap().body;
+        for key in ["model", "messages", "contents", "input", "stream"] {
+            body.as_object_mut().unwrap().remove(key);
+        }
+
+        // the unsupported params are skipped:
+        let expected = match kind {
+            ApiKind::OpenAI => json!({
+                "temperature": 0.5,
+                "max_tokens": 100,
+                "top_p": 0.75,
+                "stop": ["END"],
+                "seed": 7,
+                "presence_penalty": 0.25,
+                "frequency_penalty": 0.125,
+            }),
+            ApiKind::Anthropic => json!({
+                "temperature": 0.5,
+                "max_tokens": 100,
+                "top_p": 0.75,
+                "top_k": 40,
+                "stop_sequences": ["END"],
+            }),
+            ApiKind::Gemini => json!({
+                "generationConfig": {
+                    "temperature": 0.5,
+                    "maxOutputTokens": 100,
+                    "topP": 0.75,
+                    "topK": 40,
+                    "stopSequences": ["END"],
+                    "seed": 7,
+                    "presencePenalty": 0.25,
+                    "frequencyPenalty": 0.125,
+                }
+            }),
+            _ => json!({
+                "temperature": 0.5,
+                "max_output_tokens": 100,
+                "top_p": 0.75,
+            }),
+        };
+        assert_eq!(body, expected, "{kind:?}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_extra_body() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::new().text("Hi"));
+
+    read_chunks(
+        server
+            .completions("mock-model")
+            .user_message(vec!["Hi!".into()])
+            .temperature(0.5)
+            .seed(7)
+            .extra_body("temperature", json!(0.0))
+            .extra_body("reasoning_effort", json!("low")),
+    )
+    .await?;
+
+    // the extra fields are merged last and override the mapped params:
+    let body = server.last_request().unwrap().body;
+    assert_eq!(body["temperature"], 0.0);
+    assert_eq!(body["seed"], 7);
+    assert_eq!(body["reasoning_effort"], "low");
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_parallel_tool_calls() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        for choice in [None, Some(ToolChoice::Required), Some(ToolChoice::None)] {
+            let server = MockServer::start(kind.clone()).await?;
+            server.respond(MockResponse::new().text("Hi"));
+
+            let mut request = server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()])
+                .tool(Tool::new("weather", "Returns the weather"))
+                .parallel_tool_calls(false);
+            if let Some(choice) = choice.clone() {
+                request = request.tool_choice(choice);
+            }
+            read_chunks(request).await?;
+
+            let body = server.last_request().unwrap().body;
+            match (&kind, &choice) {
+                (ApiKind::Anthropic, None) => assert_eq!(
+                    body["tool_choice"],
+                    json!({ "type": "auto", "disable_parallel_tool_use": true })
+                ),
+                (ApiKind::Anthropic, Some(ToolChoice::Required)) => assert_eq!(
+                    body["tool_choice"],
+                    json!({ "type": "any", "disable_parallel_tool_use": true })
+                ),
+                // the tools are disabled at all:
+                (ApiKind::Anthropic, _) => {
+                    assert_eq!(body["tool_choice"], json!({ "type": "none" }))
+                }
+                (ApiKind::Gemini, _) => {
+                    assert_eq!(body.get("parallel_tool_calls"), None);
+                    assert!(!body.to_string().contains("disable_parallel_tool_use"));
+                }
+                _ => assert_eq!(body["parallel_tool_calls"], false, "{kind:?}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_sse_content_types() -> Result<()> {
+    let events = concat!(
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"}}]}\n\n",
+        "data: [DONE]\n\n"
+    );
+
+    // some local servers send SSE with the other content types:
+    for mime in ["application/json", "text/plain", "application/x-ndjson"] {
+        let server = MockServer::start(ApiKind::OpenAI).await?;
+        server.respond(MockResponse::bytes(mime, events));
+
+        let chunks = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()]),
+        )
+        .await?;
+        assert_eq!(text(&chunks), "Hi", "{mime}");
+    }
+
+    // the successful non-SSE bodies are errors:
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::bytes("text/html", "<h1>Login</h1>"));
+
+    let err = read_chunks(
+        server
+            .completions("mock-model")
+            .user_message(vec!["Hi!".into()]),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Login"), "{err}");
+    Ok(())
+}
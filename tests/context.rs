@@ -0,0 +1,103 @@
+use anylm::{
+    ApiKind, Content, ContextStrategy, DropToolResults, KeepFirstLast, Message, MockResponse,
+    MockServer, Role, SlidingWindow, Summarize, ToolCall,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+/// Creates the dialog with a parallel tool calls round-trip
+fn dialog() -> Vec<Message> {
+    let long = "The weather report is long. ".repeat(20);
+
+    vec![
+        Message::system(vec!["You are a helpful assistant.".into()]),
+        Message::user(vec!["What's the weather in Paris and Rome?".into()]),
+        Message::tool_calls(
+            vec![],
+            vec![
+                ToolCall::new("call_1", "weather", r#"{"city":"Paris"}"#),
+                ToolCall::new("call_2", "weather", r#"{"city":"Rome"}"#),
+            ],
+        ),
+        Message::tool("call_1", vec![long.clone().into()]),
+        Message::tool("call_2", vec![long.into()]),
+        Message::assistant(vec!["Sunny in Paris, rainy in Rome.".into()]),
+        Message::user(vec!["And tomorrow?".into()]),
+    ]
+}
+
+/// Checks the tool calls are kept together with their results and the dialog starts with a user message
+fn assert_consistent(messages: &[Message]) {
+    let dialog: Vec<&Message> = messages.iter().filter(|m| !m.role.is_system()).collect();
+    assert_eq!(dialog[0].role, Role::User);
+
+    let call_ids: Vec<&str> = messages
+        .iter()
+        .flat_map(|m| m.tool_calls.iter().map(|call| call.id.as_str()))
+        .collect();
+    let result_ids: Vec<&str> = messages
+        .iter()
+        .filter_map(|m| m.tool_call_id.as_deref())
+        .collect();
+    assert_eq!(call_ids, result_ids);
+}
+
+#[tokio::test]
+async fn context_tool_round_trip() -> Result<()> {
+    let messages = dialog();
+    let total: usize = messages.iter().map(|m| m.tokens_count).sum();
+    let tools: usize = messages[2..5].iter().map(|m| m.tokens_count).sum();
+    let budget = total - tools;
+
+    // the tool calls unit is dropped whole:
+    let fitted = DropToolResults::new().fit(dialog(), budget).await?;
+    assert_consistent(&fitted);
+    assert_eq!(fitted.len(), 4);
+    assert!(fitted.iter().all(|m| m.tool_calls.is_empty()));
+
+    // the dialog never starts with the leftover assistant or tool messages:
+    let fitted = SlidingWindow::new().fit(dialog(), budget).await?;
+    assert_consistent(&fitted);
+    assert_eq!(fitted.len(), 2);
+    assert_eq!(fitted[1].role, Role::User);
+
+    // the last range cut in the middle of unit keeps it whole:
+    let mut messages = dialog();
+    messages.insert(1, Message::user(vec!["Hi!".into()]));
+    messages.insert(2, Message::assistant(vec!["Hello!".into()]));
+    let budget_first_last = total + messages[1].tokens_count;
+
+    let fitted = KeepFirstLast::new(1, 4)
+        .fit(messages, budget_first_last)
+        .await?;
+    assert_consistent(&fitted);
+    assert_eq!(fitted.len(), 7);
+    assert_eq!(fitted[2].tool_calls.len(), 2);
+
+    let fitted = KeepFirstLast::new(0, 3).fit(dialog(), budget).await?;
+    assert_consistent(&fitted);
+    assert_eq!(fitted.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn context_summarize_tool_round_trip() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::new().text("The user greeted."));
+
+    let mut messages = dialog();
+    let budget: usize = messages.iter().map(|m| m.tokens_count).sum::<usize>() + 30;
+    messages.insert(1, Message::user(vec!["Hi!".repeat(20).into()]));
+    messages.insert(2, Message::assistant(vec!["Hello!".into()]));
+
+    // the kept last 3 messages are extended to the whole tool round-trip with its question:
+    let strategy = Summarize::new(server.completions("mock-model"), 3);
+    let fitted = strategy.fit(messages, budget).await?;
+    assert_consistent(&fitted);
+    assert_eq!(fitted.len(), 8);
+    assert!(
+        matches!(&fitted[1].content[0], Content::Text { text, .. } if text.contains("The user greeted."))
+    );
+    assert_eq!(fitted[3].tool_calls.len(), 2);
+    Ok(())
+}
@@ -0,0 +1,314 @@
+mod common;
+
+use anylm::{ApiKind, MockResponse, MockServer, TaskType};
+use common::Result;
+use serde_json::json;
+
+#[tokio::test]
+async fn mock_batched_embeddings() -> Result<()> {
+    /// The embeddings response with the vectors `[n]` for `n` in range
+    fn response(range: std::ops::Range<usize>) -> MockResponse {
+        let data: Vec<_> = range
+            .enumerate()
+            .map(|(index, n)| json!({ "object": "embedding", "index": index, "embedding": [n as f32] }))
+            .collect();
+
+        MockResponse::json(json!({
+            "object": "list",
+            "data": data,
+            "model": "mock-embed",
+            "usage": { "prompt_tokens": 2, "total_tokens": 2 }
+        }))
+    }
+
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server
+        .respond(response(0..2))
+        .respond(
+            MockResponse::json(json!({ "error": { "code": 503, "message": "Busy" } })).status(503),
+        )
+        .respond(response(2..4))
+        .respond(response(4..5));
+
+    let embeddings = (0..5)
+        .fold(server.embeddings("mock-embed"), |request, n| {
+            request.input(format!("text {n}"))
+        })
+        .batch_size(2)
+        .concurrency(1)
+        .send_batched()
+        .await?;
+
+    let vectors: Vec<_> = embeddings
+        .data
+        .iter()
+        .map(|e| (e.index, e.embedding[0]))
+        .collect();
+    assert_eq!(
+        vectors,
+        vec![(0, 0.0), (1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)]
+    );
+    assert_eq!(embeddings.usage.total_tokens, 6);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[3].body["input"], json!(["text 4"]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_gemini_embeddings() -> Result<()> {
+    let server = MockServer::start(ApiKind::Gemini).await?;
+    server.respond(
+        MockResponse::json(json!({
+            "embeddings": [{ "values": [0.1, 0.2] }, { "values": [0.3, 0.4] }]
+        }))
+        .expect_body(json!({
+            "requests": [
+                {
+                    "model": "models/gemini-embedding-001",
+                    "content": { "parts": [{ "text": "Hello" }] },
+                    "taskType": "RETRIEVAL_DOCUMENT",
+                    "title": "Greetings",
+                    "outputDimensionality": 2
+                },
+                {
+                    "model": "models/gemini-embedding-001",
+                    "content": { "parts": [{ "text": "World" }] },
+                    "taskType": "RETRIEVAL_DOCUMENT",
+                    "title": "Greetings",
+                    "outputDimensionality": 2
+                }
+            ]
+        })),
+    );
+
+    let embeddings = server
+        .embeddings("gemini-embedding-001")
+        .input("Hello")
+        .input("World")
+        .task_type(TaskType::RetrievalDocument)
+        .title("Greetings")
+        .dimensions(2)
+        .send()
+        .await?;
+
+    assert_eq!(embeddings.data.len(), 2);
+    assert_eq!(embeddings.data[1].index, 1);
+    assert_eq!(embeddings.data[1].embedding, vec![0.3, 0.4]);
+    assert_eq!(
+        server.last_request().map(|r| r.path),
+        Some("/v1beta/models/gemini-embedding-001:batchEmbedContents".into())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_missing_embeddings() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::json(json!({
+        "object": "list",
+        "data": [{ "object": "embedding", "index": 1, "embedding": [0.1, 0.2] }],
+        "model": "mock-embed"
+    })));
+
+    let err = server
+        .embeddings("mock-embed")
+        .input("Hello")
+        .input("World")
+        .input("!")
+        .send()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("[0, 2]"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_embedding_options() -> Result<()> {
+    use anylm::{EncodingFormat, OutputDtype, QuantizedEmbedding, Truncation};
+    use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+
+    // OpenAI base64 vectors with Matryoshka truncation:
+    let bytes: Vec<u8> = [3.0f32, 4.0, 5.0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+
+    let server = MockServer::start(ApiKind::LmStudio).await?;
+    server.respond(
+        MockResponse::json(json!({
+            "object": "list",
+            "data": [{ "object": "embedding", "index": 0, "embedding": BASE64.encode(bytes) }],
+            "model": "mock-embed",
+            "usage": { "prompt_tokens": 1, "total_tokens": 1 }
+        }))
+        .expect_body(json!({ "dimensions": 2, "encoding_format": "base64" })),
+    );
+
+    let embeddings = server
+        .embeddings("mock-embed")
+        .input("Hello")
+        .dimensions(2)
+        .encoding_format(EncodingFormat::Base64)
+        .truncation(Truncation::End)
+        .send()
+        .await?;
+    assert_eq!(embeddings.data[0].embedding, vec![0.6, 0.8]);
+    let body = server.last_request().unwrap().body;
+    assert!(body.get("truncate").is_none());
+
+    // the Cohere-only truncation isn't sent to OpenAI:
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::json(json!({
+        "object": "list",
+        "data": [{ "object": "embedding", "index": 0, "embedding": [0.1, 0.2] }],
+        "model": "text-embedding-3-small"
+    })));
+
+    server
+        .embeddings("text-embedding-3-small")
+        .input("Hello")
+        .truncation(Truncation::End)
+        .send()
+        .await?;
+    let body = server.last_request().unwrap().body;
+    assert!(body.get("truncate").is_none());
+
+    // Voyage quantized vectors:
+    let server = MockServer::start(ApiKind::Voyage).await?;
+    server.respond(
+        MockResponse::json(json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 0, "embedding": [-128, 127] },
+            ],
+            "model": "voyage-3.5",
+            "usage": { "total_tokens": 1 }
+        }))
+        .expect_body(json!({
+            "input_type": "query",
+            "output_dtype": "binary",
+            "truncation": false
+        })),
+    );
+
+    let embeddings = server
+        .embeddings("voyage-3.5")
+        .input("Hello")
+        .task_type(TaskType::RetrievalQuery)
+        .output_dtype(OutputDtype::Binary)
+        .truncation(Truncation::None)
+        .send()
+        .await?;
+
+    let emb = &embeddings.data[0];
+    assert_eq!(
+        emb.quantized,
+        Some(QuantizedEmbedding::Binary(vec![-128, 127]))
+    );
+    assert_eq!(emb.embedding.len(), 16);
+    assert_eq!(emb.embedding[..8], [-1.0; 8]);
+    assert_eq!(emb.embedding[8..], [1.0; 8]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_cohere_embeddings() -> Result<()> {
+    use anylm::{OutputDtype, QuantizedEmbedding, Truncation};
+
+    let server = MockServer::start(ApiKind::Cohere).await?;
+    server
+        .respond(
+            MockResponse::json(json!({
+                "id": "emb_1",
+                "embeddings": { "float": [[0.1, 0.2], [0.3, 0.4]] },
+                "texts": ["Hello", "World"],
+                "meta": { "billed_units": { "input_tokens": 4 } }
+            }))
+            .expect_body(json!({
+                "model": "embed-v4.0",
+                "texts": ["Hello", "World"],
+                "input_type": "search_query",
+                "embedding_types": ["float"],
+                "truncate": "START",
+                "output_dimension": 256
+            })),
+        )
+        .respond(MockResponse::json(json!({
+            "embeddings": { "int8": [[-1, 2]] },
+            "meta": { "billed_units": { "input_tokens": 1 } }
+        })));
+
+    let embeddings = server
+        .embeddings("embed-v4.0")
+        .input("Hello")
+        .input("World")
+        .task_type(TaskType::RetrievalQuery)
+        .truncation(Truncation::Start)
+        .dimensions(256)
+        .send()
+        .await?;
+    assert_eq!(server.last_request().unwrap().path, "/v2/embed");
+    assert_eq!(embeddings.data[1].embedding, vec![0.3, 0.4]);
+    assert_eq!(embeddings.usage.total_tokens, 4);
+
+    // the quantized vectors are read by the embedding type key:
+    let embeddings = server
+        .embeddings("embed-v4.0")
+        .input("Hello")
+        .output_dtype(OutputDtype::Int8)
+        .send()
+        .await?;
+    let body = server.last_request().unwrap().body;
+    assert_eq!(body["input_type"], "search_document");
+    assert_eq!(body["embedding_types"], json!(["int8"]));
+    assert_eq!(
+        embeddings.data[0].quantized,
+        Some(QuantizedEmbedding::Int8(vec![-1, 2]))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_embeddings_retries() -> Result<()> {
+    let error =
+        |code: u16| json!({ "error": { "code": code, "message": format!("Error {code}") } });
+    let ok = json!({
+        "object": "list",
+        "data": [{ "object": "embedding", "index": 0, "embedding": [1.0] }],
+        "model": "mock-embed"
+    });
+
+    // the server errors are retried:
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server
+        .respond(MockResponse::json(error(503)).status(503))
+        .respond(MockResponse::json(ok));
+
+    let data = server
+        .embeddings("mock-embed")
+        .input("Hello")
+        .retries(3)
+        .send_batched()
+        .await?;
+    assert_eq!(data.data[0].embedding, vec![1.0]);
+    assert_eq!(server.requests().len(), 2);
+
+    // the client errors aren't retried:
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(MockResponse::json(error(401)).status(401));
+
+    let err = server
+        .embeddings("mock-embed")
+        .input("Hello")
+        .retries(3)
+        .send_batched()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("401"), "{err}");
+    assert_eq!(server.requests().len(), 1);
+    Ok(())
+}
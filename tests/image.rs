@@ -0,0 +1,78 @@
+use anylm::{ApiKind, image};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+#[test]
+fn image_tokens() {
+    // OpenAI: 1024x1024 -> 768x768 -> 2x2 tiles:
+    assert_eq!(image::openai_tokens(1024, 1024, Some("high")), 85 + 170 * 4);
+    assert_eq!(image::openai_tokens(1024, 1024, Some("low")), 85);
+    // 4096x2048 -> 2048x1024 -> 1536x768 -> 3x2 tiles:
+    assert_eq!(image::openai_tokens(4096, 2048, None), 85 + 170 * 6);
+
+    // Anthropic: width * height / 750 (after the 1568px limit):
+    assert_eq!(image::anthropic_tokens(1000, 750), 1000);
+    assert_eq!(
+        image::anthropic_tokens(3136, 1568),
+        image::anthropic_tokens(1568, 784)
+    );
+
+    // Gemini: 258 per small image or per tile (768 / 1.5 = 512px tiles, 3x2):
+    assert_eq!(image::gemini_tokens(384, 384), 258);
+    assert_eq!(image::gemini_tokens(1536, 768), 258 * 6);
+
+    assert_eq!(image::image_tokens(&ApiKind::Claude, 1000, 750, None), 1000);
+    assert_eq!(image::tokens::fit(4000, 1000, 1568, 1568), (1568, 392));
+}
+
+#[test]
+fn image_mime_sniffing() -> Result<()> {
+    let path = std::env::temp_dir().join("anylm-image-sniff.bin");
+    std::fs::write(&path, b"\x89PNG\r\n\x1a\nmock")?;
+
+    let url = image::read(&path)?;
+    assert!(url.starts_with("data:image/png;base64,"));
+    Ok(())
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn image_process() -> Result<()> {
+    use ::image::{ImageFormat as Format, RgbImage};
+    use anylm::{Content, Message, image::ImageOptions};
+
+    // a large PNG:
+    let mut png = std::io::Cursor::new(Vec::new());
+    RgbImage::from_pixel(4000, 1000, ::image::Rgb([200, 40, 40]))
+        .write_to(&mut png, Format::Png)?;
+    let png = png.into_inner();
+    assert_eq!(image::dimensions(&png)?, (4000, 1000));
+
+    // downsized to the Anthropic limits & re-encoded into JPEG:
+    let options = ImageOptions::for_kind(&ApiKind::Anthropic).quality(80);
+    let (mime, jpeg) = image::process(&png, &options)?;
+    assert_eq!(mime, "image/jpeg");
+    assert_eq!(image::dimensions(&jpeg)?, (1568, 392));
+    assert!(jpeg.len() < png.len());
+
+    // lossless WebP:
+    let (mime, webp) = image::process(&png, &options.format(image::ImageFormat::WebP))?;
+    assert_eq!(mime, "image/webp");
+    assert_eq!(image::dimensions(&webp)?, (1568, 392));
+
+    // the real tokens cost:
+    let path = std::env::temp_dir().join("anylm-image-process.png");
+    std::fs::write(&path, &png)?;
+    let content = Content::image_file_with(&path, Some("high".into()), &options)?;
+
+    let mut msg = Message::user(vec![content]);
+    assert_eq!(
+        msg.tokens_count,
+        image::openai_tokens(1568, 392, Some("high"))
+    );
+    msg.update_tokens_for(&ApiKind::Anthropic, &anylm::BpeTokenizer::default());
+    assert_eq!(msg.tokens_count, 1568 * 392 / 750);
+    msg.update_tokens_for(&ApiKind::Gemini, &anylm::BpeTokenizer::default());
+    assert_eq!(msg.tokens_count, image::gemini_tokens(1568, 392));
+    Ok(())
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use anylm::{AiChunk, ApiKind, MockResponse, MockServer};
+use common::{Result, base64, read_chunks, text};
+use serde_json::json;
+
+#[tokio::test]
+async fn mock_images() -> Result<()> {
+    let png = b"\x89PNG\r\n\x1a\nmock-image".to_vec();
+    let dir = std::env::temp_dir().join("anylm-mock-images");
+    std::fs::create_dir_all(&dir)?;
+
+    // generation (base64 & downloaded url):
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server
+        .respond(
+            MockResponse::json(json!({
+                "created": 0,
+                "data": [
+                    { "b64_json": base64(&png), "revised_prompt": "A red crab" },
+                    { "url": format!("{}/files/2.png", server.url()) }
+                ],
+                "usage": { "input_tokens": 10, "output_tokens": 100, "total_tokens": 110 }
+            }))
+            .expect_body(json!({ "prompt": "A crab", "n": 2, "size": "1024x1024" })),
+        )
+        .respond(MockResponse::bytes("image/png", png.clone()));
+
+    let data = server
+        .image_generation("gpt-image-1")
+        .prompt("A crab")
+        .n(2)
+        .size("1024x1024")
+        .send()
+        .await?;
+
+    assert_eq!(data.images.len(), 2);
+    assert_eq!(data.images[0].mime, "image/png");
+    assert_eq!(&data.images[1].bytes[..], &png[..]);
+    assert_eq!(data.images[0].revised_prompt.as_deref(), Some("A red crab"));
+    assert_eq!(data.usage.as_ref().unwrap().total_tokens, 110);
+    assert_eq!(server.requests()[0].path, "/v1/images/generations");
+    assert_eq!(server.requests()[1].path, "/files/2.png");
+
+    let paths = data.save_all(dir.join("crab"))?;
+    assert_eq!(paths[1], dir.join("crab-2.png"));
+    assert_eq!(std::fs::read(&paths[1])?, png);
+
+    // editing (multipart form):
+    server.respond(MockResponse::json(
+        json!({ "data": [{ "b64_json": base64(&png) }] }),
+    ));
+    let data = server
+        .image_generation("gpt-image-1")
+        .prompt("Add a hat")
+        .image("crab.png", png.clone())
+        .mask("mask.png", png.clone())
+        .send()
+        .await?;
+
+    assert_eq!(data.images.len(), 1);
+    let request = server.last_request().unwrap();
+    assert_eq!(request.path, "/v1/images/edits");
+    let form = String::from_utf8_lossy(&request.raw_body);
+    assert!(form.contains("name=\"prompt\"\r\n\r\nAdd a hat"));
+    assert!(form.contains("name=\"image\"; filename=\"crab.png\""));
+    assert!(form.contains("name=\"mask\"; filename=\"mask.png\""));
+
+    // Gemini image output (`inlineData` parts):
+    let server = MockServer::start(ApiKind::Gemini).await?;
+    server.respond(
+        MockResponse::new()
+            .text("Here is a crab")
+            .image("image/png", png.clone())
+            .expect_body(
+                json!({ "generationConfig": { "responseModalities": ["TEXT", "IMAGE"] } }),
+            ),
+    );
+    let chunks = read_chunks(
+        server
+            .completions("gemini-2.5-flash-image")
+            .modalities(["text", "image"])
+            .user_message(vec!["Draw a crab".into()]),
+    )
+    .await?;
+
+    assert_eq!(text(&chunks), "Here is a crab");
+    let image = chunks
+        .iter()
+        .find(|c| matches!(c, AiChunk::Image { .. }))
+        .unwrap();
+    let path = image.save_image(dir.join("gemini"))?.unwrap();
+    assert_eq!(path, dir.join("gemini.png"));
+
+    // OpenRouter image output (`images` delta):
+    let server = MockServer::start(ApiKind::OpenRouter).await?;
+    server.respond(MockResponse::new().image("image/png", png.clone()));
+    let chunks = read_chunks(server.completions("google/gemini-2.5-flash-image")).await?;
+    assert!(
+        matches!(&chunks[0], AiChunk::Image { mime, bytes } if mime == "image/png" && bytes[..] == png[..])
+    );
+    Ok(())
+}
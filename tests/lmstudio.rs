@@ -0,0 +1,94 @@
+mod common;
+
+use anylm::{AiChunk, ApiKind, LmStats, MockResponse, MockServer};
+use common::{Result, read_chunks, text};
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn mock_lmstudio() -> Result<()> {
+    let server = MockServer::start(ApiKind::LmStudio).await?;
+    let model = json!({
+        "id": "qwen3-8b", "object": "model", "type": "llm", "publisher": "qwen", "arch": "qwen3",
+        "compatibility_type": "gguf", "quantization": "Q4_K_M", "state": "loaded",
+        "max_context_length": 32768, "loaded_context_length": 8192
+    });
+    server
+        .respond(MockResponse::json(json!({
+            "object": "list",
+            "data": [model, { "id": "nomic-embed", "type": "embeddings", "state": "not-loaded" }]
+        })))
+        .respond(MockResponse::json(model))
+        .respond(
+            MockResponse::json(json!({
+                "type": "llm", "instance_id": "qwen3-8b:2", "load_time_seconds": 1.5, "status": "loaded"
+            }))
+            .expect_body(json!({ "model": "qwen3-8b", "context_length": 16384, "ttl": 600 })),
+        )
+        .respond(
+            MockResponse::json(json!({ "instance_id": "qwen3-8b:2" }))
+                .expect_body(json!({ "instance_id": "qwen3-8b:2" })),
+        );
+
+    // the models management:
+    let client = server.lmstudio();
+    let models = client.models().await?;
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].quantization, "Q4_K_M");
+    assert!(models[0].is_loaded() && !models[1].is_loaded());
+    assert_eq!(
+        client.model("qwen3-8b").await?.loaded_context_length,
+        Some(8192)
+    );
+
+    let loaded = client
+        .load("qwen3-8b", Some(16384), Some(Duration::from_secs(600)))
+        .await?;
+    assert_eq!(loaded.instance_id, "qwen3-8b:2");
+    client.unload(&loaded.instance_id).await?;
+
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "/api/v0/models",
+            "/api/v0/models/qwen3-8b",
+            "/api/v1/models/load",
+            "/api/v1/models/unload"
+        ]
+    );
+
+    // the completions stats:
+    let stats = LmStats {
+        tokens_per_second: 42.5,
+        time_to_first_token: 0.12,
+        generation_time: 0.8,
+        stop_reason: Some("eosFound".into()),
+    };
+    server.respond(
+        MockResponse::new()
+            .text("Hi")
+            .stats(stats.clone())
+            .expect_body(json!({ "ttl": 300 })),
+    );
+
+    let chunks = read_chunks(
+        server
+            .completions("qwen3-8b")
+            .stats(true)
+            .ttl(Duration::from_secs(300))
+            .user_message(vec!["Hi!".into()]),
+    )
+    .await?;
+    assert_eq!(text(&chunks), "Hi");
+    assert!(
+        chunks
+            .iter()
+            .any(|c| matches!(c, AiChunk::Stats { stats: s } if *s == stats))
+    );
+    assert_eq!(
+        server.last_request().unwrap().path,
+        "/api/v0/chat/completions"
+    );
+    Ok(())
+}
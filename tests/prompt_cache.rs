@@ -0,0 +1,173 @@
+mod common;
+
+use anylm::{ApiKind, CacheControl, Message, MockResponse, MockServer, Schema, Tool, Usage};
+use common::{Result, read_chunks, usage};
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn cache_breakpoints() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Hi"));
+
+        read_chunks(
+            server
+                .completions("mock-model")
+                .messages(vec![
+                    Message::system(vec!["Be short.".into(), "The manual...".into()])
+                        .cache_control(CacheControl::ephemeral().ttl("1h")),
+                    Message::user(vec!["The question".into()])
+                        .cache_control(CacheControl::ephemeral()),
+                ])
+                .tool(
+                    Tool::new("search", "Searches the manual")
+                        .required_property("query", Schema::string(""))
+                        .cache_control(CacheControl::ephemeral()),
+                ),
+        )
+        .await?;
+
+        let body = server.last_request().unwrap().body;
+        if kind.is_anthropic() {
+            // the breakpoint is moved onto the last message block:
+            assert_eq!(body["system"][0].get("cache_control"), None);
+            assert_eq!(
+                body["system"][1]["cache_control"],
+                json!({ "type": "ephemeral", "ttl": "1h" })
+            );
+            assert_eq!(
+                body["messages"][0]["content"][0]["cache_control"],
+                json!({ "type": "ephemeral" })
+            );
+            assert_eq!(body["messages"][0].get("cache_control"), None);
+            assert_eq!(
+                body["tools"][0]["cache_control"],
+                json!({ "type": "ephemeral" })
+            );
+        } else {
+            // the other APIs cache automatically:
+            assert!(!body.to_string().contains("cache_control"), "{kind:?}");
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn cache_params() -> Result<()> {
+    for kind in [ApiKind::OpenAI, ApiKind::Anthropic, ApiKind::Gemini] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Hi"));
+
+        read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()])
+                .prompt_cache_key("user-1")
+                .cached_content("cachedContents/abc"),
+        )
+        .await?;
+
+        // the unsupported params are skipped:
+        let body = server.last_request().unwrap().body;
+        match kind {
+            ApiKind::OpenAI => {
+                assert_eq!(body["prompt_cache_key"], "user-1");
+                assert_eq!(body.get("cachedContent"), None);
+            }
+            ApiKind::Gemini => {
+                assert_eq!(body["cachedContent"], "cachedContents/abc");
+                assert_eq!(body.get("prompt_cache_key"), None);
+            }
+            _ => {
+                assert_eq!(body.get("prompt_cache_key"), None);
+                assert_eq!(body.get("cachedContent"), None);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn cache_create_content() -> Result<()> {
+    let server = MockServer::start(ApiKind::Gemini).await?;
+    server.respond(MockResponse::json(json!({ "name": "cachedContents/abc" })));
+
+    let request = server
+        .completions("mock-model")
+        .system_message(vec!["Answer by the manual.".into()])
+        .user_message(vec!["The manual...".into()])
+        .tool(Tool::new("search", "Searches the manual"));
+
+    let name = request
+        .create_cached_content(Duration::from_secs(600))
+        .await?;
+    assert_eq!(name, "cachedContents/abc");
+
+    let req = server.last_request().unwrap();
+    assert_eq!(req.path, "/v1beta/cachedContents");
+    assert_eq!(req.body["model"], "models/mock-model");
+    assert_eq!(req.body["ttl"], "600s");
+    assert_eq!(
+        req.body["systemInstruction"],
+        json!({ "parts": [{ "text": "Answer by the manual." }] })
+    );
+    assert_eq!(req.body["contents"].as_array().unwrap().len(), 1);
+    assert_eq!(req.body["contents"][0]["parts"][0]["text"], "The manual...");
+    assert_eq!(
+        req.body["tools"][0]["function_declarations"][0]["name"],
+        "search"
+    );
+
+    // the other APIs have no cached contents:
+    let server = MockServer::start(ApiKind::Anthropic).await?;
+    assert!(
+        server
+            .completions("mock-model")
+            .create_cached_content(Duration::from_secs(600))
+            .await
+            .is_err()
+    );
+    assert!(server.requests().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn cache_usage() -> Result<()> {
+    for kind in [
+        ApiKind::OpenAI,
+        ApiKind::Anthropic,
+        ApiKind::Gemini,
+        ApiKind::Responses,
+    ] {
+        let server = MockServer::start(kind.clone()).await?;
+        server.respond(MockResponse::new().text("Hi").usage(Usage {
+            input_tokens: 100,
+            output_tokens: 5,
+            cache_read_tokens: 80,
+            cache_write_tokens: if kind.is_anthropic() { 20 } else { 0 },
+            total_tokens: 105,
+        }));
+
+        let chunks = read_chunks(
+            server
+                .completions("mock-model")
+                .user_message(vec!["Hi!".into()]),
+        )
+        .await?;
+
+        let usage = usage(&chunks).unwrap();
+        assert_eq!(usage.cache_read_tokens, 80, "{kind:?}");
+        assert_eq!(
+            usage.cache_write_tokens,
+            if kind.is_anthropic() { 20 } else { 0 },
+            "{kind:?}"
+        );
+    }
+    Ok(())
+}
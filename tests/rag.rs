@@ -0,0 +1,133 @@
+use anylm::{
+    ApiKind, MockResponse, MockServer, Tokenizer,
+    rag::{Rag, Retriever},
+    vector::{Filter, SearchResult},
+};
+use futures::future::BoxFuture;
+use std::{collections::HashMap, sync::Arc};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+/// The static passages retriever
+struct StaticRetriever(Vec<(String, String)>);
+
+impl Retriever for StaticRetriever {
+    fn retrieve<'a>(
+        &'a self,
+        _query: &'a str,
+        k: usize,
+        _filter: Option<&'a Filter>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .iter()
+                .take(k)
+                .map(|(id, text)| SearchResult {
+                    id: id.clone(),
+                    text: text.clone(),
+                    metadata: HashMap::new(),
+                    score: 1.0,
+                })
+                .collect())
+        })
+    }
+}
+
+/// The tokenizer counting a token per char
+#[derive(Debug)]
+struct CharTokenizer;
+
+impl Tokenizer for CharTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<u32> {
+        text.chars().map(u32::from).collect()
+    }
+}
+
+/// Creates the RAG helper with test passages
+fn rag() -> Rag {
+    Rag::new(Arc::new(StaticRetriever(vec![
+        (
+            "rust-book".into(),
+            "Rust was started by Graydon Hoare.".into(),
+        ),
+        ("long-doc".into(), "Very long passage. ".repeat(500)),
+        ("ferris".into(), "Ferris is the crab mascot of Rust.".into()),
+    ])))
+}
+
+/// Returns the context source IDs
+fn source_ids(sources: &[anylm::rag::Source]) -> Vec<&str> {
+    sources.iter().map(|s| s.passage.id.as_str()).collect()
+}
+
+#[tokio::test]
+async fn rag_answer_with_citations() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(
+        MockResponse::new()
+            .text("Graydon Hoare started Rust [1], ")
+            .text("its mascot is Ferris [2, 1]. [7]"),
+    );
+
+    let answer = rag()
+        .token_budget(200)
+        .send(server.completions("mock-model"), "Who started Rust?")
+        .await?;
+
+    // the long passage is over the budget:
+    assert_eq!(
+        source_ids(&answer.context.sources),
+        vec!["rust-book", "ferris"]
+    );
+
+    let prompt = server.last_request().unwrap().body["messages"][0]["content"][0]["text"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        prompt.contains("[1] Rust was started by Graydon Hoare."),
+        "{prompt}"
+    );
+    assert!(
+        prompt.contains("[2] Ferris is the crab mascot of Rust."),
+        "{prompt}"
+    );
+    assert!(prompt.ends_with("Question: Who started Rust?"), "{prompt}");
+
+    let cited: Vec<_> = answer
+        .citations
+        .iter()
+        .map(|s| (s.marker, s.passage.id.as_str()))
+        .collect();
+    assert_eq!(cited, vec![(1, "rust-book"), (2, "ferris")]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rag_model_tokenizer() -> Result<()> {
+    // both short passages fit into the budget by the cl100k tokens:
+    let context = rag().token_budget(60).context("Who started Rust?").await?;
+    assert_eq!(source_ids(&context.sources), vec!["rust-book", "ferris"]);
+
+    // the budget is counted by the completions model tokenizer:
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    let completions = server
+        .completions("mock-model")
+        .tokenizer(Arc::new(CharTokenizer));
+
+    let (_, context) = rag()
+        .token_budget(60)
+        .prepare(completions.clone(), "Who started Rust?")
+        .await?;
+    assert_eq!(source_ids(&context.sources), vec!["rust-book"]);
+
+    // the RAG tokenizer overrides it:
+    let context = rag()
+        .token_budget(60)
+        .tokenizer(Arc::new(CharTokenizer))
+        .context("Who started Rust?")
+        .await?;
+    assert_eq!(source_ids(&context.sources), vec!["rust-book"]);
+    Ok(())
+}
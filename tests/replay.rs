@@ -0,0 +1,361 @@
+mod common;
+
+use anylm::{AiChunk, Cassette, Completions, Embeddings, Usage};
+use common::{Result, read_chunks, text, tools, usage};
+use std::sync::Arc;
+
+/// Loads the cassette fixture (without the chunk delays)
+fn fixture(name: &str) -> Arc<Cassette> {
+    let path = format!("{}/tests/fixtures/{name}.json", env!("CARGO_MANIFEST_DIR"));
+    Arc::new(Cassette::replay(path).unwrap().delays(false))
+}
+
+#[tokio::test]
+async fn openai_text() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::openai("", "gpt-4o")
+            .cassette(fixture("openai-text"))
+            .user_message(vec!["Hello!".into()]),
+    )
+    .await?;
+
+    assert_eq!(text(&chunks), "Hello, world!");
+    assert_eq!(
+        usage(&chunks),
+        Some(Usage {
+            input_tokens: 12,
+            output_tokens: 4,
+            cache_read_tokens: 8,
+            cache_write_tokens: 0,
+            total_tokens: 16,
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn openai_parallel_tools() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::openai("", "gpt-4o")
+            .cassette(fixture("openai-tools"))
+            .user_message(vec!["What's the weather and time in Paris?".into()]),
+    )
+    .await?;
+
+    assert_eq!(
+        tools(&chunks),
+        vec![
+            (
+                "call_a".into(),
+                "weather".into(),
+                serde_json::json!({ "city": "Paris" })
+            ),
+            ("call_b".into(), "time".into(), serde_json::json!({})),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn openai_error() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::openai("", "gpt-4o")
+            .cassette(fixture("openai-error"))
+            .user_message(vec!["Hello!".into()]),
+    )
+    .await;
+
+    let err = chunks.unwrap_err().to_string();
+    assert!(err.contains("401"), "{err}");
+    assert!(err.contains("Incorrect API key"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn anthropic_auth_error() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::anthropic("", "claude-opus-4-6")
+            .cassette(fixture("anthropic-auth-error"))
+            .user_message(vec!["Hello!".into()]),
+    )
+    .await;
+
+    let err = chunks.unwrap_err().to_string();
+    assert!(err.contains("invalid x-api-key"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn anthropic_text_and_tool() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::anthropic("", "claude-opus-4-6")
+            .cassette(fixture("anthropic-tools"))
+            .user_message(vec!["What's the weather in Paris?".into()]),
+    )
+    .await?;
+
+    assert_eq!(text(&chunks), "Let me check the weather.");
+    assert_eq!(
+        tools(&chunks),
+        vec![(
+            "toolu_1".into(),
+            "weather".into(),
+            serde_json::json!({ "city": "Paris" })
+        )]
+    );
+    assert_eq!(
+        usage(&chunks),
+        Some(Usage {
+            input_tokens: 20,
+            output_tokens: 42,
+            cache_read_tokens: 2000,
+            cache_write_tokens: 100,
+            total_tokens: 2162,
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn gemini_text_and_tool() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::gemini("", "gemini-2.5-flash")
+            .cassette(fixture("gemini-tools"))
+            .user_message(vec!["What's the weather in Paris?".into()]),
+    )
+    .await?;
+
+    assert_eq!(text(&chunks), "Sure, checking.");
+    assert_eq!(
+        tools(&chunks),
+        vec![(
+            "call_0".into(),
+            "weather".into(),
+            serde_json::json!({ "city": "Paris" })
+        )]
+    );
+    assert_eq!(usage(&chunks).map(|u| u.cache_read_tokens), Some(4));
+    Ok(())
+}
+
+/// Returns the logprobs chunk tokens (token, logprob, top tokens)
+fn logprobs(chunks: &[AiChunk]) -> Vec<(String, f32, Vec<String>)> {
+    chunks
+        .iter()
+        .filter_map(|c| match c {
+            AiChunk::Logprobs { logprobs } => Some(logprobs),
+            _ => None,
+        })
+        .flatten()
+        .map(|l| {
+            let top = l.top_logprobs.iter().map(|t| t.token.clone()).collect();
+            (l.token.clone(), l.logprob, top)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn openai_logprobs() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::openai("", "gpt-4o")
+            .cassette(fixture("openai-logprobs"))
+            .logprobs(2)
+            .user_message(vec!["Say hi".into()]),
+    )
+    .await?;
+
+    assert_eq!(text(&chunks), "Hi!");
+    assert_eq!(
+        logprobs(&chunks),
+        vec![
+            ("Hi".into(), -0.01, vec!["Hi".into(), "Hello".into()]),
+            ("!".into(), -0.2, vec!["!".into(), ".".into()]),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn gemini_logprobs() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::gemini("", "gemini-2.5-flash")
+            .cassette(fixture("gemini-logprobs"))
+            .logprobs(2)
+            .user_message(vec!["Say hi".into()]),
+    )
+    .await?;
+
+    assert_eq!(text(&chunks), "Hi!");
+    assert_eq!(
+        logprobs(&chunks),
+        vec![
+            ("Hi".into(), -0.01, vec!["Hi".into(), "Hello".into()]),
+            ("!".into(), -0.2, vec!["!".into(), ".".into()]),
+        ]
+    );
+    Ok(())
+}
+
+/// Returns the tool call deltas (id, partial arguments)
+fn tool_deltas(chunks: &[AiChunk]) -> Vec<(&str, &str)> {
+    chunks
+        .iter()
+        .filter_map(|c| match c {
+            AiChunk::ToolDelta { id, partial_json } => Some((id.as_str(), partial_json.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn streamed_tool_deltas() -> Result<()> {
+    let request = Completions::openai("", "gpt-4o")
+        .user_message(vec!["What's the weather and time in Paris?".into()]);
+
+    // the deltas are disabled by default:
+    let chunks = read_chunks(request.clone().cassette(fixture("openai-tools"))).await?;
+    assert!(tool_deltas(&chunks).is_empty());
+
+    let chunks = read_chunks(request.cassette(fixture("openai-tools")).tool_deltas(true)).await?;
+    assert_eq!(
+        tool_deltas(&chunks),
+        vec![
+            ("call_a", r#"{"city":"#),
+            ("call_a", r#""Paris"}"#),
+            ("call_b", "{}"),
+        ]
+    );
+    assert_eq!(tools(&chunks).len(), 2);
+
+    let chunks = read_chunks(
+        Completions::anthropic("", "claude-opus-4-6")
+            .cassette(fixture("anthropic-tools"))
+            .tool_deltas(true)
+            .user_message(vec!["What's the weather in Paris?".into()]),
+    )
+    .await?;
+    assert_eq!(
+        tool_deltas(&chunks),
+        vec![("toolu_1", r#"{"city": "Par"#), ("toolu_1", r#"is"}"#)]
+    );
+    assert_eq!(
+        tools(&chunks),
+        vec![(
+            "toolu_1".into(),
+            "weather".into(),
+            serde_json::json!({ "city": "Paris" })
+        )]
+    );
+
+    // Gemini sends the whole function calls:
+    let chunks = read_chunks(
+        Completions::gemini("", "gemini-2.5-flash")
+            .cassette(fixture("gemini-tools"))
+            .tool_deltas(true)
+            .user_message(vec!["What's the weather in Paris?".into()]),
+    )
+    .await?;
+    assert!(tool_deltas(&chunks).is_empty());
+    assert_eq!(tools(&chunks).len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn embeddings() -> Result<()> {
+    let embeddings = Embeddings::openai("", "text-embedding-3-small")
+        .cassette(fixture("openai-embeddings"))
+        .input("Hello")
+        .input("World")
+        .send()
+        .await?;
+
+    assert_eq!(embeddings.data.len(), 2);
+    assert_eq!(embeddings.data[1].embedding, vec![0.4, 0.5, 0.6]);
+    assert_eq!(embeddings.usage.total_tokens, 6);
+    Ok(())
+}
+
+#[tokio::test]
+async fn replay_mismatch() -> Result<()> {
+    let request = Completions::anthropic("", "claude-opus-4-6")
+        .cassette(fixture("openai-text"))
+        .user_message(vec!["Hello!".into()]);
+
+    assert!(request.send().await.is_err());
+
+    // the request body is matched too:
+    let request = Completions::openai("", "gpt-4o")
+        .cassette(fixture("openai-text"))
+        .user_message(vec!["Bye!".into()]);
+
+    assert!(request.send().await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn anthropic_error() -> Result<()> {
+    let chunks = read_chunks(
+        Completions::anthropic("", "claude-opus-4-6")
+            .cassette(fixture("anthropic-error"))
+            .user_message(vec!["Hello!".into()]),
+    )
+    .await;
+
+    let err = chunks.unwrap_err().to_string();
+    assert!(err.contains("Overloaded"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn record_then_replay() -> Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // the local SSE server:
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let host = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 64 * 1024];
+        let _ = socket.read(&mut buf).await.unwrap();
+
+        let body = "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\ndata: [DONE]\n\n";
+        let head = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\n\r\n",
+            body.len()
+        );
+        socket.write_all(head.as_bytes()).await.unwrap();
+        socket.write_all(body.as_bytes()).await.unwrap();
+    });
+
+    let path = std::env::temp_dir().join(format!("anylm-cassette-{}.json", std::process::id()));
+    let request = Completions::lmstudio("", "local-model")
+        .host(&host)
+        .user_message(vec!["Hello!".into()]);
+
+    // record the exchange:
+    let cassette = Arc::new(Cassette::record(&path));
+    let recorded = read_chunks(request.clone().cassette(cassette.clone())).await?;
+    assert_eq!(text(&recorded), "Hi");
+
+    // the exchange is added when the SSE reader drops the body stream:
+    for _ in 0..100 {
+        if !cassette.interactions().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(cassette.interactions().len(), 1);
+    assert!(!path.exists());
+
+    // the cassette is saved when it's dropped:
+    drop(cassette);
+    assert!(path.exists());
+
+    // replay it offline:
+    let replayed =
+        read_chunks(request.cassette(Arc::new(Cassette::replay(&path)?.delays(false)))).await?;
+    assert_eq!(text(&replayed), "Hi");
+
+    std::fs::remove_file(path).ok();
+    Ok(())
+}
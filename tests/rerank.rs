@@ -0,0 +1,73 @@
+mod common;
+
+use anylm::{ApiKind, MockResponse, MockServer};
+use common::Result;
+use serde_json::json;
+
+#[tokio::test]
+async fn mock_rerank() -> Result<()> {
+    // Voyage format (`data` list, `top_k` param):
+    let server = MockServer::start(ApiKind::Voyage).await?;
+    server.respond(
+        MockResponse::json(json!({
+            "object": "list",
+            "data": [
+                { "index": 1, "relevance_score": 0.9 },
+                { "index": 0, "relevance_score": 0.2 }
+            ],
+            "model": "rerank-2",
+            "usage": { "total_tokens": 12 }
+        }))
+        .expect_body(json!({
+            "query": "crab",
+            "documents": ["Cats", "Ferris the crab"],
+            "top_k": 2,
+            "return_documents": true
+        })),
+    );
+
+    let reranked = server
+        .rerank("rerank-2")
+        .query("crab")
+        .documents(["Cats", "Ferris the crab"])
+        .top_n(2)
+        .return_documents(true)
+        .send()
+        .await?;
+
+    assert_eq!(reranked.results[0].index, 1);
+    assert_eq!(
+        reranked.results[0].document.as_deref(),
+        Some("Ferris the crab")
+    );
+    assert_eq!(reranked.usage.total_tokens, 12);
+    assert_eq!(server.last_request().unwrap().path, "/v1/rerank");
+
+    // Cohere format (`results` list, unsorted, `top_n` param):
+    let server = MockServer::start(ApiKind::Cohere).await?;
+    server.respond(
+        MockResponse::json(json!({
+            "id": "mock",
+            "results": [
+                { "index": 0, "relevance_score": 0.1 },
+                { "index": 2, "relevance_score": 0.7 },
+                { "index": 1, "relevance_score": 0.3 }
+            ]
+        }))
+        .expect_body(json!({ "top_n": 2 })),
+    );
+
+    let reranked = server
+        .rerank("rerank-v3.5")
+        .query("crab")
+        .documents(["a", "b", "c"])
+        .top_n(2)
+        .send()
+        .await?;
+
+    let order: Vec<_> = reranked.results.iter().map(|r| r.index).collect();
+    assert_eq!(order, vec![2, 1]);
+    assert_eq!(reranked.results[0].document, None);
+    assert_eq!(server.last_request().unwrap().path, "/v2/rerank");
+    Ok(())
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use anylm::{ApiKind, DiskCache, MemoryCache, MockResponse, MockServer, ResponseCache, Usage};
+use common::{Result, text, tools, usage};
+use serde_json::json;
+use std::sync::Arc;
+
+/// The scripted response with text, tool call and usage
+fn script() -> MockResponse {
+    MockResponse::new()
+        .text("Hello!")
+        .tool("call_1", "weather", json!({ "city": "Paris" }))
+        .usage(Usage {
+            input_tokens: 3,
+            output_tokens: 2,
+            total_tokens: 5,
+            ..Default::default()
+        })
+}
+
+#[tokio::test]
+async fn cache_completions_hit() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server.respond(script()).respond(script());
+
+    let cache = Arc::new(MemoryCache::new(10));
+    let request = server
+        .completions("mock-model")
+        .response_cache(cache.clone())
+        .user_message(vec!["Hi!".into()]);
+
+    // the first request is sent and cached:
+    let mut response = request.send().await?;
+    assert!(!response.is_cached());
+    let mut first = Vec::new();
+    while let Some(chunk) = response.next().await {
+        first.push(chunk?);
+    }
+    assert_eq!(cache.len(), 1);
+
+    // the same request is replayed from cache without the HTTP call:
+    let mut response = request.send().await?;
+    assert!(response.is_cached());
+    let mut second = Vec::new();
+    while let Some(chunk) = response.next().await {
+        second.push(chunk?);
+    }
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(text(&second), text(&first));
+    assert_eq!(tools(&second), tools(&first));
+    assert_eq!(usage(&second), usage(&first));
+
+    // the different body has a different key:
+    let mut response = request.clone().temperature(0.1).send().await?;
+    assert!(!response.is_cached());
+    while response.next().await.is_some() {}
+    assert_eq!(server.requests().len(), 2);
+    assert_eq!(cache.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn cache_memory_lru() -> Result<()> {
+    let cache = MemoryCache::new(2);
+    cache.put("a", json!(1)).await?;
+    cache.put("b", json!(2)).await?;
+
+    // the read entry becomes recently used, so the other one is evicted:
+    assert_eq!(cache.get("a").await?, Some(json!(1)));
+    cache.put("c", json!(3)).await?;
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("b").await?, None);
+    assert_eq!(cache.get("a").await?, Some(json!(1)));
+    assert_eq!(cache.get("c").await?, Some(json!(3)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn cache_disk_round_trip() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("anylm-cache-{}", std::process::id()));
+    DiskCache::new(&dir)
+        .put("key", json!({ "text": "Hi" }))
+        .await?;
+
+    // the other instance reads the saved entry:
+    let cache = DiskCache::new(&dir);
+    assert_eq!(cache.get("key").await?, Some(json!({ "text": "Hi" })));
+    assert_eq!(cache.get("missing").await?, None);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn cache_embeddings_per_input() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server
+        .respond(MockResponse::json(json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 0, "embedding": [1.0, 0.0] },
+                { "object": "embedding", "index": 1, "embedding": [0.0, 1.0] }
+            ],
+            "model": "mock-embed"
+        })))
+        .respond(
+            MockResponse::json(json!({
+                "object": "list",
+                "data": [{ "object": "embedding", "index": 0, "embedding": [0.5, 0.5] }],
+                "model": "mock-embed"
+            }))
+            .expect_body(json!({ "input": ["Crab"] })),
+        );
+
+    let cache = Arc::new(MemoryCache::new(10));
+    let embeddings = server
+        .embeddings("mock-embed")
+        .response_cache(cache.clone());
+
+    embeddings
+        .clone()
+        .input("Hello")
+        .input("World")
+        .send()
+        .await?;
+    assert_eq!(cache.len(), 2);
+
+    // only the missing input is sent:
+    let data = embeddings
+        .input("World")
+        .input("Crab")
+        .input("Hello")
+        .send()
+        .await?;
+    assert_eq!(data.cache_hits, 2);
+    assert_eq!(server.requests().len(), 2);
+
+    let vectors: Vec<_> = data.data.iter().map(|e| e.embedding.clone()).collect();
+    assert_eq!(
+        vectors,
+        vec![vec![0.0, 1.0], vec![0.5, 0.5], vec![1.0, 0.0]]
+    );
+    Ok(())
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use anylm::{Content, Conversation, ConversationStore, FileStore, MemoryStore, Role, ToolCall};
+use common::{Result, base64};
+
+/// Returns the conversation with a tool calls turn and non-text content
+fn conversation() -> Result<Conversation> {
+    let mut conv = Conversation::new()
+        .metadata("user", "alice")
+        .system_message(vec!["Be short.".into()])
+        .user_message(vec![
+            "What's on the picture and in the file?".into(),
+            Content::image_url(
+                format!("data:image/png;base64,{}", base64(b"png")),
+                Some("high".into()),
+            )?,
+            Content::document_url(
+                format!("data:application/pdf;base64,{}", base64(b"%PDF-1.4")),
+                Some("doc.pdf".into()),
+            )?,
+            Content::audio(base64(b"RIFF"), "wav")?,
+        ])
+        .assistant_message(vec!["A cat and a report.".into()]);
+
+    conv.add_user_message(vec!["Weather in Paris?".into()]);
+    conv.add_tool_calls_message(
+        vec![],
+        vec![ToolCall::new("call_1", "weather", r#"{"city":"Paris"}"#)],
+    );
+    conv.add_tool_message("call_1", vec!["Sunny".into()]);
+    conv.add_assistant_message(vec!["It's sunny.".into()]);
+    Ok(conv)
+}
+
+#[tokio::test]
+async fn conversation_fork_branch() -> Result<()> {
+    let conv = conversation()?;
+
+    let mut fork = conv.fork();
+    assert_ne!(fork.id, conv.id);
+    assert_eq!(fork.parent_id.as_deref(), Some(conv.id.as_str()));
+    assert_eq!(fork.messages, conv.messages);
+    assert_eq!(fork.metadata, conv.metadata);
+
+    // the fork changes don't touch the original:
+    fork.add_user_message(vec!["And in Rome?".into()]);
+    assert_eq!(fork.len(), conv.len() + 1);
+    assert_eq!(conv.last().unwrap().role, Role::Assistant);
+
+    let mut branch = conv.branch(3);
+    assert_eq!(branch.parent_id.as_deref(), Some(conv.id.as_str()));
+    assert_eq!(branch.messages, conv.messages[..3]);
+
+    branch.add_user_message(vec!["Tell me more.".into()]);
+    assert_eq!(conv.messages[3].role, Role::User);
+    assert_ne!(conv.messages[3], branch.messages[3]);
+    assert_eq!(conv.branch(100).len(), conv.len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn conversation_undo() -> Result<()> {
+    let mut conv = conversation()?;
+    let len = conv.len();
+
+    // the whole turn with the tool calls and results is removed:
+    let removed = conv.undo();
+    assert_eq!(removed.len(), 4);
+    assert_eq!(removed[0].role, Role::User);
+    assert_eq!(removed[1].tool_calls[0].id, "call_1");
+    assert_eq!(removed[2].tool_call_id.as_deref(), Some("call_1"));
+    assert_eq!(conv.len(), len - 4);
+    assert_eq!(conv.last().unwrap().role, Role::Assistant);
+
+    assert_eq!(conv.undo().len(), 2);
+    assert_eq!(conv.len(), 1);
+
+    // there is no user turn left:
+    assert!(conv.undo().is_empty());
+    assert_eq!(conv.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn conversation_round_trip() -> Result<()> {
+    let conv = conversation()?;
+
+    assert_eq!(Conversation::from_json(&conv.to_json()?)?, conv);
+    assert_eq!(Conversation::from_jsonl(&conv.to_jsonl()?)?, conv);
+
+    // the JSONL file is appended by messages:
+    let path = std::env::temp_dir().join(format!("anylm-conv-{}.jsonl", std::process::id()));
+    conv.branch(3).save_jsonl(&path)?;
+    Conversation::append_jsonl(&path, &conv.messages[3..])?;
+
+    let loaded = Conversation::load_jsonl(&path)?;
+    assert_eq!(loaded.messages, conv.messages);
+    assert_eq!(loaded.metadata, conv.metadata);
+    assert_eq!(loaded.last().unwrap().tool_calls, Vec::<ToolCall>::new());
+    assert_eq!(loaded.messages[4].tool_calls[0].name, "weather");
+    assert!(matches!(
+        loaded.messages[1].content[3],
+        Content::Audio { .. }
+    ));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn storage_memory() -> Result<()> {
+    let store = MemoryStore::new();
+    let mut conv = conversation()?;
+    let other = Conversation::with_id("other");
+
+    store.save(&conv).await?;
+    store.save(&other).await?;
+    assert_eq!(store.load(&conv.id).await?, Some(conv.clone()));
+
+    let mut ids = vec![conv.id.clone(), other.id.clone()];
+    ids.sort();
+    assert_eq!(store.list().await?, ids);
+
+    // the saving replaces the conversation:
+    conv.undo();
+    store.save(&conv).await?;
+    assert_eq!(
+        store.load(&conv.id).await?.map(|c| c.len()),
+        Some(conv.len())
+    );
+
+    store.delete(&conv.id).await?;
+    assert_eq!(store.load(&conv.id).await?, None);
+    assert_eq!(store.list().await?, vec![other.id]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn storage_file() -> Result<()> {
+    for jsonl in [false, true] {
+        let dir =
+            std::env::temp_dir().join(format!("anylm-storage-{}-{jsonl}", std::process::id()));
+        let store = if jsonl {
+            FileStore::jsonl(&dir)
+        } else {
+            FileStore::json(&dir)
+        };
+
+        // the missing directory is an empty storage:
+        assert!(store.list().await?.is_empty());
+        assert_eq!(store.load("missing").await?, None);
+
+        let mut conv = conversation()?;
+        store.save(&conv).await?;
+        store.save(&Conversation::with_id("other")).await?;
+        assert_eq!(store.load(&conv.id).await?, Some(conv.clone()));
+
+        let mut ids = vec![conv.id.clone(), "other".to_string()];
+        ids.sort();
+        assert_eq!(store.list().await?, ids);
+
+        conv.undo();
+        store.save(&conv).await?;
+        assert_eq!(store.load(&conv.id).await?, Some(conv.clone()));
+
+        store.delete(&conv.id).await?;
+        store.delete(&conv.id).await?;
+        assert_eq!(store.load(&conv.id).await?, None);
+        assert_eq!(store.list().await?, vec!["other".to_string()]);
+
+        std::fs::remove_dir_all(&dir)?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn storage_file_ids() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("anylm-storage-ids-{}", std::process::id()));
+    let store = FileStore::json(&dir);
+
+    let mut conv = Conversation::new().user_message(vec!["Hi!".into()]);
+    store.save(&conv).await?;
+    assert_eq!(store.list().await?, vec![conv.id.clone()]);
+    assert!(store.load(&conv.id).await?.is_some());
+
+    // the IDs leaving the storage directory are rejected:
+    for id in ["", "../../etc/x", "a/b", "a\\b", "..", "a\0b"] {
+        assert!(store.load(id).await.is_err(), "load {id:?}");
+        assert!(store.delete(id).await.is_err(), "delete {id:?}");
+
+        conv.id = id.to_string();
+        assert!(store.save(&conv).await.is_err(), "save {id:?}");
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anylm::{
+    count_tokens,
+    text::{TextChunk, TextSplitter},
+};
+
+/// Checks the chunks limits & source offsets
+fn check(source: &str, chunks: &[TextChunk], max_tokens: usize) {
+    assert!(!chunks.is_empty());
+    for chunk in chunks {
+        assert_eq!(&source[chunk.start..chunk.end], chunk.text);
+        assert_eq!(chunk.tokens, count_tokens(&chunk.text));
+        assert!(
+            chunk.tokens <= max_tokens,
+            "{} > {max_tokens}: {:?}",
+            chunk.tokens,
+            chunk.text
+        );
+    }
+}
+
+#[test]
+fn recursive_split_with_overlap() {
+    let source = "Rust is a general-purpose programming language. ".repeat(40)
+        + "\n\n"
+        + &"Ferris the crab is its unofficial mascot! ".repeat(40);
+
+    let chunks = TextSplitter::recursive(50).split(&source);
+    check(&source, &chunks, 50);
+
+    // the chunks cover the source without gaps (except whitespaces):
+    for pair in chunks.windows(2) {
+        assert!(source[pair[0].end..pair[1].start].trim().is_empty());
+    }
+
+    // the overlapped chunks share text:
+    let overlapped = TextSplitter::recursive(50).overlap(15).split(&source);
+    check(&source, &overlapped, 50);
+    assert!(overlapped.len() > chunks.len());
+    assert!(
+        overlapped
+            .windows(2)
+            .all(|pair| pair[1].start < pair[0].end)
+    );
+}
+
+#[test]
+fn recursive_split_long_word() {
+    let source = "x".repeat(5000) + " 日本語のテキスト".repeat(200).as_str();
+    let chunks = TextSplitter::recursive(20).split(&source);
+    check(&source, &chunks, 20);
+}
+
+#[test]
+fn sentence_split() {
+    let source = "Hello there! How are you? I'm fine (thanks). Bye.";
+    let chunks = TextSplitter::sentence(12).split(source);
+    check(source, &chunks, 12);
+
+    // the chunks consist of whole sentences:
+    assert!(chunks.len() > 1 && chunks.len() < 4);
+    assert!(chunks.iter().all(|c| c.text.ends_with(['.', '!', '?'])));
+    assert_eq!(
+        chunks[0].text.split_once(' ').map(|(a, _)| a),
+        Some("Hello")
+    );
+}
+
+#[test]
+fn markdown_split() {
+    let source = "\
+Intro text.
+
+# Install
+
+Run the command:
+
+```sh
+# not a heading
+cargo add anylm
+```
+
+## Features
+
+Streams and tools.
+
+# Usage
+
+Call the API.
+";
+    let chunks = TextSplitter::markdown(100).split(source);
+    check(source, &chunks, 100);
+
+    let headings: Vec<_> = chunks.iter().map(|c| c.heading.as_deref()).collect();
+    assert_eq!(
+        headings,
+        vec![
+            None,
+            Some("Install"),
+            Some("Install > Features"),
+            Some("Usage")
+        ]
+    );
+    assert!(chunks[1].text.contains("cargo add anylm"));
+}
+
+#[test]
+fn code_split() {
+    let source = "\
+use std::io;
+
+fn first() {
+    let a = 1;
+
+    println!(\"{a}\");
+}
+fn second() {
+    println!(\"second\");
+}
+";
+    let chunks = TextSplitter::code(20).split(source);
+    check(source, &chunks, 20);
+
+    // the functions aren't split by the inner blank line:
+    assert!(
+        chunks
+            .iter()
+            .any(|c| c.text.starts_with("fn first()") && c.text.ends_with('}'))
+    );
+    assert!(chunks.iter().any(|c| c.text.starts_with("fn second()")));
+}
@@ -0,0 +1,50 @@
+use anylm::{ApiKind, BpeTokenizer, Tokenizer, model_family, tokenizer_for};
+
+#[test]
+fn tokenizer_routing() {
+    assert_eq!(
+        model_family("meta-llama/Llama-3.1-8B-Instruct"),
+        Some("llama")
+    );
+    assert_eq!(model_family("qwen/qwen3-8b"), Some("qwen"));
+    assert_eq!(model_family("mistralai/Mixtral-8x7B"), Some("mistral"));
+    assert_eq!(model_family("claude-sonnet-4-5"), None);
+
+    // the OpenAI models use their encodings:
+    let text = "Hello, world! Привет, мир!";
+    assert_eq!(
+        tokenizer_for(&ApiKind::OpenAI, "gpt-4o").count_tokens(text),
+        BpeTokenizer::O200k.count_tokens(text)
+    );
+
+    // the unknown models use the cl100k approximation:
+    assert_eq!(
+        tokenizer_for(&ApiKind::Claude, "claude-sonnet-4-5").count_tokens(text),
+        BpeTokenizer::Cl100k.count_tokens(text)
+    );
+}
+
+#[cfg(feature = "tokenizers")]
+#[test]
+fn tokenizer_hf_registry() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use anylm::HfTokenizer;
+
+    let json = r#"{
+        "version": "1.0", "truncation": null, "padding": null, "added_tokens": [],
+        "normalizer": null, "pre_tokenizer": { "type": "Whitespace" },
+        "post_processor": null, "decoder": null,
+        "model": { "type": "WordLevel", "vocab": { "hello": 0, "world": 1, "[UNK]": 2 }, "unk_token": "[UNK]" }
+    }"#;
+    HfTokenizer::from_bytes(json)?.register("gemma");
+
+    // the local models are routed by family:
+    let tokenizer = tokenizer_for(&ApiKind::LmStudio, "google/gemma-3-4b");
+    assert_eq!(tokenizer.tokenize("hello world again"), vec![0, 1, 2]);
+
+    // the family registered after a missed lookup is used too:
+    assert!(HfTokenizer::for_model("qwen2.5-7b").is_none());
+    HfTokenizer::from_bytes(json)?.register("qwen");
+    let tokenizer = tokenizer_for(&ApiKind::LmStudio, "qwen2.5-7b");
+    assert_eq!(tokenizer.tokenize("world hello"), vec![1, 0]);
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use anylm::{
+    ApiKind, MockResponse, MockServer,
+    vector::{Filter, FlatIndex, Metric, VectorIndex, VectorRecord, VectorStore},
+};
+use serde_json::json;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+/// Returns the deterministic pseudo-random vectors
+fn vectors(count: usize, dims: usize) -> Vec<Vec<f32>> {
+    let mut seed = 42u64;
+    (0..count)
+        .map(|_| {
+            (0..dims)
+                .map(|_| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    ((seed >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Fills the index with the records (`lang` metadata alternates)
+fn fill(index: &mut impl VectorIndex, vectors: &[Vec<f32>]) -> Result<()> {
+    for (i, vector) in vectors.iter().enumerate() {
+        index.add(
+            VectorRecord::new(format!("doc-{i}"), format!("text {i}"))
+                .metadata("lang", if i % 2 == 0 { "en" } else { "de" })
+                .metadata("year", 2000 + i)
+                .vector(vector.clone()),
+        )?;
+    }
+    Ok(())
+}
+
+#[test]
+fn flat_search_and_filter() -> Result<()> {
+    let data = vectors(100, 16);
+    let mut index = FlatIndex::new(Metric::Cosine);
+    fill(&mut index, &data)?;
+
+    let results = index.search(&data[7], 3, None)?;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].id, "doc-7");
+    assert!((results[0].score - 1.0).abs() < 1e-5);
+
+    let filter = Filter::eq("lang", "en").and(Filter::gte("year", 2050));
+    let results = index.search(&data[7], 5, Some(&filter))?;
+    assert_eq!(results.len(), 5);
+    assert!(
+        results
+            .iter()
+            .all(|r| r.metadata["lang"] == "en" && r.metadata["year"].as_u64() >= Some(2050))
+    );
+
+    // wrong dimensions & removal:
+    assert!(
+        index
+            .add(VectorRecord::new("bad", "").vector(vec![1.0]))
+            .is_err()
+    );
+    assert!(index.search(&[1.0], 1, None).is_err());
+    assert!(index.remove("doc-7").is_some());
+    assert_eq!(index.len(), 99);
+    assert_ne!(index.search(&data[7], 1, None)?[0].id, "doc-7");
+    assert_eq!(
+        index.get("doc-99").map(|r| r.text.as_str()),
+        Some("text 99")
+    );
+    Ok(())
+}
+
+#[test]
+fn flat_save_load() -> Result<()> {
+    let data = vectors(10, 8);
+    let mut index = FlatIndex::new(Metric::L2);
+    fill(&mut index, &data)?;
+
+    let path = std::env::temp_dir().join(format!("anylm-index-{}.json", std::process::id()));
+    index.save(&path)?;
+    let loaded = FlatIndex::load(&path)?;
+    std::fs::remove_file(path).ok();
+
+    assert_eq!(loaded.len(), 10);
+    assert_eq!(loaded.get("doc-3"), index.get("doc-3"));
+    assert_eq!(
+        loaded.search(&data[3], 2, None)?,
+        index.search(&data[3], 2, None)?
+    );
+
+    // the edited record with other dimensions isn't loaded:
+    let mut edited: serde_json::Value = serde_json::from_str(&index.to_json()?)?;
+    edited["records"][4]["vector"] = json!([1.0, 2.0]);
+    assert!(FlatIndex::from_json(&edited.to_string()).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "hnsw")]
+#[test]
+fn hnsw_recall() -> Result<()> {
+    use anylm::vector::HnswIndex;
+
+    let data = vectors(1000, 32);
+    let mut exact = FlatIndex::new(Metric::Cosine);
+    let mut approx = HnswIndex::new(Metric::Cosine);
+    fill(&mut exact, &data)?;
+    fill(&mut approx, &data)?;
+
+    // compare the top-10 of queries:
+    let mut found = 0;
+    for query in data.iter().step_by(50) {
+        let expected: Vec<_> = exact
+            .search(query, 10, None)?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        found += approx
+            .search(query, 10, None)?
+            .iter()
+            .filter(|r| expected.contains(&r.id))
+            .count();
+    }
+    assert!(found >= 180, "recall {found}/200");
+
+    // filtered search, removal & reload:
+    let filter = Filter::eq("lang", "de");
+    let results = approx.search(&data[1], 10, Some(&filter))?;
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(|r| r.metadata["lang"] == "de"));
+
+    approx.remove("doc-1");
+    assert_eq!(approx.len(), 999);
+    let loaded = HnswIndex::from_json(&approx.to_json()?)?;
+    assert!(loaded.get("doc-1").is_none());
+    assert_ne!(loaded.search(&data[1], 1, None)?[0].id, "doc-1");
+
+    // the edited record with other dimensions isn't loaded:
+    let mut edited: serde_json::Value = serde_json::from_str(&approx.to_json()?)?;
+    edited["nodes"][7]["record"]["vector"] = json!([1.0, 2.0]);
+    assert!(HnswIndex::from_json(&edited.to_string()).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_add_and_query() -> Result<()> {
+    let server = MockServer::start(ApiKind::OpenAI).await?;
+    server
+        .respond(MockResponse::json(json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 0, "embedding": [1.0, 0.0] },
+                { "object": "embedding", "index": 1, "embedding": [0.0, 1.0] },
+                { "object": "embedding", "index": 2, "embedding": [0.7, 0.7] }
+            ],
+            "model": "mock-embed",
+            "usage": { "prompt_tokens": 6, "total_tokens": 6 }
+        })))
+        .respond(MockResponse::json(json!({
+            "object": "list",
+            "data": [{ "object": "embedding", "index": 0, "embedding": [0.1, 0.9] }],
+            "model": "mock-embed",
+            "usage": { "prompt_tokens": 2, "total_tokens": 2 }
+        })));
+
+    let mut store = VectorStore::new(server.embeddings("mock-embed"), FlatIndex::default());
+    let usage = store
+        .add_texts(vec![
+            VectorRecord::new("a", "Cats").metadata("kind", "animal"),
+            VectorRecord::new("b", "Rust").metadata("kind", "language"),
+            VectorRecord::new("c", "Crabs").metadata("kind", "animal"),
+        ])
+        .await?;
+    assert_eq!(usage.total_tokens, 6);
+
+    let results = store
+        .query("Ferris", 2, Some(&Filter::eq("kind", "animal")))
+        .await?;
+    let ids: Vec<_> = results.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, vec!["c", "a"]);
+    assert_eq!(
+        server.last_request().unwrap().body["input"],
+        json!(["Ferris"])
+    );
+
+    // the empty response & wrong query dimensions are errors:
+    server
+        .respond(MockResponse::json(json!({ "object": "list", "data": [] })))
+        .respond(MockResponse::json(json!({
+            "object": "list",
+            "data": [{ "object": "embedding", "index": 0, "embedding": [0.1, 0.2, 0.3] }]
+        })));
+    assert!(store.query("Ferris", 2, None).await.is_err());
+
+    let err = store
+        .query("Ferris", 2, None)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("3 dimensions"), "{err}");
+    Ok(())
+}